@@ -2,4 +2,4 @@ pub mod profiles;
 
 pub use crate::types::AgentProfile; // Re-export as public
 
-pub use profiles::get_default_agents;
+pub use profiles::{get_default_agents, with_env_overrides};
@@ -1,11 +1,13 @@
+use crate::llm::default_provider;
 use crate::types::AgentProfile;
 
 pub fn get_default_agents() -> Vec<AgentProfile> {
-    vec![
+    [
         AgentProfile {
             name: "General Assistant".to_string(),
             description: "Balanced for everyday tasks.".to_string(),
             model: "gemini-2.5-flash".to_string(),
+            provider: default_provider(),
             research_mode: "Smart Hybrid".to_string(),
             context_mode: "General".to_string(),
             avatar_path: "bot.png".to_string(),
@@ -15,6 +17,7 @@ pub fn get_default_agents() -> Vec<AgentProfile> {
             name: "Bevy Architect".to_string(),
             description: "Expert in Bevy 0.18 (Future).".to_string(),
             model: "gemini-2.5-pro".to_string(),
+            provider: default_provider(),
             research_mode: "Smart Hybrid".to_string(),
             context_mode: "Bevy 0.18 (Future)".to_string(),
             avatar_path: "bevy.png".to_string(),
@@ -24,6 +27,7 @@ pub fn get_default_agents() -> Vec<AgentProfile> {
             name: "Pokemon Professor".to_string(),
             description: "Fast responses for pokedex queries.".to_string(),
             model: "gemini-2.5-flash".to_string(),
+            provider: default_provider(),
             research_mode: "Fast".to_string(),
             context_mode: "Pokemon Gen9".to_string(),
             avatar_path: "pokemon.png".to_string(),
@@ -33,10 +37,67 @@ pub fn get_default_agents() -> Vec<AgentProfile> {
             name: "Deep Researcher".to_string(),
             description: "Thorough web search and verification.".to_string(),
             model: "gemini-2.5-pro".to_string(),
+            provider: default_provider(),
             research_mode: "Deep Research".to_string(),
             context_mode: "General".to_string(),
             avatar_path: "research.png".to_string(),
             system_prompt: "You are a Deep Research Specialist. Your goal is to find, verify, and synthesize information from multiple sources.".to_string(),
         },
     ]
+    .into_iter()
+    .map(with_env_overrides)
+    .collect()
+}
+
+/// `profile` with the provider and model its environment picks, e.g. `AXIOM_BEVY_ARCHITECT_PROVIDER`
+/// and `AXIOM_BEVY_ARCHITECT_MODEL` for "Bevy Architect".
+pub fn with_env_overrides(mut profile: AgentProfile) -> AgentProfile {
+    let prefix = env_prefix(&profile.name);
+    let var = |key: &str| {
+        std::env::var(format!("{}_{}", prefix, key))
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    if let Some(provider) = var("PROVIDER") {
+        profile.provider = provider.to_lowercase();
+    }
+    if let Some(model) = var("MODEL") {
+        profile.model = model;
+    }
+    profile
+}
+
+/// `AXIOM_` and the profile name in upper case, other characters replaced by `_`.
+fn env_prefix(name: &str) -> String {
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_ascii_uppercase())
+        .collect();
+    format!("AXIOM_{}", words.join("_"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_overrides_provider_and_model_of_one_profile() {
+        assert_eq!(env_prefix("Bevy Architect"), "AXIOM_BEVY_ARCHITECT");
+        assert_eq!(env_prefix("Pokemon Professor!"), "AXIOM_POKEMON_PROFESSOR");
+
+        std::env::set_var("AXIOM_DEEP_RESEARCHER_PROVIDER", " Anthropic ");
+        std::env::set_var("AXIOM_DEEP_RESEARCHER_MODEL", "claude-sonnet-4-5");
+        let agents = get_default_agents();
+        std::env::remove_var("AXIOM_DEEP_RESEARCHER_PROVIDER");
+        std::env::remove_var("AXIOM_DEEP_RESEARCHER_MODEL");
+
+        let researcher = agents.iter().find(|a| a.name == "Deep Researcher").unwrap();
+        assert_eq!(researcher.provider, "anthropic");
+        assert_eq!(researcher.model, "claude-sonnet-4-5");
+        let architect = agents.iter().find(|a| a.name == "Bevy Architect").unwrap();
+        assert_eq!(architect.provider, default_provider());
+        assert_eq!(architect.model, "gemini-2.5-pro");
+    }
 }
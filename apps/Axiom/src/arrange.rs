@@ -0,0 +1,233 @@
+use crate::scene::PlacedAsset;
+use glam::{EulerRot, Quat, Vec3};
+
+/// World axis an operation works along.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    pub fn parse(s: &str) -> Option<Axis> {
+        match s.trim().to_lowercase().as_str() {
+            "x" => Some(Axis::X),
+            "y" => Some(Axis::Y),
+            "z" => Some(Axis::Z),
+            _ => None,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        match self {
+            Axis::X => 0,
+            Axis::Y => 1,
+            Axis::Z => 2,
+        }
+    }
+}
+
+/// Where `align` moves the selection on its axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlignTarget {
+    Min,
+    Max,
+    Center,
+    Value(f32),
+}
+
+/// Euler XYZ degrees, the convention of `bevy_upload_asset`.
+pub fn quat_from_degrees(rotation: [f32; 3]) -> Quat {
+    let [x, y, z] = rotation.map(f32::to_radians);
+    Quat::from_euler(EulerRot::XYZ, x, y, z)
+}
+
+/// Inverse of [`quat_from_degrees`], rounded so `90.0` does not come back as `89.99999`.
+/// Turns around Y alone stay `[0, yaw, 0]` with yaw in `0..360`, which is what the road tools
+/// read; a generic Euler decomposition would give `[180, 0, 180]` for a yaw of 180.
+pub fn degrees_from_quat(rotation: Quat) -> [f32; 3] {
+    let q = rotation.normalize();
+    if q.x.abs() < 1e-5 && q.z.abs() < 1e-5 {
+        let yaw = (2.0 * q.y.atan2(q.w)).to_degrees();
+        return [0.0, round(yaw, 100.0).rem_euclid(360.0) + 0.0, 0.0];
+    }
+    let (x, y, z) = q.to_euler(EulerRot::XYZ);
+    [x, y, z].map(|r| round(r.to_degrees(), 100.0) + 0.0)
+}
+
+fn round(v: f32, steps: f32) -> f32 {
+    (v * steps).round() / steps
+}
+
+fn round_point(p: Vec3) -> [f32; 3] {
+    // Adding 0.0 turns -0.0 into 0.0 for nicer output.
+    p.to_array().map(|v| round(v, 1000.0) + 0.0)
+}
+
+/// Mean of the selection's positions.
+pub fn center(items: &[PlacedAsset]) -> [f32; 3] {
+    if items.is_empty() {
+        return [0.0; 3];
+    }
+    let sum = items
+        .iter()
+        .fold(Vec3::ZERO, |acc, a| acc + Vec3::from(a.translation));
+    (sum / items.len() as f32).to_array()
+}
+
+pub fn translate(items: &mut [PlacedAsset], offset: [f32; 3]) {
+    for item in items {
+        item.translation = round_point(Vec3::from(item.translation) + Vec3::from(offset));
+    }
+}
+
+/// Adds `rotation` (Euler degrees) to every item. With a `pivot` the positions swing around it
+/// too, like rotating the selection as one object.
+pub fn rotate(items: &mut [PlacedAsset], rotation: [f32; 3], pivot: Option<[f32; 3]>) {
+    let delta = quat_from_degrees(rotation);
+    for item in items {
+        item.rotation = degrees_from_quat(delta * quat_from_degrees(item.rotation));
+        if let Some(pivot) = pivot {
+            let pivot = Vec3::from(pivot);
+            item.translation = round_point(pivot + delta * (Vec3::from(item.translation) - pivot));
+        }
+    }
+}
+
+/// Multiplies every item's scale. With a `pivot` the distances to it are scaled as well.
+pub fn scale(items: &mut [PlacedAsset], factor: [f32; 3], pivot: Option<[f32; 3]>) {
+    let factor = Vec3::from(factor);
+    for item in items {
+        item.scale = round_point(Vec3::from(item.scale) * factor);
+        if let Some(pivot) = pivot {
+            let pivot = Vec3::from(pivot);
+            item.translation = round_point(pivot + (Vec3::from(item.translation) - pivot) * factor);
+        }
+    }
+}
+
+/// Rounds positions to the grid and, with `angle`, yaw to multiples of it.
+pub fn snap(items: &mut [PlacedAsset], grid: f32, angle: Option<f32>) {
+    for item in items {
+        if grid > 0.0 {
+            item.translation = item.translation.map(|v| round(v / grid, 1.0) * grid + 0.0);
+        }
+        if let Some(angle) = angle.filter(|a| *a > 0.0) {
+            item.rotation[1] = (round(item.rotation[1] / angle, 1.0) * angle).rem_euclid(360.0);
+        }
+    }
+}
+
+/// Gives every item the same coordinate on `axis`.
+pub fn align(items: &mut [PlacedAsset], axis: Axis, target: AlignTarget) {
+    let i = axis.index();
+    let values = items.iter().map(|a| a.translation[i]);
+    let value = match target {
+        AlignTarget::Min => values.fold(f32::MAX, f32::min),
+        AlignTarget::Max => values.fold(f32::MIN, f32::max),
+        AlignTarget::Center => center(items)[i],
+        AlignTarget::Value(v) => v,
+    };
+    for item in items {
+        item.translation[i] = round(value, 1000.0);
+    }
+}
+
+/// Spaces the items evenly on `axis`, keeping their order. Without `spacing` the first and last
+/// item stay put; with it the items are laid out from the first one.
+pub fn distribute(items: &mut [PlacedAsset], axis: Axis, spacing: Option<f32>) {
+    if items.len() < 2 {
+        return;
+    }
+    let i = axis.index();
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| items[a].translation[i].total_cmp(&items[b].translation[i]));
+    let first = items[order[0]].translation[i];
+    let last = items[order[order.len() - 1]].translation[i];
+    let step = spacing.unwrap_or((last - first) / (items.len() - 1) as f32);
+    for (n, &item) in order.iter().enumerate() {
+        items[item].translation[i] = round(first + step * n as f32, 1000.0);
+    }
+}
+
+/// Reflects positions across the plane `axis = at`. Rotations are mirrored so that symmetric
+/// models (roads, props) end up as the mirror image; the models themselves are not flipped.
+pub fn mirror(items: &mut [PlacedAsset], axis: Axis, at: f32) {
+    let i = axis.index();
+    for item in items {
+        item.translation[i] = round(2.0 * at - item.translation[i], 1000.0) + 0.0;
+        let q = quat_from_degrees(item.rotation);
+        // Reflection R' = M R M for a plane with normal `axis`.
+        let mirrored = match axis {
+            Axis::X => Quat::from_xyzw(q.x, -q.y, -q.z, q.w),
+            Axis::Y => Quat::from_xyzw(-q.x, q.y, -q.z, q.w),
+            Axis::Z => Quat::from_xyzw(-q.x, -q.y, q.z, q.w),
+        };
+        item.rotation = degrees_from_quat(mirrored);
+    }
+}
+
+/// Copies with no entity yet, ready to be spawned.
+fn detached(items: &[PlacedAsset]) -> Vec<PlacedAsset> {
+    items
+        .iter()
+        .map(|a| PlacedAsset {
+            entity: None,
+            ..a.clone()
+        })
+        .collect()
+}
+
+/// `count` further copies of the selection, copy `k` moved by `k * offset` and turned by
+/// `k * rotation_step` degrees around Y.
+pub fn linear_array(
+    items: &[PlacedAsset],
+    count: usize,
+    offset: [f32; 3],
+    rotation_step: f32,
+) -> Vec<PlacedAsset> {
+    let mut copies = Vec::new();
+    for k in 1..=count {
+        let mut copy = detached(items);
+        translate(&mut copy, (Vec3::from(offset) * k as f32).to_array());
+        if rotation_step != 0.0 {
+            rotate(&mut copy, [0.0, rotation_step * k as f32, 0.0], None);
+        }
+        copies.extend(copy);
+    }
+    copies
+}
+
+/// Copies of the selection around a vertical axis through `pivot`: `count` copies spread over
+/// `angle` degrees (a full circle keeps the original as one of the steps). With `turn_items`
+/// each copy also faces outwards like the original.
+pub fn radial_array(
+    items: &[PlacedAsset],
+    count: usize,
+    pivot: [f32; 3],
+    angle: f32,
+    turn_items: bool,
+) -> Vec<PlacedAsset> {
+    let full_circle = (angle.abs() - 360.0).abs() < 1e-3;
+    let step = if full_circle {
+        angle / (count + 1) as f32
+    } else {
+        angle / count.max(1) as f32
+    };
+    let mut copies = Vec::new();
+    for k in 1..=count {
+        let mut copy = detached(items);
+        let yaw = step * k as f32;
+        let turn = quat_from_degrees([0.0, yaw, 0.0]);
+        let pivot = Vec3::from(pivot);
+        for item in &mut copy {
+            item.translation = round_point(pivot + turn * (Vec3::from(item.translation) - pivot));
+        }
+        if turn_items {
+            rotate(&mut copy, [0.0, yaw, 0.0], None);
+        }
+        copies.extend(copy);
+    }
+    copies
+}
@@ -0,0 +1,468 @@
+//! Typed client for the Bevy Remote Protocol (the Bevy 0.18 `world.*` / `registry.*` methods).
+//!
+//! All tools talk to the game through [`client`], so the endpoint, request ids, timeouts and
+//! error reporting live in one place. The calls are async; synchronous tools wrap them in
+//! [`block_on`].
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+#[cfg(test)]
+pub mod mock;
+
+pub const DEFAULT_ENDPOINT: &str = "http://127.0.0.1:15721";
+/// Timeout of ordinary calls.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Timeout of calls carrying file uploads.
+pub const UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub const TRANSFORM: &str = "bevy_transform::components::transform::Transform";
+pub const NAME: &str = "bevy_ecs::name::Name";
+pub const SCENE_ROOT: &str = "bevy_scene::components::SceneRoot";
+pub const REMOTE_ASSET: &str = "bevy_ai_remote::AxiomRemoteAsset";
+pub const PRIMITIVE: &str = "bevy_ai_remote::AxiomPrimitive";
+
+/// Version of the `axiom/*` protocol this editor speaks, `bevy_ai_remote::PROTOCOL_VERSION`.
+pub const AXIOM_PROTOCOL: u32 = 1;
+
+/// JSON-RPC error codes used by `bevy_remote`.
+pub mod error_codes {
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const ENTITY_NOT_FOUND: i64 = -23401;
+}
+
+// Endpoint set at runtime (the channel's game target), taking precedence over `AXIOM_BRP_URL`.
+static ENDPOINT_OVERRIDE: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+
+fn get_endpoint_override() -> &'static Mutex<Option<String>> {
+    ENDPOINT_OVERRIDE.get_or_init(|| Mutex::new(None))
+}
+
+// Endpoint of the tool call running in `with_endpoint`, taking precedence over everything.
+static SCOPED_ENDPOINT: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+// Held for the whole of a `with_endpoint` call, so calls aimed at different games take turns.
+static SCOPE_LOCK: Mutex<()> = Mutex::new(());
+
+fn get_scoped_endpoint() -> &'static Mutex<Option<String>> {
+    SCOPED_ENDPOINT.get_or_init(|| Mutex::new(None))
+}
+
+/// Points every following call at `url`, or back at the configured endpoint with `None`.
+pub fn set_endpoint(url: Option<String>) {
+    if let Ok(mut endpoint) = get_endpoint_override().lock() {
+        *endpoint = url;
+    }
+}
+
+/// Runs `f` with every BRP call (and the scene mirror) going to `url`, whichever thread makes
+/// them. Must not be nested.
+pub fn with_endpoint<R>(url: &str, f: impl FnOnce() -> R) -> R {
+    let _turn = SCOPE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Ok(mut scoped) = get_scoped_endpoint().lock() {
+        *scoped = Some(url.to_string());
+    }
+    // Cleared on unwind too, so a panicking tool does not leave calls pointing elsewhere.
+    struct Reset;
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            if let Ok(mut scoped) = get_scoped_endpoint().lock() {
+                *scoped = None;
+            }
+        }
+    }
+    let _reset = Reset;
+    f()
+}
+
+/// The game's BRP address: the endpoint of a scoped call, else the selected target, else
+/// [`configured_endpoint`].
+pub fn endpoint() -> String {
+    let scoped = get_scoped_endpoint().lock().ok().and_then(|e| e.clone());
+    scoped
+        .or_else(|| get_endpoint_override().lock().ok()?.clone())
+        .unwrap_or_else(configured_endpoint)
+}
+
+/// `AXIOM_BRP_URL`, or the `bevy_remote` default port on localhost.
+pub fn configured_endpoint() -> String {
+    std::env::var("AXIOM_BRP_URL")
+        .ok()
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string())
+}
+
+/// Why a BRP call failed.
+#[derive(Debug, Clone)]
+pub enum BrpError {
+    /// The game could not be reached (not running, wrong port, timeout).
+    Transport(String),
+    /// The game answered with a JSON-RPC error.
+    Rpc {
+        code: i64,
+        message: String,
+        data: Option<Value>,
+    },
+    /// The answer did not have the expected shape.
+    Decode(String),
+}
+
+impl BrpError {
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            BrpError::Rpc { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BrpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BrpError::Transport(e) => write!(
+                f,
+                "could not reach the game (is it running with bevy_remote?): {}",
+                e
+            ),
+            BrpError::Rpc {
+                code,
+                message,
+                data,
+            } => {
+                write!(f, "BRP error {}: {}", code, message)?;
+                match data {
+                    Some(data) => write!(f, " ({})", data),
+                    None => Ok(()),
+                }
+            }
+            BrpError::Decode(e) => write!(f, "unexpected BRP response: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BrpError {}
+
+pub type BrpResult<T> = std::result::Result<T, BrpError>;
+
+/// Components to fetch in `world.query`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueryData {
+    pub components: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub option: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub has: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueryFilter {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub with: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub without: Vec<String>,
+}
+
+/// Params of `world.query`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueryParams {
+    pub data: QueryData,
+    pub filter: QueryFilter,
+    pub strict: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueryRow {
+    pub entity: u64,
+    #[serde(default)]
+    pub components: HashMap<String, Value>,
+}
+
+/// Answer of a lenient `world.get_components`: the values found and an error per missing one.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GetComponentsResponse {
+    #[serde(default)]
+    pub components: HashMap<String, Value>,
+    #[serde(default)]
+    pub errors: HashMap<String, Value>,
+}
+
+/// Params of `registry.schema`. Empty lists mean no restriction.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchemaFilter {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub with_crates: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub without_crates: Vec<String>,
+    pub type_limit: TypeLimit,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TypeLimit {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub with: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub without: Vec<String>,
+}
+
+/// Answer of `rpc.discover` (an OpenRPC document), reduced to what Axiom looks at.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Discovery {
+    pub info: DiscoveryInfo,
+    #[serde(default)]
+    pub methods: Vec<DiscoveredMethod>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscoveryInfo {
+    pub title: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscoveredMethod {
+    pub name: String,
+}
+
+/// Answer of `axiom/hello`: what the game's `bevy_ai_remote` build offers.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Hello {
+    pub plugin: String,
+    pub version: String,
+    pub protocol: u32,
+    /// Type paths of the plugin's components as registered in the game.
+    #[serde(default)]
+    pub components: Vec<String>,
+    #[serde(default)]
+    pub methods: Vec<String>,
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    result: Option<Value>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct SpawnResponse {
+    entity: u64,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static HTTP: OnceLock<reqwest::Client> = OnceLock::new();
+
+fn http() -> &'static reqwest::Client {
+    HTTP.get_or_init(reqwest::Client::new)
+}
+
+/// Connection to one game. Cheap to create and clone.
+#[derive(Clone, Debug)]
+pub struct BrpClient {
+    endpoint: String,
+    timeout: Duration,
+}
+
+/// Client for the configured endpoint with the default timeout.
+pub fn client() -> BrpClient {
+    BrpClient::new(endpoint())
+}
+
+impl BrpClient {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Same endpoint with another per-call timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Sends one JSON-RPC request and decodes its `result`.
+    pub async fn call<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Option<Value>,
+    ) -> BrpResult<R> {
+        let mut request = json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "id": NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        });
+        if let Some(params) = params {
+            request["params"] = params;
+        }
+
+        let response = http()
+            .post(&self.endpoint)
+            .timeout(self.timeout)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| BrpError::Transport(e.to_string()))?;
+        let body: Response = response
+            .json()
+            .await
+            .map_err(|e| BrpError::Decode(format!("{}: {}", method, e)))?;
+
+        if let Some(error) = body.error {
+            return Err(BrpError::Rpc {
+                code: error.code,
+                message: error.message,
+                data: error.data,
+            });
+        }
+        serde_json::from_value(body.result.unwrap_or(Value::Null))
+            .map_err(|e| BrpError::Decode(format!("{}: {}", method, e)))
+    }
+
+    /// `world.spawn_entity`: returns the new entity id.
+    pub async fn spawn_entity(&self, components: Map<String, Value>) -> BrpResult<u64> {
+        let spawned: SpawnResponse = self
+            .call(
+                "world.spawn_entity",
+                Some(json!({ "components": components })),
+            )
+            .await?;
+        Ok(spawned.entity)
+    }
+
+    pub async fn query(&self, params: &QueryParams) -> BrpResult<Vec<QueryRow>> {
+        let params = serde_json::to_value(params).map_err(|e| BrpError::Decode(e.to_string()))?;
+        self.call("world.query", Some(params)).await
+    }
+
+    /// Lenient `world.get_components`: components the entity lacks are listed in `errors`.
+    pub async fn get_components(
+        &self,
+        entity: u64,
+        components: &[&str],
+    ) -> BrpResult<GetComponentsResponse> {
+        self.call(
+            "world.get_components",
+            Some(json!({ "entity": entity, "components": components, "strict": false })),
+        )
+        .await
+    }
+
+    pub async fn insert_components(
+        &self,
+        entity: u64,
+        components: Map<String, Value>,
+    ) -> BrpResult<()> {
+        let _: Value = self
+            .call(
+                "world.insert_components",
+                Some(json!({ "entity": entity, "components": components })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// `world.remove_components`: drop the listed components from `entity`.
+    pub async fn remove_components(&self, entity: u64, components: &[&str]) -> BrpResult<()> {
+        let _: Value = self
+            .call(
+                "world.remove_components",
+                Some(json!({ "entity": entity, "components": components })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn despawn_entity(&self, entity: u64) -> BrpResult<()> {
+        let _: Value = self
+            .call("world.despawn_entity", Some(json!({ "entity": entity })))
+            .await?;
+        Ok(())
+    }
+
+    /// `rpc.discover`: protocol version and the methods the game serves.
+    pub async fn discover(&self) -> BrpResult<Discovery> {
+        self.call("rpc.discover", None).await
+    }
+
+    /// `axiom/hello`: version and features of the game's `bevy_ai_remote` plugin.
+    pub async fn hello(&self) -> BrpResult<Hello> {
+        self.call("axiom/hello", None).await
+    }
+
+    /// `world.list_components`: the components of `entity`, or every registered component.
+    pub async fn list_components(&self, entity: Option<u64>) -> BrpResult<Vec<String>> {
+        let params = entity.map(|entity| json!({ "entity": entity }));
+        self.call("world.list_components", params).await
+    }
+
+    /// `registry.schema`: JSON schemas of the reflected types, keyed by type path.
+    pub async fn registry_schema(
+        &self,
+        filter: &SchemaFilter,
+    ) -> BrpResult<HashMap<String, Value>> {
+        let params = serde_json::to_value(filter).map_err(|e| BrpError::Decode(e.to_string()))?;
+        self.call("registry.schema", Some(params)).await
+    }
+}
+
+/// Serialised `Transform` from a position, a rotation quaternion `[x, y, z, w]` and a scale.
+pub fn transform(translation: [f32; 3], rotation: [f32; 4], scale: [f32; 3]) -> Value {
+    json!({
+        "translation": translation,
+        "rotation": rotation,
+        "scale": scale
+    })
+}
+
+// Runtime that drives every blocking BRP call, whichever thread (agent task, rayon worker)
+// makes it.
+static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+
+fn runtime() -> &'static tokio::runtime::Runtime {
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("axiom-brp")
+            .enable_all()
+            .build()
+            .expect("Failed to create the BRP runtime")
+    })
+}
+
+/// Runs a BRP future to completion from synchronous code. Tools run inside the agent's Tokio
+/// task, where blocking on a runtime directly would panic, so that case goes through a scoped
+/// thread.
+pub fn block_on<F>(future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    if tokio::runtime::Handle::try_current().is_err() {
+        return runtime().block_on(future);
+    }
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| runtime().block_on(future))
+            .join()
+            .expect("BRP call panicked")
+    })
+}
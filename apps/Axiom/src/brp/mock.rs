@@ -0,0 +1,373 @@
+//! In-process stand-in for a game running `bevy_remote`, for tests of the Bevy tools.
+//!
+//! [`MockBrp::start`] serves the `world.*` methods the tools use on an in-memory entity store,
+//! records every request and points [`super::endpoint`] at itself until dropped. Like the
+//! `bevy_ai_remote` plugin, spawning an `AxiomRemoteAsset` model gives the entity a `SceneRoot`,
+//! while uploaded textures and buffers lose the component again. [`MockBrp::with_plugin`] makes
+//! it answer `rpc.discover` and `axiom/hello` like a game running the plugin,
+//! [`MockBrp::with_registry`] answer `registry.schema`.
+use super::{error_codes, REMOTE_ASSET, SCENE_ROOT};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+// Tools read the endpoint and the scene mirror from globals, so tests using the mock run one
+// at a time.
+static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Default)]
+struct State {
+    entities: BTreeMap<u64, Map<String, Value>>,
+    next_index: u64,
+    requests: Vec<Value>,
+    hello: Option<Value>,
+    registry: Option<Value>,
+}
+
+impl State {
+    fn spawn(&mut self, components: Map<String, Value>) -> u64 {
+        self.next_index += 1;
+        // Index in the low bits, generation 1 in the high bits, like a real `Entity::to_bits`.
+        let entity = (1 << 32) | self.next_index;
+        self.entities.insert(entity, components);
+        entity
+    }
+}
+
+pub struct MockBrp {
+    url: String,
+    state: Arc<Mutex<State>>,
+    shutdown: Arc<AtomicBool>,
+    // Held by the instance that owns the endpoint, not by the extra games of `another`.
+    lock: Option<MutexGuard<'static, ()>>,
+}
+
+impl MockBrp {
+    /// Starts the server on a free port and makes it the BRP endpoint. The scene mirror is
+    /// cleared so every test starts from an empty game.
+    pub fn start() -> MockBrp {
+        let lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut game = MockBrp::serve();
+        super::set_endpoint(Some(game.url.clone()));
+        crate::scene::clear();
+        game.lock = Some(lock);
+        game
+    }
+
+    /// A second game on another port for the same test. It does not become the endpoint.
+    pub fn another(&self) -> MockBrp {
+        MockBrp::serve()
+    }
+
+    fn serve() -> MockBrp {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock BRP server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let (server_state, server_shutdown) = (state.clone(), shutdown.clone());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if server_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let state = server_state.clone();
+                std::thread::spawn(move || serve(stream, &state));
+            }
+        });
+
+        MockBrp {
+            url,
+            state,
+            shutdown,
+            lock: None,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Serves `rpc.discover` and `axiom/hello`, answering the latter with `hello`.
+    pub fn with_plugin(&self, hello: Value) {
+        self.state.lock().unwrap().hello = Some(hello);
+    }
+
+    /// Serves `registry.schema`, answering it with `schemas` whatever the filter.
+    pub fn with_registry(&self, schemas: Value) {
+        self.state.lock().unwrap().registry = Some(schemas);
+    }
+
+    /// Adds an entity directly, as if the game had spawned it itself.
+    pub fn insert_entity(&self, components: Value) -> u64 {
+        let components = components.as_object().cloned().unwrap_or_default();
+        self.state.lock().unwrap().spawn(components)
+    }
+
+    pub fn entity(&self, entity: u64) -> Option<Map<String, Value>> {
+        self.state.lock().unwrap().entities.get(&entity).cloned()
+    }
+
+    pub fn entity_ids(&self) -> Vec<u64> {
+        self.state
+            .lock()
+            .unwrap()
+            .entities
+            .keys()
+            .copied()
+            .collect()
+    }
+
+    /// Every JSON-RPC request received so far, in order.
+    pub fn requests(&self) -> Vec<Value> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn methods(&self) -> Vec<String> {
+        self.requests()
+            .iter()
+            .filter_map(|r| r["method"].as_str().map(str::to_string))
+            .collect()
+    }
+}
+
+/// Path of a model bundled in `resources/models`, for tests that upload one.
+pub fn model(name: &str) -> String {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("resources/models")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+impl Drop for MockBrp {
+    fn drop(&mut self) {
+        if self.lock.is_some() {
+            super::set_endpoint(None);
+            crate::scene::clear();
+        }
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the accept loop so the thread sees the flag.
+        let _ = TcpStream::connect(self.url.trim_start_matches("http://"));
+    }
+}
+
+/// Reads one HTTP request, answers it and closes the connection.
+fn serve(mut stream: TcpStream, state: &Mutex<State>) {
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(s) => s,
+        Err(_) => return,
+    });
+    let mut length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; length];
+    if reader.read_exact(&mut body).is_err() {
+        return;
+    }
+
+    let response = match serde_json::from_slice::<Value>(&body) {
+        Ok(request) => {
+            let mut state = state.lock().unwrap();
+            state.requests.push(request.clone());
+            let outcome = handle(&mut state, &request);
+            let mut response = json!({ "jsonrpc": "2.0", "id": request["id"] });
+            match outcome {
+                Ok(result) => response["result"] = result,
+                Err(error) => response["error"] = error,
+            }
+            response
+        }
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": { "code": -32700, "message": e.to_string() }
+        }),
+    };
+    let body = response.to_string();
+    let _ = write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+}
+
+fn error(code: i64, message: impl Into<String>) -> Value {
+    json!({ "code": code, "message": message.into() })
+}
+
+fn strings(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn handle(state: &mut State, request: &Value) -> Result<Value, Value> {
+    let params = &request["params"];
+    let entity = params["entity"].as_u64();
+    let existing = |state: &mut State| -> Result<u64, Value> {
+        let entity = entity.ok_or_else(|| error(-32602, "missing 'entity'"))?;
+        if state.entities.contains_key(&entity) {
+            Ok(entity)
+        } else {
+            Err(error(
+                error_codes::ENTITY_NOT_FOUND,
+                format!("Entity {} not found", entity),
+            ))
+        }
+    };
+
+    match request["method"].as_str().unwrap_or("") {
+        "rpc.discover" if state.hello.is_some() => {
+            let mut methods: Vec<Value> = [
+                "world.spawn_entity",
+                "world.insert_components",
+                "world.remove_components",
+                "world.despawn_entity",
+                "world.get_components",
+                "world.list_components",
+                "world.query",
+            ]
+            .into_iter()
+            .map(|name| json!({ "name": name }))
+            .collect();
+            let hello = state.hello.as_ref().unwrap();
+            methods.extend(
+                strings(&hello["methods"])
+                    .into_iter()
+                    .map(|name| json!({ "name": name })),
+            );
+            Ok(json!({
+                "openrpc": "1.3.2",
+                "info": { "title": "Bevy Remote Protocol", "version": "0.18.0" },
+                "methods": methods
+            }))
+        }
+        "axiom/hello" if state.hello.is_some() => Ok(state.hello.clone().unwrap()),
+        "registry.schema" if state.registry.is_some() => Ok(state.registry.clone().unwrap()),
+        "world.spawn_entity" => {
+            let mut components = params["components"]
+                .as_object()
+                .cloned()
+                .unwrap_or_default();
+            if let Some(asset) = components.get(REMOTE_ASSET) {
+                let filename = asset["filename"].as_str().unwrap_or("");
+                if filename.ends_with(".glb") || filename.ends_with(".gltf") {
+                    components.insert(SCENE_ROOT.to_string(), json!({}));
+                } else {
+                    components.remove(REMOTE_ASSET);
+                }
+            }
+            Ok(json!({ "entity": state.spawn(components) }))
+        }
+        "world.insert_components" => {
+            let entity = existing(state)?;
+            let components = params["components"]
+                .as_object()
+                .cloned()
+                .unwrap_or_default();
+            state.entities.get_mut(&entity).unwrap().extend(components);
+            Ok(Value::Null)
+        }
+        "world.remove_components" => {
+            let entity = existing(state)?;
+            let stored = state.entities.get_mut(&entity).unwrap();
+            for name in strings(&params["components"]) {
+                stored.remove(&name);
+            }
+            Ok(Value::Null)
+        }
+        "world.despawn_entity" => {
+            let entity = existing(state)?;
+            state.entities.remove(&entity);
+            Ok(Value::Null)
+        }
+        "world.get_components" => {
+            let entity = existing(state)?;
+            let stored = &state.entities[&entity];
+            let (mut found, mut errors) = (Map::new(), Map::new());
+            for name in strings(&params["components"]) {
+                match stored.get(&name) {
+                    Some(value) => found.insert(name, value.clone()),
+                    None => errors.insert(name, json!("component not present")),
+                };
+            }
+            Ok(json!({ "components": found, "errors": errors }))
+        }
+        "world.list_components" => match entity {
+            Some(_) => {
+                let entity = existing(state)?;
+                Ok(json!(state.entities[&entity].keys().collect::<Vec<_>>()))
+            }
+            None => Ok(json!([])),
+        },
+        "world.query" => {
+            let data = &params["data"];
+            let (components, option, has) = (
+                strings(&data["components"]),
+                strings(&data["option"]),
+                strings(&data["has"]),
+            );
+            let (with, without) = (
+                strings(&params["filter"]["with"]),
+                strings(&params["filter"]["without"]),
+            );
+            let rows: Vec<Value> = state
+                .entities
+                .iter()
+                .filter(|(_, stored)| {
+                    components
+                        .iter()
+                        .chain(&with)
+                        .all(|c| stored.contains_key(c))
+                        && !without.iter().any(|c| stored.contains_key(c))
+                })
+                .map(|(entity, stored)| {
+                    let values: Map<String, Value> = components
+                        .iter()
+                        .chain(&option)
+                        .filter_map(|c| Some((c.clone(), stored.get(c)?.clone())))
+                        .collect();
+                    let mut row = json!({ "entity": entity, "components": values });
+                    if !has.is_empty() {
+                        row["has"] = has
+                            .iter()
+                            .map(|c| (c.clone(), json!(stored.contains_key(c))))
+                            .collect::<Map<_, _>>()
+                            .into();
+                    }
+                    row
+                })
+                .collect();
+            Ok(json!(rows))
+        }
+        method => Err(error(
+            error_codes::METHOD_NOT_FOUND,
+            format!("Method `{}` not found", method),
+        )),
+    }
+}
@@ -0,0 +1,257 @@
+use crate::glb;
+use axiom_roads::{asset_stem, tile_spec, Dir, TileKind, GRID_SIZE};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// One model found under an asset root.
+#[derive(Clone, Debug, Serialize)]
+pub struct CatalogEntry {
+    /// File name, e.g. `road-end-barrier.glb`. This is what `bevy_upload_asset` accepts.
+    pub name: String,
+    pub path: PathBuf,
+    pub tags: Vec<String>,
+    /// Bounding box size `[x, y, z]` in world units.
+    pub size: Option<[f32; 3]>,
+    /// Grid cells covered at rotation 0, `[x, z]`.
+    pub footprint: Option<[u32; 2]>,
+    pub description: String,
+}
+
+// Index of every model under the asset roots, built on first use.
+static CATALOG: OnceLock<Mutex<Option<Vec<CatalogEntry>>>> = OnceLock::new();
+
+fn get_catalog() -> &'static Mutex<Option<Vec<CatalogEntry>>> {
+    CATALOG.get_or_init(|| Mutex::new(None))
+}
+
+/// Directories scanned for models: `AXIOM_ASSET_ROOTS` (a path list, `;` on Windows and `:`
+/// elsewhere) or the built-in `apps/axiom/resources/models`.
+pub fn asset_roots() -> Vec<PathBuf> {
+    if let Some(roots) = std::env::var_os("AXIOM_ASSET_ROOTS") {
+        let roots: Vec<PathBuf> = std::env::split_paths(&roots)
+            .filter(|p| !p.as_os_str().is_empty())
+            .collect();
+        if !roots.is_empty() {
+            return roots;
+        }
+    }
+    let cwd = std::env::current_dir().unwrap_or_default();
+    vec![cwd
+        .join("apps")
+        .join("axiom")
+        .join("resources")
+        .join("models")]
+}
+
+fn model_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            model_files(&path, out);
+        } else if matches!(
+            path.extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase())
+                .as_deref(),
+            Some("glb") | Some("gltf")
+        ) {
+            out.push(path);
+        }
+    }
+}
+
+/// Extra words for road pieces, so "T junction" or "ramp" finds the right stem.
+fn kind_tags(kind: TileKind) -> &'static [&'static str] {
+    match kind {
+        TileKind::Straight => &["straight"],
+        TileKind::Bend => &["bend", "corner", "turn"],
+        TileKind::Curve => &["curve", "wide", "turn"],
+        TileKind::TJunction => &["t", "t-junction", "junction", "tee", "intersection"],
+        TileKind::Crossroad => &["crossroad", "cross", "junction", "4-way"],
+        TileKind::End => &["end", "dead-end", "cap"],
+        TileKind::Slope => &["slope", "ramp", "hill"],
+        TileKind::Roundabout => &["roundabout", "circle", "junction"],
+    }
+}
+
+fn dir_name(dir: Dir) -> &'static str {
+    match dir {
+        Dir::North => "N",
+        Dir::East => "E",
+        Dir::South => "S",
+        Dir::West => "W",
+    }
+}
+
+/// Builds the entry for one model. Files that fail to parse are still listed, with what the
+/// file name tells us.
+pub fn describe(path: &Path) -> CatalogEntry {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = asset_stem(&name);
+
+    let mut tags: Vec<String> = stem
+        .split(['-', '_', ' '])
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    let spec = tile_spec(&name);
+    if let Some(spec) = &spec {
+        tags.extend(kind_tags(spec.kind).iter().map(|t| t.to_string()));
+    }
+    if stem.starts_with("bridge-pillar") {
+        tags.extend(["support", "column"].map(String::from));
+    }
+
+    let info = glb::load(path).map(|doc| glb::inspect(&doc));
+    let size = info.as_ref().ok().and_then(|i| i.size());
+    let footprint = size.map(|[x, _, z]| {
+        // A hair of tolerance so a 1.0001-wide tile still counts as one cell.
+        let cells = |v: f32| ((v / GRID_SIZE) - 0.05).ceil().max(1.0) as u32;
+        [cells(x), cells(z)]
+    });
+
+    let mut description = stem.replace(['-', '_'], " ");
+    if let Some(spec) = &spec {
+        let openings: Vec<&str> = spec.ports.iter().map(|p| dir_name(p.dir)).collect();
+        description.push_str(&format!(
+            ". Road piece ({:?}), open {} at rotation 0",
+            spec.kind,
+            openings.join("/")
+        ));
+    }
+    if let (Some([x, y, z]), Some([w, d])) = (size, footprint) {
+        description.push_str(&format!(
+            ". {:.2} x {:.2} x {:.2} units, {}x{} cells",
+            x, y, z, w, d
+        ));
+    }
+    match &info {
+        Ok(info) => {
+            description.push_str(&format!(", {} triangles", info.triangle_count()));
+            if !info.animations.is_empty() {
+                description.push_str(&format!(", animations: {}", info.animations.join(", ")));
+                tags.push("animated".to_string());
+            }
+        }
+        Err(e) => description.push_str(&format!(" (could not read model: {})", e)),
+    }
+
+    tags.sort();
+    tags.dedup();
+    CatalogEntry {
+        name,
+        path: path.to_path_buf(),
+        tags,
+        size,
+        footprint,
+        description,
+    }
+}
+
+pub fn build(roots: &[PathBuf]) -> Vec<CatalogEntry> {
+    let mut files = Vec::new();
+    for root in roots {
+        model_files(root, &mut files);
+    }
+    files.sort();
+    files.iter().map(|p| describe(p)).collect()
+}
+
+/// The cached catalog, (re)built from [`asset_roots`] when empty or when `refresh` is set.
+pub fn entries(refresh: bool) -> Vec<CatalogEntry> {
+    let Ok(mut catalog) = get_catalog().lock() else {
+        return build(&asset_roots());
+    };
+    if refresh || catalog.is_none() {
+        *catalog = Some(build(&asset_roots()));
+    }
+    catalog.clone().unwrap_or_default()
+}
+
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "the", "with", "and", "of", "for", "to", "in", "on", "piece", "tile", "model",
+];
+
+fn normalize(word: &str) -> String {
+    let word = word.to_lowercase();
+    match word.strip_suffix('s') {
+        Some(singular) if singular.len() > 2 && !word.ends_with("ss") => singular.to_string(),
+        _ => word,
+    }
+}
+
+/// Ranks entries against a free-text query: whole tag matches count most, then matches in the
+/// name and finally in the description. Entries matching nothing are left out.
+pub fn search<'a>(entries: &'a [CatalogEntry], query: &str) -> Vec<(u32, &'a CatalogEntry)> {
+    let words: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric() && c != '-')
+        .filter(|w| !w.is_empty())
+        .map(normalize)
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+        .collect();
+
+    let mut hits: Vec<(u32, &CatalogEntry)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let name = entry.name.to_lowercase();
+            let description = entry.description.to_lowercase();
+            let score: u32 = words
+                .iter()
+                .map(|w| {
+                    if entry.tags.iter().any(|t| normalize(t) == *w) {
+                        4
+                    } else if w.len() < 3 {
+                        // Too short to match inside words ("t" is in nearly every name).
+                        0
+                    } else if name.contains(w.as_str()) {
+                        2
+                    } else if description.contains(w.as_str()) {
+                        1
+                    } else {
+                        0
+                    }
+                })
+                .sum();
+            (score > 0).then_some((score, entry))
+        })
+        .collect();
+    // Best score first; among equals the shorter (plainer) name wins.
+    hits.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(a.1.name.len().cmp(&b.1.name.len()))
+            .then(a.1.name.cmp(&b.1.name))
+    });
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundled() -> Vec<CatalogEntry> {
+        build(&[Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/models")])
+    }
+
+    #[test]
+    fn ranks_the_closest_model_first() {
+        let entries = bundled();
+        let hits = search(&entries, "road end with barrier");
+        assert_eq!(hits[0].1.name, "road-end-barrier.glb");
+        assert!(hits.iter().any(|(_, e)| e.name == "road-end.glb"));
+    }
+
+    #[test]
+    fn unknown_words_match_nothing() {
+        let entries = bundled();
+        assert!(!entries.is_empty());
+        assert!(search(&entries, "zeppelin").is_empty());
+        assert!(search(&entries, "the piece with a").is_empty());
+    }
+}
@@ -0,0 +1,654 @@
+//! Background health check of the game's BRP endpoint.
+//!
+//! [`spawn_monitor`] calls `rpc.discover` every few seconds, backing off while the game is down
+//! and picking it up again as soon as it answers. The latest [`ConnectionStatus`] feeds the
+//! indicator in the top panel and [`prompt_note`], so the agent hears that the game is offline
+//! before it spends turns on failing Bevy tools.
+//!
+//! A game running `bevy_ai_remote` is also asked `axiom/hello`. Its features decide which tools
+//! the agent is offered, and a protocol or component mismatch is reported instead of surfacing
+//! as failing uploads.
+use crate::brp;
+use crate::scene;
+use crate::targets::{self, GameTarget};
+use crate::tools::bevy;
+use crate::types::AsyncMessage;
+use anyhow::{anyhow, Result};
+use eframe::egui;
+use futures_util::future::join_all;
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Longest wait between checks while the game is offline.
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+/// A health check answers fast or counts as a failure.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+/// Round trips slower than this turn the indicator yellow.
+pub const SLOW_LATENCY: Duration = Duration::from_millis(250);
+/// What plugins from before `axiom/hello` could do.
+const LEGACY_FEATURES: &[&str] = &["upload", "primitives", "nav_path"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkState {
+    /// No check has finished yet.
+    Unknown,
+    Online,
+    Offline,
+}
+
+/// What the game reported about itself in `rpc.discover`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameInfo {
+    /// E.g. `Bevy Remote Protocol 0.18.1`.
+    pub protocol: String,
+    pub methods: usize,
+    /// The game runs `bevy_ai_remote` (it serves the `axiom/*` methods).
+    pub axiom_plugin: bool,
+    /// The plugin's `axiom/hello`, `None` for plugins that predate it.
+    pub hello: Option<brp::Hello>,
+}
+
+impl GameInfo {
+    /// Plugin features the game offers.
+    pub fn features(&self) -> Vec<String> {
+        match &self.hello {
+            Some(hello) => hello.features.clone(),
+            None if self.axiom_plugin => LEGACY_FEATURES.iter().map(|f| f.to_string()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Differences between the game's plugin and what this editor expects.
+    pub fn warnings(&self) -> Vec<String> {
+        if !self.axiom_plugin {
+            return Vec::new();
+        }
+        let Some(hello) = &self.hello else {
+            return vec![
+                "bevy_ai_remote predates axiom/hello, update it so features can be negotiated"
+                    .to_string(),
+            ];
+        };
+        let mut warnings = Vec::new();
+        if hello.protocol != brp::AXIOM_PROTOCOL {
+            let older = if hello.protocol < brp::AXIOM_PROTOCOL {
+                "the game's bevy_ai_remote"
+            } else {
+                "Axiom"
+            };
+            warnings.push(format!(
+                "{} {} speaks protocol {}, Axiom speaks {}; update {}",
+                hello.plugin,
+                hello.version,
+                hello.protocol,
+                brp::AXIOM_PROTOCOL,
+                older
+            ));
+        }
+        for expected in [brp::REMOTE_ASSET, brp::PRIMITIVE] {
+            if hello.components.iter().any(|c| c == expected) {
+                continue;
+            }
+            match renamed(&hello.components, expected) {
+                Some(found) => warnings.push(format!(
+                    "{} is registered as {}, Axiom uses that path",
+                    expected, found
+                )),
+                None => warnings.push(format!("The game does not register {}", expected)),
+            }
+        }
+        warnings
+    }
+}
+
+/// The entry of `components` with the same type name as `expected` under another module.
+fn renamed<'a>(components: &'a [String], expected: &str) -> Option<&'a str> {
+    let name = expected.rsplit("::").next()?;
+    components
+        .iter()
+        .find(|c| c.rsplit("::").next() == Some(name))
+        .map(String::as_str)
+}
+
+#[derive(Clone, Debug)]
+pub struct ConnectionStatus {
+    pub endpoint: String,
+    pub state: LinkState,
+    /// Round trip of the last successful check.
+    pub latency: Option<Duration>,
+    pub game: Option<GameInfo>,
+    pub last_error: Option<String>,
+    /// When `state` last changed.
+    pub since: Instant,
+    /// Failed checks in a row.
+    pub failures: u32,
+}
+
+impl ConnectionStatus {
+    fn new(endpoint: String) -> Self {
+        Self {
+            endpoint,
+            state: LinkState::Unknown,
+            latency: None,
+            game: None,
+            last_error: None,
+            since: Instant::now(),
+            failures: 0,
+        }
+    }
+
+    /// Short text next to the indicator dot.
+    pub fn label(&self) -> String {
+        match self.state {
+            LinkState::Unknown => "Game: checking…".to_string(),
+            LinkState::Online => match self.latency {
+                Some(latency) => format!("Game online · {} ms", latency.as_millis()),
+                None => "Game online".to_string(),
+            },
+            LinkState::Offline => {
+                format!("Game offline · {}", format_duration(self.since.elapsed()))
+            }
+        }
+    }
+
+    /// Hover text: endpoint, game identity and the last error.
+    pub fn details(&self) -> String {
+        let mut lines = vec![format!("Endpoint: {}", self.endpoint)];
+        if let Some(game) = &self.game {
+            lines.push(format!("{} ({} methods)", game.protocol, game.methods));
+            lines.push(match (&game.hello, game.axiom_plugin) {
+                (Some(hello), _) => format!(
+                    "{} {} (protocol {}): {}",
+                    hello.plugin,
+                    hello.version,
+                    hello.protocol,
+                    hello.features.join(", ")
+                ),
+                (None, true) => "bevy_ai_remote plugin: yes".to_string(),
+                (None, false) => {
+                    "bevy_ai_remote plugin: missing, uploads will not load".to_string()
+                }
+            });
+            for warning in game.warnings() {
+                lines.push(format!("⚠ {}", warning));
+            }
+        }
+        if self.state == LinkState::Offline {
+            if let Some(error) = &self.last_error {
+                lines.push(format!("Last error: {}", error));
+            }
+        }
+        lines.push("Click to check now".to_string());
+        lines.join("\n")
+    }
+
+    /// Green when fine, yellow when slow or missing the plugin, red when offline.
+    pub fn color(&self) -> egui::Color32 {
+        match self.state {
+            LinkState::Unknown => egui::Color32::GRAY,
+            LinkState::Offline => egui::Color32::from_rgb(220, 60, 60),
+            LinkState::Online => {
+                let slow = self.latency.is_some_and(|l| l > SLOW_LATENCY);
+                let plugin = self
+                    .game
+                    .as_ref()
+                    .is_some_and(|g| g.axiom_plugin && g.warnings().is_empty());
+                if slow || !plugin {
+                    egui::Color32::from_rgb(230, 180, 40)
+                } else {
+                    egui::Color32::from_rgb(70, 190, 90)
+                }
+            }
+        }
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}h", secs / 3600)
+    }
+}
+
+// Latest status of every game target, keyed by endpoint.
+static CONNECTION_STATUS: OnceLock<Mutex<HashMap<String, ConnectionStatus>>> = OnceLock::new();
+
+fn get_statuses() -> &'static Mutex<HashMap<String, ConnectionStatus>> {
+    CONNECTION_STATUS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Latest known state of the connection to the selected game target.
+pub fn status() -> ConnectionStatus {
+    status_of(&targets::selected_endpoint())
+}
+
+pub fn status_of(endpoint: &str) -> ConnectionStatus {
+    get_statuses()
+        .lock()
+        .ok()
+        .and_then(|statuses| statuses.get(endpoint).cloned())
+        .unwrap_or_else(|| ConnectionStatus::new(endpoint.to_string()))
+}
+
+/// Plugin features of the game at `endpoint`. `None` while unknown: not checked yet, offline,
+/// or too old for `rpc.discover`.
+pub fn features_of(endpoint: &str) -> Option<Vec<String>> {
+    let status = status_of(endpoint);
+    match (status.state, status.game) {
+        (LinkState::Online, Some(game)) => Some(game.features()),
+        _ => None,
+    }
+}
+
+/// Whether the game at `endpoint` offers `feature`; yes while that is unknown.
+pub fn supports(endpoint: &str, feature: &str) -> bool {
+    features_of(endpoint).is_none_or(|features| features.iter().any(|f| f == feature))
+}
+
+/// Features from `required` the game at `endpoint` is known to lack.
+pub fn missing<'a>(endpoint: &str, required: &[&'a str]) -> Vec<&'a str> {
+    required
+        .iter()
+        .copied()
+        .filter(|feature| !supports(endpoint, feature))
+        .collect()
+}
+
+/// Fails when the game at `endpoint` is known to lack `feature`.
+pub fn require(endpoint: &str, feature: &str) -> Result<()> {
+    if supports(endpoint, feature) {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "The game at {} does not offer '{}'; its bevy_ai_remote is too old or built without it",
+        endpoint,
+        feature
+    ))
+}
+
+/// Whether a tool needing `required` is usable from the active channel: on its game, or on
+/// any game when broadcasting.
+pub fn offers(required: &[&str]) -> bool {
+    if required.is_empty() {
+        return true;
+    }
+    if targets::selected() == targets::BROADCAST {
+        return targets::list()
+            .iter()
+            .any(|t| missing(&t.endpoint, required).is_empty());
+    }
+    missing(&targets::selected_endpoint(), required).is_empty()
+}
+
+/// Type path the game at `endpoint` registered the plugin component `expected` under, which
+/// differs from `expected` when the plugin moved it to another module.
+pub fn type_path(endpoint: &str, expected: &str) -> String {
+    status_of(endpoint)
+        .game
+        .and_then(|game| game.hello)
+        .and_then(|hello| {
+            if hello.components.iter().any(|c| c == expected) {
+                return None;
+            }
+            renamed(&hello.components, expected).map(str::to_string)
+        })
+        .unwrap_or_else(|| expected.to_string())
+}
+
+/// Every target with its status, in list order.
+pub fn all() -> Vec<(GameTarget, ConnectionStatus)> {
+    targets::list()
+        .into_iter()
+        .map(|target| {
+            let status = status_of(&target.endpoint);
+            (target, status)
+        })
+        .collect()
+}
+
+/// `AXIOM_BRP_POLL_SECS`, or every two seconds.
+pub fn poll_interval() -> Duration {
+    std::env::var("AXIOM_BRP_POLL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|secs| *secs > 0.0)
+        .map(Duration::from_secs_f64)
+        .unwrap_or(DEFAULT_POLL_INTERVAL)
+}
+
+/// Checks every target once. Returns a message per game that went offline, came back or has a
+/// plugin mismatch, for the chat log.
+pub async fn check() -> Vec<String> {
+    join_all(targets::list().iter().map(check_target))
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Checks one target and records the result.
+pub async fn check_target(target: &GameTarget) -> Option<String> {
+    let client = brp::BrpClient::new(target.endpoint.clone()).with_timeout(CHECK_TIMEOUT);
+    let started = Instant::now();
+    let outcome = client.discover().await;
+    let latency = started.elapsed();
+    let hello = match &outcome {
+        Ok(discovery) if discovery.methods.iter().any(|m| m.name == "axiom/hello") => {
+            client.hello().await.ok()
+        }
+        _ => None,
+    };
+
+    let mut statuses = get_statuses().lock().unwrap_or_else(|e| e.into_inner());
+    let status = statuses
+        .entry(target.endpoint.clone())
+        .or_insert_with(|| ConnectionStatus::new(target.endpoint.clone()));
+    let previous = status.state;
+    let previous_warnings = status
+        .game
+        .as_ref()
+        .map(GameInfo::warnings)
+        .unwrap_or_default();
+
+    match outcome {
+        Ok(discovery) => {
+            status.latency = Some(latency);
+            status.game = Some(GameInfo {
+                protocol: format!("{} {}", discovery.info.title, discovery.info.version),
+                methods: discovery.methods.len(),
+                axiom_plugin: discovery
+                    .methods
+                    .iter()
+                    .any(|m| m.name.starts_with("axiom/")),
+                hello,
+            });
+            status.last_error = None;
+            status.failures = 0;
+            status.state = LinkState::Online;
+        }
+        // Any JSON-RPC answer means the game is up, even if it is too old for `rpc.discover`.
+        Err(brp::BrpError::Rpc { .. }) => {
+            status.latency = Some(latency);
+            status.last_error = None;
+            status.failures = 0;
+            status.state = LinkState::Online;
+        }
+        Err(e) => {
+            status.latency = None;
+            status.last_error = Some(e.to_string());
+            status.failures += 1;
+            status.state = LinkState::Offline;
+        }
+    }
+    if status.state != previous {
+        status.since = Instant::now();
+    }
+
+    let mut messages = Vec::new();
+    match (previous, status.state) {
+        (LinkState::Online, LinkState::Offline) => messages.push(format!(
+            "🔴 Game '{}' at {} went offline. Bevy tools will fail on it until it is running again.",
+            target.name, target.endpoint
+        )),
+        (LinkState::Offline, LinkState::Online) => {
+            // The game most likely restarted, so what Axiom spawned or uploaded there is gone.
+            scene::clear_game(&target.endpoint);
+            bevy::forget_uploads(&target.endpoint);
+            messages.push(format!(
+                "🟢 Game '{}' at {} is back online. Entities spawned before it went offline are no longer tracked.",
+                target.name, target.endpoint
+            ))
+        }
+        _ => {}
+    }
+    // Reported once per change, e.g. when a rebuilt game brings another plugin version.
+    if let Some(game) = &status.game {
+        let warnings = game.warnings();
+        if !warnings.is_empty() && warnings != previous_warnings {
+            messages.push(format!(
+                "⚠️ Game '{}' at {}: {}.",
+                target.name,
+                target.endpoint,
+                warnings.join("; ")
+            ));
+        }
+    }
+    (!messages.is_empty()).then(|| messages.join("\n"))
+}
+
+/// Wait before the next check: the poll interval while online, doubling per failure while
+/// offline, up to [`MAX_BACKOFF`].
+fn next_delay(status: &ConnectionStatus, interval: Duration) -> Duration {
+    if status.state != LinkState::Offline {
+        return interval;
+    }
+    let factor = 1u32 << status.failures.saturating_sub(1).min(5);
+    (interval * factor).min(MAX_BACKOFF.max(interval))
+}
+
+/// Starts polling every target on `rt`. Transitions are posted to the chat as system logs.
+pub fn spawn_monitor(rt: &tokio::runtime::Runtime, ctx: egui::Context, tx: Sender<AsyncMessage>) {
+    let interval = poll_interval();
+    rt.spawn(async move {
+        loop {
+            for change in check().await {
+                let _ = tx.send(AsyncMessage::Log(change));
+            }
+            ctx.request_repaint();
+            // The soonest any target is due; a game that is up is never checked less often
+            // because another one is down.
+            let delay = all()
+                .iter()
+                .map(|(_, status)| next_delay(status, interval))
+                .min()
+                .unwrap_or(interval);
+            tokio::time::sleep(delay).await;
+        }
+    });
+}
+
+/// Paragraph for the system prompt saying whether the game can be reached, and which other
+/// targets there are. `None` until the first check has finished.
+pub fn prompt_note() -> Option<String> {
+    let broadcasting = targets::selected() == targets::BROADCAST;
+    let status = status();
+    let mut note = match status.state {
+        LinkState::Unknown => return None,
+        _ if broadcasting => format!(
+            "**GAME STATUS**: this channel broadcasts Bevy tool calls to every connected game target ({} online).",
+            all()
+                .iter()
+                .filter(|(_, s)| s.state == LinkState::Online)
+                .count()
+        ),
+        LinkState::Online => {
+            let mut note = format!("**GAME STATUS**: online at {}", status.endpoint);
+            if let Some(latency) = status.latency {
+                note.push_str(&format!(" ({} ms)", latency.as_millis()));
+            }
+            note.push('.');
+            if let Some(game) = &status.game {
+                if !game.axiom_plugin {
+                    note.push_str(" The game does not run the bevy_ai_remote plugin, so uploads cannot be spawned and tools needing it are not available; tell the user to add `BevyAiRemotePlugin`.");
+                }
+                let warnings = game.warnings();
+                if !warnings.is_empty() {
+                    note.push_str(&format!(
+                        " Plugin mismatch, mention it to the user if tools fail: {}.",
+                        warnings.join("; ")
+                    ));
+                }
+                if game.axiom_plugin {
+                    note.push_str(&format!(
+                        " Plugin features: {}; tools needing others are not available.",
+                        game.features().join(", ")
+                    ));
+                }
+            }
+            note
+        }
+        LinkState::Offline => format!(
+            "**GAME STATUS: OFFLINE**. The Bevy game at {} has not answered for {} ({}). Do NOT call tools that act on the running game (bevy_*, edit_transforms, road_turtle, road_spline, road_from_sketch, scatter with spawn): they will only fail. Tell the user the game is not running and continue with work that does not need it, such as planning with previews or editing code.",
+            status.endpoint,
+            format_duration(status.since.elapsed()),
+            status.last_error.as_deref().unwrap_or("no answer")
+        ),
+    };
+
+    let all = all();
+    if all.len() > 1 {
+        let selected = targets::selected();
+        let lines: Vec<String> = all
+            .iter()
+            .map(|(target, status)| {
+                let marker = if target.name == selected {
+                    " (this channel)"
+                } else {
+                    ""
+                };
+                format!(
+                    "- {}{}: {} ({})",
+                    target.name,
+                    marker,
+                    target.endpoint,
+                    match status.state {
+                        LinkState::Unknown => "unknown",
+                        LinkState::Online => "online",
+                        LinkState::Offline => "offline",
+                    }
+                )
+            })
+            .collect();
+        note.push_str(&format!(
+            "\nGame targets (Bevy tools take an optional `target`; '{}' runs the call on every connected one):\n{}",
+            targets::BROADCAST,
+            lines.join("\n")
+        ));
+    }
+    Some(note)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brp::mock::MockBrp;
+    use crate::tools::bevy::{BevySpawnPrimitiveTool, BevyUploadAssetTool};
+    use crate::tools::target::Targeted;
+    use crate::tools::Tool;
+    use serde_json::json;
+
+    fn hello(protocol: u32, components: &[&str], features: &[&str]) -> serde_json::Value {
+        json!({
+            "plugin": "bevy_ai_remote",
+            "version": "0.2.0",
+            "protocol": protocol,
+            "components": components,
+            "methods": ["axiom/hello", "axiom/nav_path"],
+            "features": features
+        })
+    }
+
+    #[test]
+    fn hello_decides_features_and_the_upload_component() {
+        let game = MockBrp::start();
+        let moved = "bevy_ai_remote::upload::AxiomRemoteAsset";
+        game.with_plugin(hello(
+            brp::AXIOM_PROTOCOL,
+            &[moved, brp::PRIMITIVE],
+            &["upload"],
+        ));
+        let target = targets::add("hello-test", game.url()).unwrap();
+        let message = brp::block_on(check_target(&target)).unwrap();
+        assert!(message.contains("registered as"), "{}", message);
+
+        let info = status_of(game.url()).game.unwrap();
+        assert_eq!(info.hello.as_ref().unwrap().version, "0.2.0");
+        assert_eq!(
+            missing(game.url(), &["upload", "primitives"]),
+            ["primitives"]
+        );
+        assert_eq!(type_path(game.url(), brp::REMOTE_ASSET), moved);
+
+        // A tool needing a feature the game lacks is refused.
+        let err = Targeted::new(BevySpawnPrimitiveTool, "hello-test")
+            .execute(json!({ "target": "hello-test", "position": [0, 0, 0] }))
+            .unwrap_err();
+        assert!(err.to_string().contains("primitives"), "{}", err);
+
+        // Uploads use the reported type path and leave out the field this build does not know.
+        let model = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/models/road-straight.glb");
+        Targeted::new(BevyUploadAssetTool, "hello-test")
+            .execute(json!({ "target": "hello-test", "local_path": model, "translation": [0.0, 0.0, 0.0] }))
+            .unwrap();
+        let spawn = game
+            .requests()
+            .into_iter()
+            .rfind(|r| r["method"] == "world.spawn_entity")
+            .unwrap();
+        let asset = &spawn["params"]["components"][moved];
+        assert_eq!(asset["filename"], "road-straight.glb");
+        assert!(asset.get("dependencies").is_none(), "{}", asset);
+
+        targets::remove("hello-test").unwrap();
+    }
+
+    #[test]
+    fn protocol_mismatch_and_old_plugins_are_reported() {
+        let game = MockBrp::start();
+        game.with_plugin(hello(
+            brp::AXIOM_PROTOCOL + 1,
+            &[brp::REMOTE_ASSET, brp::PRIMITIVE],
+            &["upload", "nav_path"],
+        ));
+        let target = targets::add("newer-test", game.url()).unwrap();
+        brp::block_on(check_target(&target));
+        let warnings = status_of(game.url()).game.unwrap().warnings();
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].contains("update Axiom"), "{}", warnings[0]);
+        targets::remove("newer-test").unwrap();
+
+        // A plugin from before axiom/hello keeps its old features.
+        let legacy = GameInfo {
+            protocol: "Bevy Remote Protocol 0.18.0".to_string(),
+            methods: 9,
+            axiom_plugin: true,
+            hello: None,
+        };
+        assert!(legacy.features().contains(&"upload".to_string()));
+        assert!(legacy.warnings()[0].contains("predates"));
+    }
+
+    #[test]
+    fn coming_back_online_forgets_the_mirrored_scene() {
+        let game = MockBrp::start();
+        let target = targets::add("reconnect-test", game.url()).unwrap();
+        brp::with_endpoint(game.url(), || {
+            scene::record(scene::PlacedAsset::new(
+                Some(7),
+                "road-end.glb".to_string(),
+                "road-end.glb".into(),
+                [0.0; 3],
+                [0.0; 3],
+            ))
+        });
+        get_statuses()
+            .lock()
+            .unwrap()
+            .entry(game.url().to_string())
+            .or_insert_with(|| ConnectionStatus::new(game.url().to_string()))
+            .state = LinkState::Offline;
+
+        let message = brp::block_on(check_target(&target)).unwrap();
+        assert!(message.contains("back online"), "{}", message);
+        assert!(brp::with_endpoint(game.url(), scene::snapshot).is_empty());
+        targets::remove("reconnect-test").unwrap();
+    }
+}
@@ -0,0 +1,484 @@
+//! Runs the game under Axiom: `cargo run` in the game's folder, stdout and stderr kept for the
+//! log panel and `game_logs`, readiness checked over BRP, restarts on demand or when the game's
+//! sources change.
+//!
+//! There is one managed process per game target, keyed by its BRP endpoint. The launched game
+//! gets `AXIOM_BRP_PORT` set to the target's port, which `bevy_ai_remote` listens on.
+use crate::brp;
+use crate::connection;
+use crate::scene;
+use crate::targets::GameTarget;
+use crate::tools::bevy;
+use crate::types::AsyncMessage;
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::Sender;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Lines kept per game; older ones are dropped.
+const MAX_LOG_LINES: usize = 5000;
+/// First build of a Bevy game can take minutes.
+pub const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(300);
+/// How long a stopped game gets to exit before it is killed.
+const STOP_GRACE: Duration = Duration::from_secs(3);
+
+/// How to launch the game.
+#[derive(Clone, Debug)]
+pub struct GameConfig {
+    /// Folder `cargo run` runs in.
+    pub dir: PathBuf,
+    /// `-p` package, for a workspace with several binaries.
+    pub package: Option<String>,
+    /// Extra cargo arguments, e.g. `--release` or `--features dev`.
+    pub cargo_args: Vec<String>,
+    /// Arguments for the game itself, passed after `--`.
+    pub args: Vec<String>,
+    /// Restart when a file under `dir/src` or `dir/Cargo.toml` changes.
+    pub restart_on_edit: bool,
+}
+
+fn env_list(name: &str) -> Vec<String> {
+    std::env::var(name)
+        .map(|v| v.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+impl GameConfig {
+    /// `AXIOM_GAME_DIR` (default `examples/simple_game`), `AXIOM_GAME_PACKAGE`,
+    /// `AXIOM_GAME_CARGO_ARGS`, `AXIOM_GAME_ARGS` and `AXIOM_GAME_RESTART_ON_EDIT`.
+    pub fn from_env() -> Self {
+        Self {
+            dir: std::env::var("AXIOM_GAME_DIR")
+                .ok()
+                .filter(|d| !d.trim().is_empty())
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("examples/simple_game")),
+            package: std::env::var("AXIOM_GAME_PACKAGE")
+                .ok()
+                .filter(|p| !p.trim().is_empty()),
+            cargo_args: env_list("AXIOM_GAME_CARGO_ARGS"),
+            args: env_list("AXIOM_GAME_ARGS"),
+            restart_on_edit: std::env::var("AXIOM_GAME_RESTART_ON_EDIT")
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+        }
+    }
+
+    fn cargo_command_args(&self) -> Vec<String> {
+        let mut args = vec!["run".to_string()];
+        if let Some(package) = &self.package {
+            args.push("-p".to_string());
+            args.push(package.clone());
+        }
+        args.extend(self.cargo_args.iter().cloned());
+        if !self.args.is_empty() {
+            args.push("--".to_string());
+            args.extend(self.args.iter().cloned());
+        }
+        args
+    }
+
+    /// `cargo run ...` as typed in a shell.
+    pub fn command_line(&self) -> String {
+        format!("cargo {}", self.cargo_command_args().join(" "))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+    /// Notes from Axiom itself: launches, stops, restarts.
+    Axiom,
+}
+
+#[derive(Clone, Debug)]
+pub struct LogLine {
+    pub stream: LogStream,
+    pub text: String,
+    /// Launch this line belongs to, counting from 1.
+    pub run: u32,
+}
+
+/// Snapshot of one managed game for the UI and the tools.
+#[derive(Clone, Debug)]
+pub struct ProcessStatus {
+    pub pid: Option<u32>,
+    pub uptime: Option<Duration>,
+    pub run: u32,
+    pub command: String,
+    pub dir: PathBuf,
+    /// How the last run ended, if it did.
+    pub exit: Option<String>,
+}
+
+impl ProcessStatus {
+    pub fn is_running(&self) -> bool {
+        self.pid.is_some()
+    }
+}
+
+struct ManagedGame {
+    config: GameConfig,
+    child: Option<Child>,
+    run: u32,
+    started: Option<Instant>,
+    exit: Option<String>,
+    logs: VecDeque<LogLine>,
+    /// Newest source modification when the current run was launched.
+    sources_stamp: Option<SystemTime>,
+    /// Change seen by the watcher, restarted on once it stops changing.
+    pending_change: Option<SystemTime>,
+}
+
+impl ManagedGame {
+    fn new(config: GameConfig) -> Self {
+        Self {
+            config,
+            child: None,
+            run: 0,
+            started: None,
+            exit: None,
+            logs: VecDeque::new(),
+            sources_stamp: None,
+            pending_change: None,
+        }
+    }
+
+    fn log(&mut self, stream: LogStream, text: String) {
+        if self.logs.len() >= MAX_LOG_LINES {
+            self.logs.pop_front();
+        }
+        let run = self.run;
+        self.logs.push_back(LogLine { stream, text, run });
+    }
+
+    /// Notices a game that exited on its own.
+    fn reap(&mut self) {
+        let Some(child) = &mut self.child else {
+            return;
+        };
+        if let Ok(Some(status)) = child.try_wait() {
+            let exit = match status.code() {
+                Some(0) => "exited normally".to_string(),
+                Some(code) => format!("exited with code {}", code),
+                None => "was terminated".to_string(),
+            };
+            self.child = None;
+            self.started = None;
+            self.log(LogStream::Axiom, format!("Game {}", exit));
+            self.exit = Some(exit);
+        }
+    }
+
+    fn status(&self) -> ProcessStatus {
+        ProcessStatus {
+            pid: self.child.as_ref().map(Child::id),
+            uptime: self.started.map(|s| s.elapsed()),
+            run: self.run,
+            command: self.config.command_line(),
+            dir: self.config.dir.clone(),
+            exit: self.exit.clone(),
+        }
+    }
+}
+
+static MANAGED_GAMES: OnceLock<Mutex<HashMap<String, ManagedGame>>> = OnceLock::new();
+
+fn get_games() -> &'static Mutex<HashMap<String, ManagedGame>> {
+    MANAGED_GAMES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn with_game<R>(endpoint: &str, f: impl FnOnce(&mut ManagedGame) -> R) -> R {
+    let mut games = get_games().lock().unwrap_or_else(|e| e.into_inner());
+    let game = games
+        .entry(endpoint.to_string())
+        .or_insert_with(|| ManagedGame::new(GameConfig::from_env()));
+    game.reap();
+    f(game)
+}
+
+/// Port the game should serve BRP on, taken from the target's endpoint.
+fn port_of(endpoint: &str) -> Option<u16> {
+    endpoint
+        .trim_end_matches('/')
+        .rsplit(':')
+        .next()
+        .and_then(|port| port.parse().ok())
+}
+
+/// Newest modification time under `dir/src` and of `dir/Cargo.toml`.
+fn newest_source(dir: &Path) -> Option<SystemTime> {
+    fn walk(path: &Path, newest: &mut Option<SystemTime>) {
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                walk(&path, newest);
+            } else if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+                *newest = (*newest).max(Some(modified));
+            }
+        }
+    }
+    let mut newest = std::fs::metadata(dir.join("Cargo.toml"))
+        .and_then(|m| m.modified())
+        .ok();
+    walk(&dir.join("src"), &mut newest);
+    newest
+}
+
+fn pipe_to_log(endpoint: String, stream: LogStream, pipe: impl Read + Send + 'static) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(pipe).lines() {
+            let Ok(line) = line else { break };
+            if let Ok(mut games) = get_games().lock() {
+                if let Some(game) = games.get_mut(&endpoint) {
+                    game.log(stream, line);
+                }
+            }
+        }
+    });
+}
+
+/// Launches the game for `endpoint` with `config`, or with the one it last ran with.
+pub fn start(endpoint: &str, config: Option<GameConfig>) -> Result<ProcessStatus> {
+    with_game(endpoint, |game| {
+        if let Some(config) = config {
+            game.config = config;
+        }
+        if let Some(child) = &game.child {
+            return Err(anyhow!(
+                "The game is already running (pid {}), use game_restart",
+                child.id()
+            ));
+        }
+        let dir = game.config.dir.clone();
+        if !dir.join("Cargo.toml").is_file() {
+            return Err(anyhow!(
+                "No Cargo.toml in {}. Set AXIOM_GAME_DIR or pass 'dir'",
+                dir.display()
+            ));
+        }
+
+        let mut command = Command::new("cargo");
+        command
+            .args(game.config.cargo_command_args())
+            .current_dir(&dir)
+            .env("CARGO_TERM_COLOR", "never")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(port) = port_of(endpoint) {
+            command.env("AXIOM_BRP_PORT", port.to_string());
+        }
+        // Own process group, so stopping takes the game down with cargo.
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+            command.creation_flags(CREATE_NO_WINDOW);
+        }
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| anyhow!("Failed to run cargo: {}", e))?;
+        game.run += 1;
+        let command_line = game.config.command_line();
+        game.log(
+            LogStream::Axiom,
+            format!("$ {} (in {})", command_line, dir.display()),
+        );
+        if let Some(stdout) = child.stdout.take() {
+            pipe_to_log(endpoint.to_string(), LogStream::Stdout, stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            pipe_to_log(endpoint.to_string(), LogStream::Stderr, stderr);
+        }
+        game.child = Some(child);
+        game.started = Some(Instant::now());
+        game.exit = None;
+        game.sources_stamp = newest_source(&dir);
+        game.pending_change = None;
+        Ok(game.status())
+    })
+}
+
+/// Ends the process tree: cargo and the game it started.
+fn kill_tree(child: &mut Child) {
+    let pid = child.id().to_string();
+    #[cfg(unix)]
+    {
+        let group = format!("-{}", pid);
+        let _ = Command::new("kill").args(["-TERM", "--", &group]).status();
+        let deadline = Instant::now() + STOP_GRACE;
+        while Instant::now() < deadline {
+            if matches!(child.try_wait(), Ok(Some(_))) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let _ = Command::new("kill").args(["-KILL", "--", &group]).status();
+    }
+    #[cfg(windows)]
+    {
+        let _ = Command::new("taskkill")
+            .args(["/PID", &pid, "/T", "/F"])
+            .status();
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Stops the game for `endpoint`. Returns false if it was not running.
+pub fn stop(endpoint: &str) -> bool {
+    // Taken out of the map first: killing waits, and the log threads need the lock meanwhile.
+    let Some(mut child) = with_game(endpoint, |game| game.child.take()) else {
+        return false;
+    };
+    kill_tree(&mut child);
+    with_game(endpoint, |game| {
+        game.started = None;
+        game.exit = Some("stopped by Axiom".to_string());
+        game.log(LogStream::Axiom, "Game stopped".to_string());
+    });
+    true
+}
+
+pub fn restart(endpoint: &str) -> Result<ProcessStatus> {
+    stop(endpoint);
+    // The new process starts from an empty world and may have lost its asset cache.
+    scene::clear_game(endpoint);
+    bevy::forget_uploads(endpoint);
+    start(endpoint, None)
+}
+
+/// Stops every game Axiom started, e.g. when the editor closes.
+pub fn stop_all() {
+    let endpoints: Vec<String> = get_games()
+        .lock()
+        .map(|games| games.keys().cloned().collect())
+        .unwrap_or_default();
+    for endpoint in endpoints {
+        stop(&endpoint);
+    }
+}
+
+/// Waits until the game answers over BRP. Fails early if the process exits, e.g. on a compile
+/// error, with the end of its output.
+pub fn wait_ready(target: &GameTarget, timeout: Duration) -> Result<Duration> {
+    let started = Instant::now();
+    let client = brp::BrpClient::new(target.endpoint.clone()).with_timeout(Duration::from_secs(1));
+    loop {
+        let status = with_game(&target.endpoint, |game| game.status());
+        if !status.is_running() {
+            return Err(anyhow!(
+                "The game {} before BRP came up. Last output:\n{}",
+                status.exit.as_deref().unwrap_or("stopped"),
+                tail(&target.endpoint, 40)
+            ));
+        }
+        let answered = match brp::block_on(client.discover()) {
+            Ok(_) | Err(brp::BrpError::Rpc { .. }) => true,
+            Err(_) => false,
+        };
+        if answered {
+            brp::block_on(connection::check_target(target));
+            return Ok(started.elapsed());
+        }
+        if started.elapsed() > timeout {
+            return Err(anyhow!(
+                "BRP at {} did not answer within {}s (the game is still running). Last output:\n{}",
+                target.endpoint,
+                timeout.as_secs(),
+                tail(&target.endpoint, 20)
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+/// Launch settings for `endpoint`: the last ones used, or the configured defaults.
+pub fn config(endpoint: &str) -> GameConfig {
+    with_game(endpoint, |game| game.config.clone())
+}
+
+pub fn status(endpoint: &str) -> ProcessStatus {
+    with_game(endpoint, |game| game.status())
+}
+
+/// Lines of the game for `endpoint`, oldest first.
+pub fn logs(endpoint: &str) -> Vec<LogLine> {
+    with_game(endpoint, |game| game.logs.iter().cloned().collect())
+}
+
+pub fn clear_logs(endpoint: &str) {
+    with_game(endpoint, |game| game.logs.clear());
+}
+
+/// Last `n` lines of the current run as plain text.
+pub fn tail(endpoint: &str, n: usize) -> String {
+    let lines = logs(endpoint);
+    let run = lines.last().map(|l| l.run).unwrap_or(0);
+    let current: Vec<&LogLine> = lines.iter().filter(|l| l.run == run).collect();
+    current[current.len().saturating_sub(n)..]
+        .iter()
+        .map(|l| l.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Restarts games launched with `restart_on_edit` once their sources stop changing. Restarts are
+/// posted to the chat as system logs.
+pub fn spawn_watcher(tx: Sender<AsyncMessage>) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_secs(1));
+        let due: Vec<String> = get_games()
+            .lock()
+            .map(|mut games| {
+                games
+                    .iter_mut()
+                    .filter_map(|(endpoint, game)| {
+                        game.reap();
+                        if !game.config.restart_on_edit || game.child.is_none() {
+                            return None;
+                        }
+                        let newest = newest_source(&game.config.dir);
+                        if newest <= game.sources_stamp {
+                            return None;
+                        }
+                        // Restart once two checks in a row see the same state, so a save
+                        // touching several files triggers one rebuild.
+                        if game.pending_change == newest {
+                            Some(endpoint.clone())
+                        } else {
+                            game.pending_change = newest;
+                            None
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        for endpoint in due {
+            let message = match restart(&endpoint) {
+                Ok(status) => format!(
+                    "🔁 Game sources changed, restarted the game for {} (pid {}).",
+                    endpoint,
+                    status.pid.unwrap_or(0)
+                ),
+                Err(e) => format!("🔁 Game sources changed, restart failed: {}", e),
+            };
+            let _ = tx.send(AsyncMessage::Log(message));
+        }
+    });
+}
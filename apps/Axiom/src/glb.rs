@@ -0,0 +1,517 @@
+use anyhow::{anyhow, Result};
+use glam::{Mat4, Quat, Vec3};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+const GLB_MAGIC: u32 = 0x4654_6C67; // "glTF"
+const CHUNK_JSON: u32 = 0x4E4F_534A; // "JSON"
+const CHUNK_BIN: u32 = 0x004E_4942; // "BIN\0"
+
+/// A glTF document: the JSON part and, for `.glb`, the embedded binary chunk.
+#[derive(Clone, Debug)]
+pub struct GltfDocument {
+    pub json: Value,
+    pub bin: Option<Vec<u8>>,
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| anyhow!("Truncated GLB at byte {}", offset))
+}
+
+/// Splits a binary glTF into its JSON and BIN chunks.
+pub fn parse_glb(bytes: &[u8]) -> Result<GltfDocument> {
+    if read_u32(bytes, 0)? != GLB_MAGIC {
+        return Err(anyhow!("Not a GLB file (bad magic)"));
+    }
+    let version = read_u32(bytes, 4)?;
+    if version != 2 {
+        return Err(anyhow!("Unsupported GLB version {}", version));
+    }
+    let length = (read_u32(bytes, 8)? as usize).min(bytes.len());
+
+    let mut json = None;
+    let mut bin = None;
+    let mut offset = 12;
+    while offset + 8 <= length {
+        let chunk_len = read_u32(bytes, offset)? as usize;
+        let chunk_type = read_u32(bytes, offset + 4)?;
+        let data = bytes
+            .get(offset + 8..offset + 8 + chunk_len)
+            .ok_or_else(|| anyhow!("GLB chunk runs past the end of the file"))?;
+        match chunk_type {
+            CHUNK_JSON => json = Some(serde_json::from_slice::<Value>(data)?),
+            CHUNK_BIN => bin = Some(data.to_vec()),
+            _ => {}
+        }
+        offset += 8 + chunk_len;
+    }
+
+    Ok(GltfDocument {
+        json: json.ok_or_else(|| anyhow!("GLB has no JSON chunk"))?,
+        bin,
+    })
+}
+
+/// Loads a `.glb` or a `.gltf` (JSON) file. External buffers of a `.gltf` are not read.
+pub fn load(path: &Path) -> Result<GltfDocument> {
+    let bytes =
+        std::fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    if bytes.starts_with(b"glTF") {
+        parse_glb(&bytes)
+    } else {
+        Ok(GltfDocument {
+            json: serde_json::from_slice(&bytes)
+                .map_err(|e| anyhow!("{} is neither GLB nor glTF JSON: {}", path.display(), e))?,
+            bin: None,
+        })
+    }
+}
+
+/// Serialises a document as binary glTF. The BIN chunk is only written when `bin` is not empty.
+pub fn write_glb(json: &Value, bin: &[u8]) -> Result<Vec<u8>> {
+    let mut json_chunk = serde_json::to_vec(json)?;
+    // Chunks are 4-byte aligned: JSON is padded with spaces, BIN with zeros.
+    json_chunk.resize(json_chunk.len().next_multiple_of(4), b' ');
+    let mut bin_chunk = bin.to_vec();
+    bin_chunk.resize(bin_chunk.len().next_multiple_of(4), 0);
+
+    let mut total = 12 + 8 + json_chunk.len();
+    if !bin_chunk.is_empty() {
+        total += 8 + bin_chunk.len();
+    }
+    let mut out = Vec::with_capacity(total);
+    out.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+    out.extend_from_slice(&2u32.to_le_bytes());
+    out.extend_from_slice(&(total as u32).to_le_bytes());
+    out.extend_from_slice(&(json_chunk.len() as u32).to_le_bytes());
+    out.extend_from_slice(&CHUNK_JSON.to_le_bytes());
+    out.extend_from_slice(&json_chunk);
+    if !bin_chunk.is_empty() {
+        out.extend_from_slice(&(bin_chunk.len() as u32).to_le_bytes());
+        out.extend_from_slice(&CHUNK_BIN.to_le_bytes());
+        out.extend_from_slice(&bin_chunk);
+    }
+    Ok(out)
+}
+
+/// Decodes `%XX` escapes in a glTF URI (`my%20texture.png` -> `my texture.png`).
+pub fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct NodeInfo {
+    pub name: String,
+    pub mesh: Option<String>,
+    pub children: Vec<NodeInfo>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MeshInfo {
+    pub name: String,
+    pub primitives: usize,
+    pub vertices: u64,
+    pub triangles: u64,
+    pub materials: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TextureInfo {
+    /// File the texture is loaded from, `None` when embedded in a buffer or data URI.
+    pub uri: Option<String>,
+    pub mime_type: Option<String>,
+    pub embedded: bool,
+}
+
+/// Everything `gltf_inspect` reports about a model.
+#[derive(Clone, Debug, Serialize)]
+pub struct ModelInfo {
+    pub generator: Option<String>,
+    pub nodes: Vec<NodeInfo>,
+    pub meshes: Vec<MeshInfo>,
+    pub materials: Vec<String>,
+    pub textures: Vec<TextureInfo>,
+    pub animations: Vec<String>,
+    /// Files the model needs next to it: external buffers and images.
+    pub external_files: Vec<String>,
+    /// World-space bounds of the default scene, `[min, max]`.
+    pub bounds: Option<[[f32; 3]; 2]>,
+    /// Size of the embedded BIN chunk.
+    pub binary_bytes: usize,
+}
+
+impl ModelInfo {
+    pub fn vertex_count(&self) -> u64 {
+        self.meshes.iter().map(|m| m.vertices).sum()
+    }
+
+    pub fn triangle_count(&self) -> u64 {
+        self.meshes.iter().map(|m| m.triangles).sum()
+    }
+
+    /// Size of the bounding box, `[x, y, z]`.
+    pub fn size(&self) -> Option<[f32; 3]> {
+        self.bounds
+            .map(|[min, max]| [max[0] - min[0], max[1] - min[1], max[2] - min[2]])
+    }
+}
+
+pub fn array<'a>(json: &'a Value, key: &str) -> &'a [Value] {
+    json.get(key)
+        .and_then(|v| v.as_array())
+        .map(|a| a.as_slice())
+        .unwrap_or(&[])
+}
+
+fn name_or(item: &Value, fallback: String) -> String {
+    item.get("name")
+        .and_then(|n| n.as_str())
+        .filter(|n| !n.is_empty())
+        .map(str::to_string)
+        .unwrap_or(fallback)
+}
+
+pub fn index(item: &Value, key: &str) -> Option<usize> {
+    item.get(key).and_then(|v| v.as_u64()).map(|v| v as usize)
+}
+
+fn vec3(value: Option<&Value>) -> Option<Vec3> {
+    let a = value?.as_array()?;
+    let at = |i: usize| a.get(i).and_then(|v| v.as_f64()).map(|v| v as f32);
+    Some(Vec3::new(at(0)?, at(1)?, at(2)?))
+}
+
+fn local_transform(node: &Value) -> Mat4 {
+    if let Some(m) = node.get("matrix").and_then(|m| m.as_array()) {
+        let cols: Vec<f32> = m
+            .iter()
+            .filter_map(|v| v.as_f64())
+            .map(|v| v as f32)
+            .collect();
+        if cols.len() == 16 {
+            return Mat4::from_cols_slice(&cols);
+        }
+    }
+    let translation = vec3(node.get("translation")).unwrap_or(Vec3::ZERO);
+    let scale = vec3(node.get("scale")).unwrap_or(Vec3::ONE);
+    let rotation = node
+        .get("rotation")
+        .and_then(|r| r.as_array())
+        .and_then(|r| {
+            let q: Vec<f32> = r
+                .iter()
+                .filter_map(|v| v.as_f64())
+                .map(|v| v as f32)
+                .collect();
+            (q.len() == 4).then(|| Quat::from_xyzw(q[0], q[1], q[2], q[3]).normalize())
+        })
+        .unwrap_or(Quat::IDENTITY);
+    Mat4::from_scale_rotation_translation(scale, rotation, translation)
+}
+
+/// Local bounds of a mesh from the min/max of its POSITION accessors.
+fn mesh_bounds(json: &Value, mesh: &Value) -> Option<(Vec3, Vec3)> {
+    let accessors = array(json, "accessors");
+    let mut bounds: Option<(Vec3, Vec3)> = None;
+    for primitive in array(mesh, "primitives") {
+        let Some(accessor) = primitive
+            .get("attributes")
+            .and_then(|a| index(a, "POSITION"))
+            .and_then(|i| accessors.get(i))
+        else {
+            continue;
+        };
+        let (Some(min), Some(max)) = (vec3(accessor.get("min")), vec3(accessor.get("max"))) else {
+            continue;
+        };
+        bounds = Some(match bounds {
+            Some((lo, hi)) => (lo.min(min), hi.max(max)),
+            None => (min, max),
+        });
+    }
+    bounds
+}
+
+struct Walker<'a> {
+    json: &'a Value,
+    bounds: Option<(Vec3, Vec3)>,
+    /// Nodes from the root down to the one being visited.
+    path: Vec<usize>,
+}
+
+impl Walker<'_> {
+    fn visit(&mut self, node_index: usize, parent: Mat4) -> Option<NodeInfo> {
+        // Cyclic hierarchies are invalid glTF, but don't hang on them.
+        if self.path.contains(&node_index) {
+            return None;
+        }
+        let node = array(self.json, "nodes").get(node_index)?;
+        let world = parent * local_transform(node);
+
+        let mesh =
+            index(node, "mesh").and_then(|i| array(self.json, "meshes").get(i).map(|m| (i, m)));
+        if let Some((_, mesh)) = mesh {
+            if let Some((min, max)) = mesh_bounds(self.json, mesh) {
+                for corner in 0..8 {
+                    let p = world.transform_point3(Vec3::new(
+                        if corner & 1 == 0 { min.x } else { max.x },
+                        if corner & 2 == 0 { min.y } else { max.y },
+                        if corner & 4 == 0 { min.z } else { max.z },
+                    ));
+                    self.bounds = Some(match self.bounds {
+                        Some((lo, hi)) => (lo.min(p), hi.max(p)),
+                        None => (p, p),
+                    });
+                }
+            }
+        }
+
+        self.path.push(node_index);
+        let children = array(node, "children")
+            .iter()
+            .filter_map(|c| c.as_u64())
+            .filter_map(|c| self.visit(c as usize, world))
+            .collect();
+        self.path.pop();
+        Some(NodeInfo {
+            name: name_or(node, format!("node {}", node_index)),
+            mesh: mesh.map(|(i, m)| name_or(m, format!("mesh {}", i))),
+            children,
+        })
+    }
+}
+
+/// Root nodes of the default scene, or every node nobody lists as a child.
+pub fn root_nodes(json: &Value) -> Vec<usize> {
+    let scenes = array(json, "scenes");
+    let scene = index(json, "scene").unwrap_or(0);
+    if let Some(scene) = scenes.get(scene) {
+        return array(scene, "nodes")
+            .iter()
+            .filter_map(|n| n.as_u64())
+            .map(|n| n as usize)
+            .collect();
+    }
+    let nodes = array(json, "nodes");
+    let children: Vec<usize> = nodes
+        .iter()
+        .flat_map(|n| array(n, "children").iter().filter_map(|c| c.as_u64()))
+        .map(|c| c as usize)
+        .collect();
+    (0..nodes.len()).filter(|i| !children.contains(i)).collect()
+}
+
+fn is_external(uri: &str) -> bool {
+    !uri.starts_with("data:")
+}
+
+pub fn inspect(doc: &GltfDocument) -> ModelInfo {
+    let json = &doc.json;
+    let accessors = array(json, "accessors");
+    let materials: Vec<String> = array(json, "materials")
+        .iter()
+        .enumerate()
+        .map(|(i, m)| name_or(m, format!("material {}", i)))
+        .collect();
+
+    let meshes = array(json, "meshes")
+        .iter()
+        .enumerate()
+        .map(|(i, mesh)| {
+            let primitives = array(mesh, "primitives");
+            let count = |accessor: Option<usize>| {
+                accessor
+                    .and_then(|a| accessors.get(a))
+                    .and_then(|a| a.get("count"))
+                    .and_then(|c| c.as_u64())
+                    .unwrap_or(0)
+            };
+            let mut vertices = 0;
+            let mut triangles = 0;
+            let mut mesh_materials = Vec::new();
+            for primitive in primitives {
+                let verts = count(
+                    primitive
+                        .get("attributes")
+                        .and_then(|a| index(a, "POSITION")),
+                );
+                vertices += verts;
+                // Mode 4 (TRIANGLES) is the default; other modes are not counted as triangles.
+                if index(primitive, "mode").unwrap_or(4) == 4 {
+                    triangles += match index(primitive, "indices") {
+                        Some(indices) => count(Some(indices)) / 3,
+                        None => verts / 3,
+                    };
+                }
+                if let Some(name) = index(primitive, "material").and_then(|m| materials.get(m)) {
+                    if !mesh_materials.contains(name) {
+                        mesh_materials.push(name.clone());
+                    }
+                }
+            }
+            MeshInfo {
+                name: name_or(mesh, format!("mesh {}", i)),
+                primitives: primitives.len(),
+                vertices,
+                triangles,
+                materials: mesh_materials,
+            }
+        })
+        .collect();
+
+    let images = array(json, "images");
+    let textures = array(json, "textures")
+        .iter()
+        .filter_map(|t| index(t, "source").and_then(|s| images.get(s)))
+        .map(|image| {
+            let uri = image.get("uri").and_then(|u| u.as_str());
+            TextureInfo {
+                uri: uri.filter(|u| is_external(u)).map(str::to_string),
+                mime_type: image
+                    .get("mimeType")
+                    .and_then(|m| m.as_str())
+                    .map(str::to_string),
+                embedded: uri.is_none_or(|u| !is_external(u)),
+            }
+        })
+        .collect();
+
+    let mut external_files: Vec<String> = array(json, "buffers")
+        .iter()
+        .chain(images)
+        .filter_map(|item| item.get("uri").and_then(|u| u.as_str()))
+        .filter(|u| is_external(u))
+        .map(str::to_string)
+        .collect();
+    external_files.sort();
+    external_files.dedup();
+
+    let mut walker = Walker {
+        json,
+        bounds: None,
+        path: Vec::new(),
+    };
+    let nodes = root_nodes(json)
+        .into_iter()
+        .filter_map(|n| walker.visit(n, Mat4::IDENTITY))
+        .collect();
+
+    ModelInfo {
+        generator: json
+            .get("asset")
+            .and_then(|a| a.get("generator"))
+            .and_then(|g| g.as_str())
+            .map(str::to_string),
+        nodes,
+        meshes,
+        materials,
+        textures,
+        animations: array(json, "animations")
+            .iter()
+            .enumerate()
+            .map(|(i, a)| name_or(a, format!("animation {}", i)))
+            .collect(),
+        external_files,
+        bounds: walker
+            .bounds
+            .map(|(min, max)| [min.to_array(), max.to_array()]),
+        binary_bytes: doc.bin.as_ref().map_or(0, Vec::len),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brp::mock::model;
+    use serde_json::json;
+
+    #[test]
+    fn inspects_a_kenney_road_tile() {
+        let bytes = std::fs::read(model("road-bend.glb")).unwrap();
+        let info = inspect(&parse_glb(&bytes).unwrap());
+
+        assert_eq!(info.nodes.len(), 1);
+        assert_eq!(info.nodes[0].mesh.as_deref(), Some("road-bend"));
+        assert_eq!(info.meshes.len(), 1);
+        assert_eq!(info.meshes[0].triangles, 260);
+        assert_eq!(info.materials, ["colormap"]);
+        assert_eq!(info.external_files, ["Textures/colormap.png"]);
+        assert!(!info.textures[0].embedded);
+
+        let [min, max] = info.bounds.unwrap();
+        let expected = [[-0.5, 0.0, -0.5], [0.5, 0.02, 0.5]];
+        for (got, want) in min.iter().chain(&max).zip(expected.iter().flatten()) {
+            assert!((got - want).abs() < 1e-3, "{:?}", info.bounds);
+        }
+    }
+
+    #[test]
+    fn external_files_are_listed_once() {
+        let doc = GltfDocument {
+            json: json!({
+                "buffers": [{ "uri": "road.bin", "byteLength": 4 }],
+                "images": [
+                    { "uri": "Textures/colormap.png" },
+                    { "uri": "Textures/variation.png" },
+                    { "uri": "Textures/colormap.png" },
+                    { "uri": "data:image/png;base64,iVBORw0KGgo=" }
+                ]
+            }),
+            bin: None,
+        };
+        assert_eq!(
+            inspect(&doc).external_files,
+            [
+                "Textures/colormap.png",
+                "Textures/variation.png",
+                "road.bin"
+            ]
+        );
+        assert!(parse_glb(b"glTF").is_err());
+    }
+
+    #[test]
+    fn large_scenes_are_walked_fully_and_cycles_stop() {
+        let count = 12_000;
+        let mut nodes =
+            vec![json!({ "name": "root", "children": (1..=count).collect::<Vec<_>>() })];
+        nodes.extend((1..=count).map(|i| json!({ "name": format!("lamp {}", i) })));
+        let doc = GltfDocument {
+            json: json!({ "scenes": [{ "nodes": [0] }], "nodes": nodes }),
+            bin: None,
+        };
+        assert_eq!(inspect(&doc).nodes[0].children.len(), count);
+
+        let doc = GltfDocument {
+            json: json!({
+                "scenes": [{ "nodes": [0] }],
+                "nodes": [{ "children": [1] }, { "children": [0, 2] }, {}]
+            }),
+            bin: None,
+        };
+        let nodes = inspect(&doc).nodes;
+        let child = &nodes[0].children[0];
+        assert_eq!(child.name, "node 1");
+        assert_eq!(child.children.len(), 1);
+        assert_eq!(child.children[0].name, "node 2");
+    }
+}
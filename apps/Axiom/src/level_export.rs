@@ -0,0 +1,650 @@
+use crate::glb::{self, array, index, GltfDocument};
+use crate::scene::PlacedAsset;
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use glam::Quat;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Compressed geometry lives in extension-specific buffer layouts we cannot re-pack.
+const UNSUPPORTED_EXTENSIONS: &[&str] = &["KHR_draco_mesh_compression", "EXT_meshopt_compression"];
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExportOptions {
+    /// Put the placements of each model under a parent node named after the model.
+    pub group_by_model: bool,
+}
+
+/// What [`merge`] put into the level file.
+#[derive(Clone, Debug, Default)]
+pub struct ExportSummary {
+    pub placements: usize,
+    /// Distinct source files read.
+    pub models: usize,
+    pub meshes: usize,
+    pub materials: usize,
+    pub images: usize,
+    /// Placements left out, with the reason.
+    pub skipped: Vec<String>,
+}
+
+/// One source model, read once however many times it is placed.
+struct Source {
+    json: Value,
+    dir: PathBuf,
+    buffers: Vec<Vec<u8>>,
+    roots: Vec<usize>,
+    // Source index -> index in the merged document.
+    buffer_views: HashMap<usize, usize>,
+    accessors: HashMap<usize, usize>,
+    images: HashMap<usize, usize>,
+    textures: HashMap<usize, usize>,
+    materials: HashMap<usize, usize>,
+    meshes: HashMap<usize, usize>,
+}
+
+fn read_uri(dir: &Path, uri: &str) -> Result<Vec<u8>> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, encoded) = data
+            .split_once(";base64,")
+            .ok_or_else(|| anyhow!("Only base64 data URIs are supported"))?;
+        return Ok(BASE64.decode(encoded)?);
+    }
+    let path = dir.join(glb::percent_decode(uri));
+    std::fs::read(&path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))
+}
+
+fn load_source(path: &Path) -> Result<Source> {
+    let GltfDocument { json, bin } = glb::load(path)?;
+    if let Some(ext) = array(&json, "extensionsRequired")
+        .iter()
+        .filter_map(|e| e.as_str())
+        .find(|e| UNSUPPORTED_EXTENSIONS.contains(e))
+    {
+        return Err(anyhow!(
+            "{} requires {}, which cannot be merged",
+            path.display(),
+            ext
+        ));
+    }
+
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let mut bin = bin;
+    let buffers = array(&json, "buffers")
+        .iter()
+        .map(|buffer| match buffer.get("uri").and_then(|u| u.as_str()) {
+            Some(uri) => read_uri(&dir, uri),
+            // Only the first buffer of a GLB may omit its URI and use the BIN chunk.
+            None => bin
+                .take()
+                .ok_or_else(|| anyhow!("Buffer without URI and no BIN chunk")),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Source {
+        roots: glb::root_nodes(&json),
+        json,
+        dir,
+        buffers,
+        buffer_views: HashMap::new(),
+        accessors: HashMap::new(),
+        images: HashMap::new(),
+        textures: HashMap::new(),
+        materials: HashMap::new(),
+        meshes: HashMap::new(),
+    })
+}
+
+/// Item `i` of a top-level array of the source, cloned so it can be remapped.
+fn item(src: &Source, key: &str, i: usize) -> Result<Value> {
+    array(&src.json, key)
+        .get(i)
+        .cloned()
+        .ok_or_else(|| anyhow!("{} {} is out of range", key, i))
+}
+
+/// The merged document under construction. Everything lands in a single buffer.
+#[derive(Default)]
+struct Merger {
+    bin: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    images: Vec<Value>,
+    samplers: Vec<Value>,
+    textures: Vec<Value>,
+    materials: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    // Images by content, samplers/textures/materials by their (remapped) JSON.
+    image_by_bytes: HashMap<Vec<u8>, usize>,
+    by_json: HashMap<(&'static str, String), usize>,
+    extensions_used: Vec<String>,
+    extensions_required: Vec<String>,
+}
+
+impl Merger {
+    fn push_view(&mut self, data: &[u8], template: Option<&Value>) -> usize {
+        self.bin.resize(self.bin.len().next_multiple_of(4), 0);
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.bin.len(),
+            "byteLength": data.len(),
+        });
+        for key in ["byteStride", "target"] {
+            if let Some(value) = template.and_then(|t| t.get(key)) {
+                view[key] = value.clone();
+            }
+        }
+        self.bin.extend_from_slice(data);
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    /// Stores `value` in the `key` list unless an identical one is already there.
+    fn dedup(&mut self, key: &'static str, value: Value) -> usize {
+        let text = value.to_string();
+        if let Some(&i) = self.by_json.get(&(key, text.clone())) {
+            return i;
+        }
+        let list = match key {
+            "samplers" => &mut self.samplers,
+            "textures" => &mut self.textures,
+            _ => &mut self.materials,
+        };
+        list.push(value);
+        let i = list.len() - 1;
+        self.by_json.insert((key, text), i);
+        i
+    }
+
+    fn view_bytes<'a>(src: &'a Source, view: &Value) -> Result<&'a [u8]> {
+        let buffer = index(view, "buffer").unwrap_or(0);
+        let offset = index(view, "byteOffset").unwrap_or(0);
+        let length = index(view, "byteLength").unwrap_or(0);
+        src.buffers
+            .get(buffer)
+            .and_then(|b| b.get(offset..offset + length))
+            .ok_or_else(|| anyhow!("bufferView runs past the end of buffer {}", buffer))
+    }
+
+    fn buffer_view(&mut self, src: &mut Source, i: usize) -> Result<usize> {
+        if let Some(&out) = src.buffer_views.get(&i) {
+            return Ok(out);
+        }
+        let view = item(src, "bufferViews", i)?;
+        let out = self.push_view(Self::view_bytes(src, &view)?, Some(&view));
+        src.buffer_views.insert(i, out);
+        Ok(out)
+    }
+
+    fn accessor(&mut self, src: &mut Source, i: usize) -> Result<usize> {
+        if let Some(&out) = src.accessors.get(&i) {
+            return Ok(out);
+        }
+        let mut accessor = item(src, "accessors", i)?;
+        if let Some(view) = index(&accessor, "bufferView") {
+            accessor["bufferView"] = json!(self.buffer_view(src, view)?);
+        }
+        for part in ["indices", "values"] {
+            if let Some(view) = accessor
+                .get("sparse")
+                .and_then(|s| s.get(part))
+                .and_then(|p| index(p, "bufferView"))
+            {
+                accessor["sparse"][part]["bufferView"] = json!(self.buffer_view(src, view)?);
+            }
+        }
+        self.accessors.push(accessor);
+        let out = self.accessors.len() - 1;
+        src.accessors.insert(i, out);
+        Ok(out)
+    }
+
+    /// Embeds an image in the buffer; images with the same bytes are stored once.
+    fn image(&mut self, src: &mut Source, i: usize) -> Result<usize> {
+        if let Some(&out) = src.images.get(&i) {
+            return Ok(out);
+        }
+        let image = item(src, "images", i)?;
+        let bytes = match (
+            index(&image, "bufferView"),
+            image.get("uri").and_then(|u| u.as_str()),
+        ) {
+            (Some(view), _) => Self::view_bytes(src, &item(src, "bufferViews", view)?)?.to_vec(),
+            (None, Some(uri)) => read_uri(&src.dir, uri)?,
+            (None, None) => return Err(anyhow!("Image {} has no data", i)),
+        };
+        let out = match self.image_by_bytes.get(&bytes) {
+            Some(&out) => out,
+            None => {
+                let mime = image
+                    .get("mimeType")
+                    .and_then(|m| m.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| {
+                        if bytes.starts_with(&[0xFF, 0xD8]) {
+                            "image/jpeg".to_string()
+                        } else {
+                            "image/png".to_string()
+                        }
+                    });
+                let view = self.push_view(&bytes, None);
+                let mut merged = json!({ "bufferView": view, "mimeType": mime });
+                if let Some(name) = image.get("name") {
+                    merged["name"] = name.clone();
+                }
+                self.images.push(merged);
+                let out = self.images.len() - 1;
+                self.image_by_bytes.insert(bytes, out);
+                out
+            }
+        };
+        src.images.insert(i, out);
+        Ok(out)
+    }
+
+    fn texture(&mut self, src: &mut Source, i: usize) -> Result<usize> {
+        if let Some(&out) = src.textures.get(&i) {
+            return Ok(out);
+        }
+        let mut texture = item(src, "textures", i)?;
+        if let Some(image) = index(&texture, "source") {
+            texture["source"] = json!(self.image(src, image)?);
+        }
+        if let Some(sampler) = index(&texture, "sampler") {
+            let sampler = item(src, "samplers", sampler)?;
+            texture["sampler"] = json!(self.dedup("samplers", sampler));
+        }
+        let out = self.dedup("textures", texture);
+        src.textures.insert(i, out);
+        Ok(out)
+    }
+
+    /// Rewrites every texture reference (`baseColorTexture`, `normalTexture`, extension
+    /// textures, ...) found anywhere inside a material.
+    fn remap_textures(&mut self, src: &mut Source, value: &mut Value) -> Result<()> {
+        if let Some(object) = value.as_object_mut() {
+            for (key, child) in object.iter_mut() {
+                if key.ends_with("Texture") {
+                    if let Some(texture) = index(child, "index") {
+                        child["index"] = json!(self.texture(src, texture)?);
+                    }
+                }
+                self.remap_textures(src, child)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn material(&mut self, src: &mut Source, i: usize) -> Result<usize> {
+        if let Some(&out) = src.materials.get(&i) {
+            return Ok(out);
+        }
+        let mut material = item(src, "materials", i)?;
+        self.remap_textures(src, &mut material)?;
+        let out = self.dedup("materials", material);
+        src.materials.insert(i, out);
+        Ok(out)
+    }
+
+    fn remap_attributes(&mut self, src: &mut Source, attributes: &mut Value) -> Result<()> {
+        if let Some(attributes) = attributes.as_object_mut() {
+            for accessor in attributes.values_mut() {
+                if let Some(i) = accessor.as_u64() {
+                    *accessor = json!(self.accessor(src, i as usize)?);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn mesh(&mut self, src: &mut Source, i: usize) -> Result<usize> {
+        if let Some(&out) = src.meshes.get(&i) {
+            return Ok(out);
+        }
+        let mut mesh = item(src, "meshes", i)?;
+        if let Some(primitives) = mesh.get_mut("primitives").and_then(|p| p.as_array_mut()) {
+            for primitive in primitives {
+                if let Some(attributes) = primitive.get_mut("attributes") {
+                    self.remap_attributes(src, attributes)?;
+                }
+                if let Some(targets) = primitive.get_mut("targets").and_then(|t| t.as_array_mut()) {
+                    for target in targets {
+                        self.remap_attributes(src, target)?;
+                    }
+                }
+                if let Some(indices) = index(primitive, "indices") {
+                    primitive["indices"] = json!(self.accessor(src, indices)?);
+                }
+                if let Some(material) = index(primitive, "material") {
+                    primitive["material"] = json!(self.material(src, material)?);
+                }
+            }
+        }
+        self.meshes.push(mesh);
+        let out = self.meshes.len() - 1;
+        src.meshes.insert(i, out);
+        Ok(out)
+    }
+
+    /// Copies a source node and its children. Nodes are copied per placement (a glTF node has
+    /// one parent), but the meshes they point at are shared.
+    fn node(&mut self, src: &mut Source, i: usize, depth: usize) -> Result<usize> {
+        if depth > 256 {
+            return Err(anyhow!("Node hierarchy is too deep (cyclic?)"));
+        }
+        let mut node = item(src, "nodes", i)?;
+        let object = node
+            .as_object_mut()
+            .ok_or_else(|| anyhow!("Node {} is not an object", i))?;
+        // Cameras, skins and animations are not carried over.
+        object.remove("camera");
+        object.remove("skin");
+        let children: Vec<usize> = object
+            .remove("children")
+            .and_then(|c| c.as_array().cloned())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|c| c.as_u64())
+            .map(|c| c as usize)
+            .collect();
+        let mesh = object.remove("mesh").and_then(|m| m.as_u64());
+
+        if let Some(mesh) = mesh {
+            node["mesh"] = json!(self.mesh(src, mesh as usize)?);
+        }
+        let children = children
+            .into_iter()
+            .map(|c| self.node(src, c, depth + 1))
+            .collect::<Result<Vec<_>>>()?;
+        if !children.is_empty() {
+            node["children"] = json!(children);
+        }
+        Ok(self.push_node(node))
+    }
+
+    fn push_node(&mut self, node: Value) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn note_extensions(&mut self, src: &Source) {
+        for (key, list) in [
+            ("extensionsUsed", &mut self.extensions_used),
+            ("extensionsRequired", &mut self.extensions_required),
+        ] {
+            for ext in array(&src.json, key).iter().filter_map(|e| e.as_str()) {
+                if !list.iter().any(|e| e == ext) {
+                    list.push(ext.to_string());
+                }
+            }
+        }
+    }
+
+    /// The finished glTF JSON and the buffer it refers to.
+    fn into_document(self, roots: Vec<usize>) -> (Value, Vec<u8>) {
+        let mut json = Map::new();
+        json.insert(
+            "asset".into(),
+            json!({ "version": "2.0", "generator": "Axiom level export" }),
+        );
+        for (key, list) in [
+            ("extensionsUsed", self.extensions_used),
+            ("extensionsRequired", self.extensions_required),
+        ] {
+            if !list.is_empty() {
+                json.insert(key.into(), json!(list));
+            }
+        }
+        json.insert("scene".into(), json!(0));
+        json.insert(
+            "scenes".into(),
+            json!([{ "name": "Level", "nodes": roots }]),
+        );
+        // glTF forbids empty top-level arrays.
+        for (key, list) in [
+            ("nodes", self.nodes),
+            ("meshes", self.meshes),
+            ("materials", self.materials),
+            ("textures", self.textures),
+            ("samplers", self.samplers),
+            ("images", self.images),
+            ("accessors", self.accessors),
+            ("bufferViews", self.buffer_views),
+        ] {
+            if !list.is_empty() {
+                json.insert(key.into(), Value::Array(list));
+            }
+        }
+        if !self.bin.is_empty() {
+            json.insert(
+                "buffers".into(),
+                json!([{ "byteLength": self.bin.len().next_multiple_of(4) }]),
+            );
+        }
+        (Value::Object(json), self.bin)
+    }
+}
+
+fn model_name(placement: &PlacedAsset) -> String {
+    placement
+        .filename
+        .rsplit_once('.')
+        .map_or(placement.filename.as_str(), |(stem, _)| stem)
+        .to_string()
+}
+
+/// Builds one GLB holding every placement, one node per placement carrying its transform.
+/// Each source model is read once and its meshes are shared by all of its placements; identical
+/// images, samplers and materials across models are stored once. Placements whose model cannot
+/// be read are skipped and listed in the summary.
+pub fn merge(
+    placements: &[PlacedAsset],
+    options: ExportOptions,
+) -> Result<(Vec<u8>, ExportSummary)> {
+    let mut merger = Merger::default();
+    let mut sources: HashMap<PathBuf, Result<Source, String>> = HashMap::new();
+    let mut summary = ExportSummary::default();
+    // Placement nodes per model, in first-seen order.
+    let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+
+    for placement in placements {
+        let source = sources
+            .entry(placement.local_path.clone())
+            .or_insert_with(|| {
+                let source = load_source(&placement.local_path).map_err(|e| e.to_string());
+                if let Ok(source) = &source {
+                    merger.note_extensions(source);
+                }
+                source
+            });
+        let source = match source {
+            Ok(source) => source,
+            Err(e) => {
+                summary
+                    .skipped
+                    .push(format!("{}: {}", placement.filename, e));
+                continue;
+            }
+        };
+
+        let roots = source.roots.clone();
+        let children = match roots
+            .into_iter()
+            .map(|root| merger.node(source, root, 0))
+            .collect::<Result<Vec<_>>>()
+        {
+            Ok(children) => children,
+            Err(e) => {
+                summary
+                    .skipped
+                    .push(format!("{}: {}", placement.filename, e));
+                continue;
+            }
+        };
+
+        let name = model_name(placement);
+        let group = match groups.iter().position(|(n, _)| *n == name) {
+            Some(g) => g,
+            None => {
+                groups.push((name.clone(), Vec::new()));
+                groups.len() - 1
+            }
+        };
+        let [rx, ry, rz] = placement.rotation.map(f32::to_radians);
+        // Same convention as bevy_upload_asset, so the export matches the game.
+        let rotation = Quat::from_euler(glam::EulerRot::XYZ, rx, ry, rz);
+        let mut node = json!({
+            "name": format!("{} {}", name, groups[group].1.len() + 1),
+            "translation": placement.translation,
+            "rotation": rotation.to_array(),
+        });
+        if placement.scale != [1.0; 3] {
+            node["scale"] = json!(placement.scale);
+        }
+        if !children.is_empty() {
+            node["children"] = json!(children);
+        }
+        let node = merger.push_node(node);
+        groups[group].1.push(node);
+        summary.placements += 1;
+    }
+
+    if summary.placements == 0 {
+        return Err(anyhow!(
+            "Nothing to export{}",
+            if summary.skipped.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", summary.skipped.join("; "))
+            }
+        ));
+    }
+
+    let roots = if options.group_by_model {
+        groups
+            .into_iter()
+            .map(|(name, children)| merger.push_node(json!({ "name": name, "children": children })))
+            .collect()
+    } else {
+        groups.into_iter().flat_map(|(_, nodes)| nodes).collect()
+    };
+
+    summary.models = sources.values().filter(|s| s.is_ok()).count();
+    summary.meshes = merger.meshes.len();
+    summary.materials = merger.materials.len();
+    summary.images = merger.images.len();
+    let (json, bin) = merger.into_document(roots);
+    Ok((glb::write_glb(&json, &bin)?, summary))
+}
+
+/// [`merge`] written to `path`.
+pub fn export(
+    placements: &[PlacedAsset],
+    path: &Path,
+    options: ExportOptions,
+) -> Result<ExportSummary> {
+    let (bytes, summary) = merge(placements, options)?;
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, bytes)
+        .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place(filename: &str, translation: [f32; 3], yaw: f32) -> PlacedAsset {
+        PlacedAsset::new(
+            None,
+            filename.to_string(),
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("resources")
+                .join("models")
+                .join(filename),
+            translation,
+            [0.0, yaw, 0.0],
+        )
+    }
+
+    fn level() -> Vec<PlacedAsset> {
+        vec![
+            place("road-straight.glb", [0.0, 0.0, 0.0], 0.0),
+            place("road-straight.glb", [1.0, 0.0, 0.0], 90.0),
+            place("road-bend.glb", [2.0, 0.0, 0.0], 180.0),
+        ]
+    }
+
+    #[test]
+    fn shares_meshes_materials_and_images() {
+        let (bytes, summary) = merge(&level(), ExportOptions::default()).unwrap();
+        assert!(summary.skipped.is_empty(), "{:?}", summary.skipped);
+        assert_eq!(summary.placements, 3);
+        assert_eq!(summary.models, 2);
+        // One mesh per model; both Kenney models use the same colormap material and texture.
+        assert_eq!(summary.meshes, 2);
+        assert_eq!(summary.materials, 1);
+        assert_eq!(summary.images, 1);
+
+        let doc = glb::parse_glb(&bytes).unwrap();
+        let info = glb::inspect(&doc);
+        assert_eq!(info.nodes.len(), 3);
+        assert_eq!(info.nodes[0].name, "road-straight 1");
+        assert_eq!(info.nodes[1].name, "road-straight 2");
+        assert_eq!(info.nodes[2].name, "road-bend 1");
+        assert!(info.external_files.is_empty(), "{:?}", info.external_files);
+        assert_eq!(info.textures.len(), 1);
+        assert!(info.textures[0].embedded);
+
+        let [min, max] = info.bounds.unwrap();
+        assert!((min[0] + 0.5).abs() < 0.01 && (max[0] - 2.5).abs() < 0.01);
+
+        assert_eq!(array(&doc.json, "buffers").len(), 1);
+        let bin = doc.bin.unwrap();
+        for view in array(&doc.json, "bufferViews") {
+            let end = index(view, "byteOffset").unwrap() + index(view, "byteLength").unwrap();
+            assert!(end <= bin.len());
+        }
+    }
+
+    #[test]
+    fn matches_source_geometry() {
+        let (bytes, _) = merge(&level()[..1], ExportOptions::default()).unwrap();
+        let merged = glb::inspect(&glb::parse_glb(&bytes).unwrap());
+        let source = glb::inspect(&glb::load(&level()[0].local_path).unwrap());
+        assert_eq!(merged.vertex_count(), source.vertex_count());
+        assert_eq!(merged.triangle_count(), source.triangle_count());
+        assert_eq!(merged.bounds, source.bounds);
+    }
+
+    #[test]
+    fn groups_placements_by_model() {
+        let options = ExportOptions {
+            group_by_model: true,
+        };
+        let (bytes, _) = merge(&level(), options).unwrap();
+        let info = glb::inspect(&glb::parse_glb(&bytes).unwrap());
+        let groups: Vec<(&str, usize)> = info
+            .nodes
+            .iter()
+            .map(|n| (n.name.as_str(), n.children.len()))
+            .collect();
+        assert_eq!(groups, vec![("road-straight", 2), ("road-bend", 1)]);
+    }
+
+    #[test]
+    fn skips_missing_models() {
+        let mut placements = level();
+        placements.push(place("no-such-model.glb", [0.0; 3], 0.0));
+        let (_, summary) = merge(&placements, ExportOptions::default()).unwrap();
+        assert_eq!(summary.placements, 3);
+        assert_eq!(summary.skipped.len(), 1);
+        assert!(summary.skipped[0].starts_with("no-such-model.glb"));
+
+        assert!(merge(&placements[3..], ExportOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,363 @@
+//! Anthropic's Messages API.
+use super::sse::{self, Decoder, SseStream};
+use super::{
+    EventStream, LlmProvider, Message, Part, StreamDeltaFunction, StreamDeltaToolCall, StreamEvent,
+    Usage,
+};
+use anyhow::{anyhow, Result};
+use futures_util::future::BoxFuture;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+pub const DEFAULT_BASE_URL: &str = "https://api.anthropic.com/v1";
+const API_VERSION: &str = "2023-06-01";
+/// The API needs an answer limit; tool-heavy turns can run long.
+const MAX_TOKENS: u32 = 8192;
+
+#[derive(Clone)]
+pub struct AnthropicProvider {
+    api_key: String,
+    base_url: String,
+    model: String,
+    client: Client,
+}
+
+impl AnthropicProvider {
+    pub fn new(api_key: String, base_url: String, model: String) -> Result<Self> {
+        Ok(Self {
+            api_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model,
+            client: super::http_client()?,
+        })
+    }
+
+    async fn stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<Value>>,
+    ) -> Result<EventStream> {
+        let url = format!("{}/messages", self.base_url);
+        let body = request_body(&self.model, &messages, tools.as_deref());
+        let resp = super::post_stream(&url, || {
+            self.client
+                .post(&url)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", API_VERSION)
+                .json(&body)
+        })
+        .await?;
+        Ok(Box::pin(SseStream::new(
+            resp.bytes_stream(),
+            AnthropicDecoder::default(),
+        )))
+    }
+}
+
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn chat_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<Value>>,
+    ) -> BoxFuture<'_, Result<EventStream>> {
+        Box::pin(self.stream(messages, tools))
+    }
+}
+
+/// Messages body: system messages go to `system`, tool calls become `tool_use` blocks and
+/// their results `tool_result` blocks of the next user turn.
+pub fn request_body(model: &str, messages: &[Message], tools: Option<&[Value]>) -> Value {
+    let mut system = Vec::new();
+    let mut turns: Vec<Value> = Vec::new();
+
+    for message in messages {
+        let mut blocks = Vec::new();
+        let role = match message.role.as_str() {
+            "system" => {
+                if let Some(content) = &message.content {
+                    system.push(content.text());
+                }
+                continue;
+            }
+            "assistant" => {
+                if let Some(content) = &message.content {
+                    let text = content.text();
+                    if !text.is_empty() {
+                        blocks.push(json!({ "type": "text", "text": text }));
+                    }
+                }
+                for call in message.tool_calls.iter().flatten() {
+                    blocks.push(json!({
+                        "type": "tool_use",
+                        "id": call.id,
+                        "name": call.function.name,
+                        "input": super::parse_arguments(&call.function.arguments)
+                    }));
+                }
+                "assistant"
+            }
+            "tool" => {
+                blocks.push(json!({
+                    "type": "tool_result",
+                    "tool_use_id": message.tool_call_id,
+                    "content": message.content.as_ref().map(|c| c.text()).unwrap_or_default()
+                }));
+                "user"
+            }
+            _ => {
+                for part in message.content.iter().flat_map(|c| c.parts()) {
+                    blocks.push(match part {
+                        Part::Text(text) => json!({ "type": "text", "text": text }),
+                        Part::Image { mime, data } => json!({
+                            "type": "image",
+                            "source": { "type": "base64", "media_type": mime, "data": data }
+                        }),
+                    });
+                }
+                "user"
+            }
+        };
+        if blocks.is_empty() {
+            continue;
+        }
+        // Roles have to alternate, so tool results and a following user message merge.
+        match turns.last_mut() {
+            Some(last) if last["role"] == role => {
+                last["content"].as_array_mut().unwrap().extend(blocks);
+            }
+            _ => turns.push(json!({ "role": role, "content": blocks })),
+        }
+    }
+
+    let mut body = json!({
+        "model": model,
+        "max_tokens": MAX_TOKENS,
+        "messages": turns,
+        "stream": true
+    });
+    if !system.is_empty() {
+        body["system"] = json!(system.join("\n\n"));
+    }
+    if let Some(tools) = tools.filter(|t| !t.is_empty()) {
+        body["tools"] = super::functions(tools)
+            .map(|f| {
+                json!({
+                    "name": f["name"],
+                    "description": f["description"],
+                    "input_schema": f["parameters"]
+                })
+            })
+            .collect();
+    }
+    body
+}
+
+/// `event:`/`data:` pairs; the data carries its own `type`, so only data lines are read.
+/// Tool inputs stream as JSON fragments of the block at `index`.
+#[derive(Default)]
+pub struct AnthropicDecoder {
+    /// Tool blocks that have not received any input yet.
+    empty_tools: HashSet<i64>,
+    /// Prompt tokens from `message_start`; output tokens come with `message_delta`.
+    input_tokens: u64,
+}
+
+impl Decoder for AnthropicDecoder {
+    fn line(&mut self, line: &str) -> Result<Vec<StreamEvent>> {
+        let Some(data) = sse::data(line) else {
+            return Ok(Vec::new());
+        };
+        let event: Value = serde_json::from_str(data)?;
+        let index = event["index"].as_i64().unwrap_or(0);
+        let tool_chunk = |id: Option<String>, name: Option<String>, arguments: Option<String>| {
+            StreamEvent::ToolCallChunk(StreamDeltaToolCall {
+                index: index as i32,
+                id,
+                r#type: Some("function".to_string()),
+                function: Some(StreamDeltaFunction { name, arguments }),
+            })
+        };
+
+        let events = match event["type"].as_str().unwrap_or("") {
+            "content_block_start" => {
+                let block = &event["content_block"];
+                match block["type"].as_str() {
+                    Some("tool_use") => {
+                        self.empty_tools.insert(index);
+                        vec![tool_chunk(
+                            block["id"].as_str().map(str::to_string),
+                            block["name"].as_str().map(str::to_string),
+                            None,
+                        )]
+                    }
+                    Some("text") => match block["text"].as_str().filter(|t| !t.is_empty()) {
+                        Some(text) => vec![StreamEvent::TextChunk(text.to_string())],
+                        None => Vec::new(),
+                    },
+                    _ => Vec::new(),
+                }
+            }
+            "content_block_delta" => {
+                let delta = &event["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => {
+                        vec![StreamEvent::TextChunk(
+                            delta["text"].as_str().unwrap_or("").to_string(),
+                        )]
+                    }
+                    Some("input_json_delta") => {
+                        let json = delta["partial_json"].as_str().unwrap_or("");
+                        if json.is_empty() {
+                            Vec::new()
+                        } else {
+                            self.empty_tools.remove(&index);
+                            vec![tool_chunk(None, None, Some(json.to_string()))]
+                        }
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            // A tool without parameters gets no input deltas at all.
+            "content_block_stop" if self.empty_tools.remove(&index) => {
+                vec![tool_chunk(None, None, Some("{}".to_string()))]
+            }
+            "message_start" => {
+                self.input_tokens = event["message"]["usage"]["input_tokens"]
+                    .as_u64()
+                    .unwrap_or(0);
+                Vec::new()
+            }
+            "message_delta" => {
+                let mut events = Vec::new();
+                if let Some(reason) = event["delta"]["stop_reason"].as_str() {
+                    events.push(StreamEvent::Finish(reason.to_string()));
+                }
+                let usage = &event["usage"];
+                if usage.is_object() {
+                    events.push(StreamEvent::Usage(Usage {
+                        input_tokens: usage["input_tokens"].as_u64().unwrap_or(self.input_tokens),
+                        output_tokens: usage["output_tokens"].as_u64().unwrap_or(0),
+                    }));
+                }
+                events
+            }
+            "message_stop" => vec![StreamEvent::Done],
+            "error" => {
+                return Err(anyhow!(
+                    "Anthropic error ({}): {}",
+                    event["error"]["type"].as_str().unwrap_or("unknown"),
+                    event["error"]["message"].as_str().unwrap_or("")
+                ))
+            }
+            _ => Vec::new(),
+        };
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::sse::fixtures::{outcome, replay, text, tool_calls};
+    use crate::llm::{FunctionCall, MessageContent, ToolCall};
+
+    #[test]
+    fn decodes_recorded_stream_with_tool_use() {
+        let events = replay(
+            include_str!("fixtures/anthropic.sse"),
+            AnthropicDecoder::default(),
+        );
+        assert_eq!(text(&events), "I'll check the scene first.");
+        assert_eq!(
+            tool_calls(&events),
+            [
+                (
+                    1,
+                    Some("toolu_01A".to_string()),
+                    "query_scene".to_string(),
+                    r#"{"filter": "lamp"}"#.to_string()
+                ),
+                (
+                    2,
+                    Some("toolu_01B".to_string()),
+                    "scene_minimap".to_string(),
+                    "{}".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            outcome(&events),
+            (
+                Some("tool_use".to_string()),
+                Some(Usage {
+                    input_tokens: 1472,
+                    output_tokens: 89
+                })
+            )
+        );
+        assert!(matches!(events.last(), Some(StreamEvent::Done)));
+    }
+
+    #[test]
+    fn stream_errors_are_reported() {
+        let mut decoder = AnthropicDecoder::default();
+        let err = decoder
+            .line(r#"data: {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Anthropic error (overloaded_error): Overloaded"
+        );
+    }
+
+    #[test]
+    fn tool_results_share_the_user_turn() {
+        let text = |role: &str, text: &str| Message {
+            role: role.to_string(),
+            content: Some(MessageContent::Text(text.to_string())),
+            tool_calls: None,
+            tool_call_id: None,
+        };
+        let calls: Vec<ToolCall> = ["toolu_01A", "toolu_01B"]
+            .iter()
+            .map(|id| ToolCall {
+                id: id.to_string(),
+                r#type: "function".to_string(),
+                function: FunctionCall {
+                    name: "query_scene".to_string(),
+                    arguments: r#"{"filter":"lamp"}"#.to_string(),
+                },
+            })
+            .collect();
+        let mut assistant = text("assistant", "Looking.");
+        assistant.tool_calls = Some(calls);
+        let results = ["toolu_01A", "toolu_01B"].map(|id| Message {
+            tool_call_id: Some(id.to_string()),
+            ..text("tool", "2 lamps")
+        });
+        let mut messages = vec![
+            text("system", "You are Axiom."),
+            text("user", "Lamps?"),
+            assistant,
+        ];
+        messages.extend(results);
+
+        let body = request_body("claude-sonnet-4-5", &messages, None);
+        assert_eq!(body["system"], "You are Axiom.");
+        let turns = body["messages"].as_array().unwrap();
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[1]["content"][1]["input"]["filter"], "lamp");
+        assert_eq!(turns[2]["role"], "user");
+        assert_eq!(turns[2]["content"][1]["tool_use_id"], "toolu_01B");
+        assert!(body.get("tools").is_none());
+    }
+}
@@ -1,357 +1,337 @@
-use serde::{Deserialize, Serialize};
-use anyhow::{Context as _, Result};
+//! Gemini's native `streamGenerateContent` API.
+use super::sse::{self, Decoder, SseStream};
+use super::{
+    fresh_call_id, EventStream, LlmProvider, Message, Part, StreamDeltaToolCall, StreamEvent, Usage,
+};
+use anyhow::{anyhow, Result};
+use futures_util::future::BoxFuture;
 use reqwest::Client;
-use serde_json::Value;
-use futures_util::Stream;
-use std::pin::Pin;
-use std::task::{Context, Poll};
+use serde_json::{json, Value};
 
-use std::time::Duration;
-use tokio::time::sleep;
+pub const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
 
 #[derive(Clone)]
-pub struct GeminiClient {
+pub struct GeminiProvider {
     api_key: String,
+    base_url: String,
     model: String,
     client: Client,
 }
 
-#[derive(Serialize)]
-struct ChatCompletionRequest {
-    model: String,
-    messages: Vec<Message>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    tools: Option<Vec<Value>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    stream: Option<bool>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(untagged)]
-pub enum MessageContent {
-    Text(String),
-    Parts(Vec<ContentPart>),
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct ContentPart {
-    pub r#type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub text: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub image_url: Option<ImageUrl>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct ImageUrl {
-    pub url: String,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Message {
-    pub role: String,
-    pub content: Option<MessageContent>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tool_calls: Option<Vec<ToolCall>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tool_call_id: Option<String>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct ToolCall {
-    pub id: String,
-    pub r#type: String,
-    pub function: FunctionCall,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct FunctionCall {
-    pub name: String,
-    pub arguments: String,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[allow(dead_code)]
-pub struct ChatCompletionResponse {
-    pub choices: Vec<Choice>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[allow(dead_code)]
-pub struct Choice {
-    pub message: Message,
-}
-
-// Stream types
-#[derive(Debug, Clone)]
-pub enum StreamEvent {
-    TextChunk(String),
-    ToolCallChunk(StreamDeltaToolCall),
-    Done,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct StreamChunk {
-    #[allow(dead_code)]
-    pub id: Option<String>,
-    pub choices: Vec<StreamChoice>,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct StreamChoice {
-    pub delta: StreamDelta,
-    #[allow(dead_code)]
-    pub finish_reason: Option<String>,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct StreamDelta {
-    pub content: Option<String>,
-    pub tool_calls: Option<Vec<StreamDeltaToolCall>>,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-pub struct StreamDeltaToolCall {
-    #[allow(dead_code)]
-    pub index: i32,
-    pub id: Option<String>,
-    pub r#type: Option<String>,
-    pub function: Option<StreamDeltaFunction>,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-pub struct StreamDeltaFunction {
-    pub name: Option<String>,
-    pub arguments: Option<String>,
-}
-
-impl GeminiClient {
-    pub fn new(api_key: String, model: String) -> Result<Self> {
-        let builder = Client::builder();
-        
-        println!("GeminiClient::new called");
-        
-        // We only configure proxy if explicitly set, otherwise we trust the local rotation proxy
-        // which the user provided (http://127.0.0.1:8045).
-        // Since that local proxy is an OpenAI adapter, we likely don't need an upstream HTTPS_PROXY for it
-        // unless it's running on a different machine (unlikely for 127.0.0.1).
-        
-        if let Ok(proxy_url) = std::env::var("HTTPS_PROXY").or_else(|_| std::env::var("https_proxy")) {
-             // Only apply if the target isn't localhost/127.0.0.1, OR if the user really wants it.
-             // But usually for local dev we don't proxy localhost.
-             // Assuming the user might have set it for other things.
-             // Let's just log it for now.
-             println!("HTTPS_PROXY env var found: {}", proxy_url);
-        }
-
-        let client = builder
-            .build()
-            .context("Failed to build reqwest client")?;
-
+impl GeminiProvider {
+    pub fn new(api_key: String, base_url: String, model: String) -> Result<Self> {
         Ok(Self {
             api_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
             model,
-            client,
+            client: super::http_client()?,
         })
     }
 
-    pub async fn chat_completion_stream(
+    async fn stream(
         &self,
         messages: Vec<Message>,
         tools: Option<Vec<Value>>,
-    ) -> Result<impl Stream<Item = Result<StreamEvent>>> {
-        // Default to local proxy as requested by user example
-        let base_url = std::env::var("GEMINI_BASE_URL").unwrap_or_else(|_| "http://127.0.0.1:8045/v1".to_string());
-        let url = format!("{}/chat/completions", base_url.trim_end_matches('/'));
-
-        println!("Sending OpenAI-compatible STREAM request to: {}", url);
+    ) -> Result<EventStream> {
+        let url = format!(
+            "{}/models/{}:streamGenerateContent?alt=sse",
+            self.base_url, self.model
+        );
+        let body = request_body(&messages, tools.as_deref());
+        let resp = super::post_stream(&url, || {
+            self.client
+                .post(&url)
+                .header("x-goog-api-key", &self.api_key)
+                .json(&body)
+        })
+        .await?;
+        Ok(Box::pin(SseStream::new(
+            resp.bytes_stream(),
+            GeminiDecoder::default(),
+        )))
+    }
+}
 
-        let request_body = ChatCompletionRequest {
-            model: self.model.clone(),
-            messages,
-            tools,
-            stream: Some(true),
-        };
+impl LlmProvider for GeminiProvider {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
 
-        let mut retry_count = 0;
-        const MAX_RETRIES: u32 = 3;
-        let mut base_delay = 2; // seconds
+    fn model(&self) -> &str {
+        &self.model
+    }
 
-        loop {
-            let response = self.client
-                .post(&url)
-                .header("Authorization", format!("Bearer {}", self.api_key))
-                .json(&request_body)
-                .send()
-                .await;
+    fn chat_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<Value>>,
+    ) -> BoxFuture<'_, Result<EventStream>> {
+        Box::pin(self.stream(messages, tools))
+    }
+}
 
-            match response {
-                Ok(resp) => {
-                    if resp.status().is_success() {
-                        println!("Successfully sent stream request");
-                        let stream = resp.bytes_stream();
-                        return Ok(SseStream::new(stream));
-                    } else if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS || resp.status().as_u16() == 429 {
-                        if retry_count >= MAX_RETRIES {
-                            let error_text = resp.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-                            return Err(anyhow::anyhow!("API error (Rate Limit Exceeded): {}", error_text));
-                        }
-                        println!("Rate limited (429). Retrying in {} seconds...", base_delay);
-                        sleep(Duration::from_secs(base_delay)).await;
-                        retry_count += 1;
-                        base_delay *= 2; // Exponential backoff
-                        continue;
-                    } else {
-                        // Check for other errors (like 500) that might be transient
-                         if resp.status().is_server_error() && retry_count < MAX_RETRIES {
-                            println!("Server error ({}). Retrying in {} seconds...", resp.status(), base_delay);
-                            sleep(Duration::from_secs(base_delay)).await;
-                            retry_count += 1;
-                            base_delay *= 2; 
-                            continue;
-                         }
-                        
-                        let error_text = resp.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-                        return Err(anyhow::anyhow!("API error: {}", error_text));
-                    }
+/// `generateContent` body: system messages become the system instruction, the assistant is
+/// `model` and tool results are `functionResponse` parts of a user turn.
+pub fn request_body(messages: &[Message], tools: Option<&[Value]>) -> Value {
+    let names = super::tool_names(messages);
+    let mut system = Vec::new();
+    let mut contents: Vec<Value> = Vec::new();
+
+    for message in messages {
+        let mut parts = Vec::new();
+        let role = match message.role.as_str() {
+            "system" => {
+                if let Some(content) = &message.content {
+                    system.push(json!({ "text": content.text() }));
                 }
-                Err(e) => {
-                    println!("Failed to send stream request: {}", e);
-                    if retry_count >= MAX_RETRIES {
-                         return Err(anyhow::anyhow!("Network error: {}", e));
+                continue;
+            }
+            "assistant" => {
+                if let Some(content) = &message.content {
+                    let text = content.text();
+                    if !text.is_empty() {
+                        parts.push(json!({ "text": text }));
                     }
-                    sleep(Duration::from_secs(base_delay)).await;
-                    retry_count += 1;
-                    base_delay *= 2;
                 }
+                for call in message.tool_calls.iter().flatten() {
+                    parts.push(json!({ "functionCall": {
+                        "name": call.function.name,
+                        "args": super::parse_arguments(&call.function.arguments)
+                    }}));
+                }
+                "model"
+            }
+            "tool" => {
+                let id = message.tool_call_id.as_deref().unwrap_or("");
+                let result = message
+                    .content
+                    .as_ref()
+                    .map(|c| c.text())
+                    .unwrap_or_default();
+                parts.push(json!({ "functionResponse": {
+                    "name": names.get(id).copied().unwrap_or(id),
+                    "response": { "result": result }
+                }}));
+                "user"
+            }
+            _ => {
+                for part in message.content.iter().flat_map(|c| c.parts()) {
+                    parts.push(match part {
+                        Part::Text(text) => json!({ "text": text }),
+                        Part::Image { mime, data } => {
+                            json!({ "inlineData": { "mimeType": mime, "data": data } })
+                        }
+                    });
+                }
+                "user"
             }
+        };
+        if parts.is_empty() {
+            continue;
+        }
+        // Parallel tool results and the messages after them have to share one turn.
+        match contents.last_mut() {
+            Some(last) if last["role"] == role => {
+                last["parts"].as_array_mut().unwrap().extend(parts);
+            }
+            _ => contents.push(json!({ "role": role, "parts": parts })),
         }
     }
-}
 
-#[allow(dead_code)]
-impl GeminiClient {
-    pub async fn chat_completion(
-        &self,
-        messages: Vec<Message>,
-        tools: Option<Vec<Value>>,
-    ) -> Result<ChatCompletionResponse> {
-        // Default to local proxy as requested by user example
-        let base_url = std::env::var("GEMINI_BASE_URL").unwrap_or_else(|_| "http://127.0.0.1:8045/v1".to_string());
-        let url = format!("{}/chat/completions", base_url.trim_end_matches('/'));
+    let mut body = json!({ "contents": contents });
+    if !system.is_empty() {
+        body["systemInstruction"] = json!({ "parts": system });
+    }
+    if let Some(tools) = tools.filter(|t| !t.is_empty()) {
+        let declarations: Vec<Value> = super::functions(tools)
+            .map(|f| {
+                json!({
+                    "name": f["name"],
+                    "description": f["description"],
+                    "parametersJsonSchema": f["parameters"]
+                })
+            })
+            .collect();
+        body["tools"] = json!([{ "functionDeclarations": declarations }]);
+    }
+    body
+}
 
-        println!("Sending OpenAI-compatible request to: {}", url);
+/// `data: {GenerateContentResponse}` lines. Function calls come whole and usually without an
+/// id, numbered and given one here.
+#[derive(Default)]
+pub struct GeminiDecoder {
+    next_call: i32,
+}
 
-        let request_body = ChatCompletionRequest {
-            model: self.model.clone(),
-            messages,
-            tools,
-            stream: None,
+impl Decoder for GeminiDecoder {
+    fn line(&mut self, line: &str) -> Result<Vec<StreamEvent>> {
+        let Some(data) = sse::data(line) else {
+            return Ok(Vec::new());
         };
-
-        let response = self.client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&request_body)
-            .send()
-            .await;
-
-        match &response {
-            Ok(_) => println!("Successfully sent request"),
-            Err(e) => println!("Failed to send request: {}", e),
+        let chunk: Value = serde_json::from_str(data)?;
+        if let Some(error) = chunk.get("error") {
+            return Err(anyhow!("Gemini error: {}", error["message"]));
         }
 
-        let response = response.context("Failed to send request to API")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(anyhow::anyhow!("API error: {}", error_text));
+        let mut events = Vec::new();
+        let Some(candidate) = chunk["candidates"].get(0) else {
+            return Ok(events);
+        };
+        for part in candidate["content"]["parts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            if let Some(call) = part.get("functionCall") {
+                let id = call["id"]
+                    .as_str()
+                    .map_or_else(fresh_call_id, str::to_string);
+                events.push(StreamEvent::ToolCallChunk(StreamDeltaToolCall::complete(
+                    self.next_call,
+                    Some(id),
+                    call["name"].as_str().unwrap_or(""),
+                    call.get("args").unwrap_or(&json!({})).to_string(),
+                )));
+                self.next_call += 1;
+            } else if part["thought"] != true {
+                if let Some(text) = part["text"].as_str().filter(|t| !t.is_empty()) {
+                    events.push(StreamEvent::TextChunk(text.to_string()));
+                }
+            }
         }
-
-        let response_body: ChatCompletionResponse = response.json().await
-            .context("Failed to parse API response")?;
-
-        Ok(response_body)
+        // Usage is repeated as a running total, the last chunk has the final one.
+        if let Some(reason) = candidate["finishReason"].as_str() {
+            events.push(StreamEvent::Finish(reason.to_string()));
+            let usage = &chunk["usageMetadata"];
+            if usage.is_object() {
+                events.push(StreamEvent::Usage(Usage {
+                    input_tokens: usage["promptTokenCount"].as_u64().unwrap_or(0),
+                    output_tokens: usage["candidatesTokenCount"].as_u64().unwrap_or(0)
+                        + usage["thoughtsTokenCount"].as_u64().unwrap_or(0),
+                }));
+            }
+            events.push(StreamEvent::Done);
+        }
+        Ok(events)
     }
 }
 
-
-pub struct SseStream<S> {
-    inner: S,
-    buffer: Vec<u8>,
-}
-
-impl<S> SseStream<S> {
-    pub fn new(inner: S) -> Self {
-        Self {
-            inner,
-            buffer: Vec::new(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::sse::fixtures::{outcome, replay, text, tool_calls};
+    use crate::llm::{ContentPart, FunctionCall, ImageUrl, MessageContent, ToolCall};
+
+    fn message(role: &str, text: &str) -> Message {
+        Message {
+            role: role.to_string(),
+            content: Some(MessageContent::Text(text.to_string())),
+            tool_calls: None,
+            tool_call_id: None,
         }
     }
-}
-
-impl<S, B> Stream for SseStream<S>
-where
-    S: Stream<Item = reqwest::Result<B>> + Unpin,
-    B: AsRef<[u8]>,
-{
-    type Item = Result<StreamEvent>;
-
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        loop {
-            // Check buffer for newline
-            if let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
-                let line_bytes = self.buffer.drain(..pos + 1).collect::<Vec<u8>>();
-                let line_str = String::from_utf8_lossy(&line_bytes[..line_bytes.len() - 1]).to_string();
-                let line = line_str.trim();
 
-                if !line.is_empty() {
-                     // println!("SSE Received Line: {}", line);
-                }
-
-                if line.starts_with("data: ") {
-                    let data = &line[6..];
-                    if data == "[DONE]" {
-                        return Poll::Ready(Some(Ok(StreamEvent::Done)));
-                    }
+    #[test]
+    fn decodes_recorded_stream_with_parallel_calls() {
+        let events = replay(
+            include_str!("fixtures/gemini.sse"),
+            GeminiDecoder::default(),
+        );
+        assert_eq!(text(&events), "Two lamps, one on each side.");
+        let calls = tool_calls(&events);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].0, 0);
+        assert_eq!(calls[1].0, 1);
+        assert_eq!(calls[1].2, "bevy_spawn_primitive");
+        assert_eq!(
+            serde_json::from_str::<Value>(&calls[1].3).unwrap(),
+            json!({ "shape": "sphere", "position": [-2, 0, 2] })
+        );
+        assert_eq!(
+            outcome(&events),
+            (
+                Some("STOP".to_string()),
+                Some(Usage {
+                    input_tokens: 812,
+                    output_tokens: 41
+                })
+            )
+        );
+        assert!(matches!(events.last(), Some(StreamEvent::Done)));
+    }
 
-                    // Parse OpenAI-compatible Stream Response
-                    match serde_json::from_str::<StreamChunk>(data) {
-                        Ok(chunk) => {
-                             if let Some(choice) = chunk.choices.first() {
-                                if let Some(content) = &choice.delta.content {
-                                    if !content.is_empty() {
-                                        return Poll::Ready(Some(Ok(StreamEvent::TextChunk(content.clone()))));
-                                    }
-                                }
-                                if let Some(tool_calls) = &choice.delta.tool_calls {
-                                    if let Some(tool_call) = tool_calls.first() {
-                                        return Poll::Ready(Some(Ok(StreamEvent::ToolCallChunk(tool_call.clone()))));
-                                    }
-                                }
-                            }
-                        }
-                        Err(_e) => {
-                             // Ignore parse errors
-                        }
-                    }
-                }
-                continue;
-            }
+    #[test]
+    fn calls_without_ids_get_ids_unique_across_turns() {
+        let ids: Vec<String> = (0..2)
+            .flat_map(|_| {
+                let events = replay(
+                    include_str!("fixtures/gemini.sse"),
+                    GeminiDecoder::default(),
+                );
+                tool_calls(&events)
+            })
+            .map(|call| call.1.unwrap())
+            .collect();
+        assert_eq!(ids.len(), 4);
+        let unique: std::collections::HashSet<&String> = ids.iter().collect();
+        assert_eq!(unique.len(), 4, "{:?}", ids);
+    }
 
-            // No newline found, pull more data
-            match Pin::new(&mut self.inner).poll_next(cx) {
-                Poll::Ready(Some(Ok(chunk))) => {
-                    self.buffer.extend_from_slice(chunk.as_ref());
-                }
-                Poll::Ready(Some(Err(e))) => {
-                    return Poll::Ready(Some(Err(anyhow::Error::from(e))));
-                },
-                Poll::Ready(None) => {
-                    return Poll::Ready(None);
-                },
-                Poll::Pending => return Poll::Pending,
-            }
-        }
+    #[test]
+    fn builds_turns_with_function_responses_and_images() {
+        let mut assistant = message("assistant", "");
+        assistant.tool_calls = Some(vec![ToolCall {
+            id: "call_0".to_string(),
+            r#type: "function".to_string(),
+            function: FunctionCall {
+                name: "scene_minimap".to_string(),
+                arguments: "{}".to_string(),
+            },
+        }]);
+        let mut result = message("tool", "rendered");
+        result.tool_call_id = Some("call_0".to_string());
+        let image = Message {
+            role: "user".to_string(),
+            content: Some(MessageContent::Parts(vec![ContentPart {
+                r#type: "image_url".to_string(),
+                text: None,
+                image_url: Some(ImageUrl {
+                    url: "data:image/png;base64,iVBORw0KGgo=".to_string(),
+                }),
+            }])),
+            tool_calls: None,
+            tool_call_id: None,
+        };
+        let messages = vec![
+            message("system", "You are Axiom."),
+            message("user", "Show me the map"),
+            assistant,
+            result,
+            image,
+        ];
+        let tools = vec![json!({ "type": "function", "function": {
+            "name": "scene_minimap", "description": "Render", "parameters": { "type": "object" }
+        }})];
+
+        let body = request_body(&messages, Some(&tools));
+        assert_eq!(
+            body["systemInstruction"]["parts"][0]["text"],
+            "You are Axiom."
+        );
+        let contents = body["contents"].as_array().unwrap();
+        assert_eq!(contents.len(), 3);
+        assert_eq!(
+            contents[1]["parts"][0]["functionCall"]["name"],
+            "scene_minimap"
+        );
+        // The tool result and the minimap image share the user turn after the call.
+        let parts = &contents[2]["parts"];
+        assert_eq!(parts[0]["functionResponse"]["name"], "scene_minimap");
+        assert_eq!(parts[1]["inlineData"]["mimeType"], "image/png");
+        assert_eq!(
+            body["tools"][0]["functionDeclarations"][0]["parametersJsonSchema"]["type"],
+            "object"
+        );
     }
 }
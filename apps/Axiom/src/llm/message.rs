@@ -0,0 +1,149 @@
+//! Chat messages and stream events shared by every provider. They follow the OpenAI chat format,
+//! which the other backends translate to and from.
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ContentPart {
+    pub r#type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<ImageUrl>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImageUrl {
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Message {
+    pub role: String,
+    pub content: Option<MessageContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ToolCall {
+    pub id: String,
+    pub r#type: String,
+    pub function: FunctionCall,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FunctionCall {
+    pub name: String,
+    pub arguments: String,
+}
+
+/// One piece of a streamed answer. Tool calls arrive in fragments keyed by `index`.
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    TextChunk(String),
+    ToolCallChunk(StreamDeltaToolCall),
+    /// Why the model stopped, in the provider's words (`stop`, `tool_calls`, `max_tokens`, ..).
+    Finish(String),
+    /// Tokens used by the request, sent once near the end.
+    Usage(Usage),
+    Done,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// Whether a finish reason means the answer was cut off at the token limit.
+pub fn is_truncated(finish_reason: &str) -> bool {
+    matches!(finish_reason, "length" | "max_tokens" | "MAX_TOKENS")
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct StreamDeltaToolCall {
+    pub index: i32,
+    pub id: Option<String>,
+    pub r#type: Option<String>,
+    pub function: Option<StreamDeltaFunction>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct StreamDeltaFunction {
+    pub name: Option<String>,
+    pub arguments: Option<String>,
+}
+
+impl StreamDeltaToolCall {
+    /// A call that arrives whole, as Gemini and Ollama send them.
+    pub fn complete(index: i32, id: Option<String>, name: &str, arguments: String) -> Self {
+        Self {
+            index,
+            id,
+            r#type: Some("function".to_string()),
+            function: Some(StreamDeltaFunction {
+                name: Some(name.to_string()),
+                arguments: Some(arguments),
+            }),
+        }
+    }
+}
+
+/// A piece of message content in the form the native APIs need.
+pub enum Part<'a> {
+    Text(&'a str),
+    /// Base64 image from a `data:` URL.
+    Image {
+        mime: &'a str,
+        data: &'a str,
+    },
+}
+
+impl MessageContent {
+    /// Text and images, with images given by other URLs than `data:` passed on as text.
+    pub fn parts(&self) -> Vec<Part<'_>> {
+        match self {
+            MessageContent::Text(text) => vec![Part::Text(text)],
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| {
+                    if let Some(url) = &part.image_url {
+                        return Some(match data_url(&url.url) {
+                            Some((mime, data)) => Part::Image { mime, data },
+                            None => Part::Text(&url.url),
+                        });
+                    }
+                    part.text.as_deref().map(Part::Text)
+                })
+                .collect(),
+        }
+    }
+
+    /// All text of the content, images left out.
+    pub fn text(&self) -> String {
+        self.parts()
+            .into_iter()
+            .filter_map(|part| match part {
+                Part::Text(text) => Some(text),
+                Part::Image { .. } => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Media type and base64 payload of `data:<mime>;base64,<data>`.
+fn data_url(url: &str) -> Option<(&str, &str)> {
+    let (header, data) = url.strip_prefix("data:")?.split_once(',')?;
+    let mime = header.strip_suffix(";base64")?;
+    Some((mime, data))
+}
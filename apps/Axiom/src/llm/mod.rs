@@ -1,7 +1,197 @@
+//! Chat model backends behind [`LlmProvider`]. Every profile picks one by name: an
+//! OpenAI-compatible endpoint (the default, e.g. a local proxy), Gemini's native
+//! `generateContent`, Anthropic Messages or a local Ollama.
+pub mod anthropic;
 pub mod gemini;
+mod message;
+pub mod ollama;
+pub mod openai;
+pub mod sse;
 
-pub use gemini::{
-    GeminiClient, Message, MessageContent, ContentPart, ImageUrl,
-    StreamEvent,
-    ToolCall, FunctionCall
+pub use message::{
+    is_truncated, ContentPart, FunctionCall, ImageUrl, Message, MessageContent, Part,
+    StreamDeltaFunction, StreamDeltaToolCall, StreamEvent, ToolCall, Usage,
 };
+
+use crate::types::AgentProfile;
+use anyhow::{anyhow, Context as _, Result};
+use futures_util::future::BoxFuture;
+use futures_util::Stream;
+use reqwest::{Client, RequestBuilder, Response};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// Streamed answer of a provider.
+pub type EventStream = Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send>>;
+
+/// Names accepted in `AgentProfile::provider`.
+pub const PROVIDERS: &[&str] = &["openai", "gemini", "anthropic", "ollama"];
+
+/// A chat model API. Messages and tools are given in the OpenAI format and translated by
+/// each backend.
+pub trait LlmProvider: Send + Sync {
+    /// One of [`PROVIDERS`].
+    fn name(&self) -> &'static str;
+
+    fn model(&self) -> &str;
+
+    /// Sends the conversation and streams the answer. `tools` are OpenAI function schemas
+    /// (`{"type": "function", "function": {..}}`).
+    fn chat_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<Value>>,
+    ) -> BoxFuture<'_, Result<EventStream>>;
+}
+
+/// Provider of profiles that do not name one: `AXIOM_LLM_PROVIDER`, default `openai`.
+pub fn default_provider() -> String {
+    std::env::var("AXIOM_LLM_PROVIDER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| p.trim().to_lowercase())
+        .unwrap_or_else(|| "openai".to_string())
+}
+
+/// The backend `profile` asks for, configured from the environment.
+pub fn provider_for(profile: &AgentProfile) -> Result<Arc<dyn LlmProvider>> {
+    let model = profile.model.clone();
+    let provider: Arc<dyn LlmProvider> = match profile.provider.as_str() {
+        "openai" => Arc::new(openai::OpenAiProvider::new(
+            env_first(&["OPENAI_API_KEY", "GEMINI_API_KEY"]).unwrap_or_default(),
+            env_first(&["OPENAI_BASE_URL", "GEMINI_BASE_URL"])
+                .unwrap_or_else(|| openai::DEFAULT_BASE_URL.to_string()),
+            model,
+        )?),
+        "gemini" => Arc::new(gemini::GeminiProvider::new(
+            env_first(&["GOOGLE_API_KEY", "GEMINI_API_KEY"])
+                .context("Set GOOGLE_API_KEY to use the gemini provider")?,
+            env_first(&["GOOGLE_BASE_URL"]).unwrap_or_else(|| gemini::DEFAULT_BASE_URL.to_string()),
+            model,
+        )?),
+        "anthropic" => Arc::new(anthropic::AnthropicProvider::new(
+            env_first(&["ANTHROPIC_API_KEY"])
+                .context("Set ANTHROPIC_API_KEY to use the anthropic provider")?,
+            env_first(&["ANTHROPIC_BASE_URL"])
+                .unwrap_or_else(|| anthropic::DEFAULT_BASE_URL.to_string()),
+            model,
+        )?),
+        "ollama" => Arc::new(ollama::OllamaProvider::new(
+            env_first(&["OLLAMA_HOST"]).unwrap_or_else(|| ollama::DEFAULT_HOST.to_string()),
+            model,
+        )?),
+        other => {
+            return Err(anyhow!(
+                "Unknown LLM provider '{}' in profile '{}' (expected one of: {})",
+                other,
+                profile.name,
+                PROVIDERS.join(", ")
+            ))
+        }
+    };
+    Ok(provider)
+}
+
+/// First of `keys` set to a non-empty value.
+fn env_first(keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find(|value| !value.trim().is_empty())
+}
+
+fn http_client() -> Result<Client> {
+    if let Ok(proxy_url) = std::env::var("HTTPS_PROXY").or_else(|_| std::env::var("https_proxy")) {
+        println!("HTTPS_PROXY env var found: {}", proxy_url);
+    }
+    Client::builder()
+        .build()
+        .context("Failed to build reqwest client")
+}
+
+/// The `function` objects of OpenAI tool schemas.
+fn functions(tools: &[Value]) -> impl Iterator<Item = &Value> {
+    tools
+        .iter()
+        .map(|tool| tool.get("function").unwrap_or(tool))
+}
+
+/// Id for a tool call the API sent without one. Unique for the whole session, so the calls of
+/// earlier turns keep their own names in [`tool_names`].
+pub fn fresh_call_id() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    format!("call_{}", NEXT.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Tool name of every tool call id, for APIs that answer a call by name.
+fn tool_names(messages: &[Message]) -> HashMap<&str, &str> {
+    messages
+        .iter()
+        .flat_map(|m| m.tool_calls.iter().flatten())
+        .map(|tc| (tc.id.as_str(), tc.function.name.as_str()))
+        .collect()
+}
+
+/// Arguments of a tool call as a JSON object, for APIs that take them parsed.
+fn parse_arguments(arguments: &str) -> Value {
+    serde_json::from_str(arguments)
+        .ok()
+        .filter(Value::is_object)
+        .unwrap_or_else(|| Value::Object(Default::default()))
+}
+
+/// Sends a streaming request, retrying rate limits, server errors and network failures with
+/// exponential backoff.
+async fn post_stream(url: &str, request: impl Fn() -> RequestBuilder) -> Result<Response> {
+    println!("Sending STREAM request to: {}", url);
+
+    let mut retry_count = 0;
+    const MAX_RETRIES: u32 = 3;
+    let mut base_delay = 2; // seconds
+
+    loop {
+        match request().send().await {
+            Ok(resp) => {
+                let status = resp.status();
+                if status.is_success() {
+                    println!("Successfully sent stream request");
+                    return Ok(resp);
+                }
+                let transient =
+                    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                if transient && retry_count < MAX_RETRIES {
+                    println!(
+                        "API error ({}). Retrying in {} seconds...",
+                        status, base_delay
+                    );
+                    sleep(Duration::from_secs(base_delay)).await;
+                    retry_count += 1;
+                    base_delay *= 2; // Exponential backoff
+                    continue;
+                }
+
+                let error_text = resp
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| "Unknown error".to_string());
+                if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    return Err(anyhow!("API error (Rate Limit Exceeded): {}", error_text));
+                }
+                return Err(anyhow!("API error ({}): {}", status, error_text));
+            }
+            Err(e) => {
+                println!("Failed to send stream request: {}", e);
+                if retry_count >= MAX_RETRIES {
+                    return Err(anyhow!("Network error: {}", e));
+                }
+                sleep(Duration::from_secs(base_delay)).await;
+                retry_count += 1;
+                base_delay *= 2;
+            }
+        }
+    }
+}
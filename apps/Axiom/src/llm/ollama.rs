@@ -0,0 +1,196 @@
+//! A local Ollama server's `/api/chat`, which streams one JSON object per line.
+use super::sse::{Decoder, SseStream};
+use super::{
+    fresh_call_id, EventStream, LlmProvider, Message, Part, StreamDeltaToolCall, StreamEvent, Usage,
+};
+use anyhow::{anyhow, Result};
+use futures_util::future::BoxFuture;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+pub const DEFAULT_HOST: &str = "http://127.0.0.1:11434";
+
+#[derive(Clone)]
+pub struct OllamaProvider {
+    host: String,
+    model: String,
+    client: Client,
+}
+
+impl OllamaProvider {
+    /// `host` as in `OLLAMA_HOST`, with or without the scheme.
+    pub fn new(host: String, model: String) -> Result<Self> {
+        let host = host.trim_end_matches('/');
+        let host = if host.contains("://") {
+            host.to_string()
+        } else {
+            format!("http://{}", host)
+        };
+        Ok(Self {
+            host,
+            model,
+            client: super::http_client()?,
+        })
+    }
+
+    async fn stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<Value>>,
+    ) -> Result<EventStream> {
+        let url = format!("{}/api/chat", self.host);
+        let body = request_body(&self.model, &messages, tools.as_deref());
+        let resp = super::post_stream(&url, || self.client.post(&url).json(&body)).await?;
+        Ok(Box::pin(SseStream::new(
+            resp.bytes_stream(),
+            OllamaDecoder::default(),
+        )))
+    }
+}
+
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn chat_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<Value>>,
+    ) -> BoxFuture<'_, Result<EventStream>> {
+        Box::pin(self.stream(messages, tools))
+    }
+}
+
+/// `/api/chat` body. Roles and tools keep the OpenAI shape; images move to `images` and tool
+/// call arguments are objects.
+pub fn request_body(model: &str, messages: &[Message], tools: Option<&[Value]>) -> Value {
+    let names = super::tool_names(messages);
+    let messages: Vec<Value> = messages
+        .iter()
+        .map(|message| {
+            let mut text = Vec::new();
+            let mut images = Vec::new();
+            for part in message.content.iter().flat_map(|c| c.parts()) {
+                match part {
+                    Part::Text(t) => text.push(t),
+                    Part::Image { data, .. } => images.push(data),
+                }
+            }
+            let mut out = json!({ "role": message.role, "content": text.join("\n") });
+            if !images.is_empty() {
+                out["images"] = json!(images);
+            }
+            if let Some(calls) = &message.tool_calls {
+                out["tool_calls"] = calls
+                    .iter()
+                    .map(|call| {
+                        json!({ "function": {
+                            "name": call.function.name,
+                            "arguments": super::parse_arguments(&call.function.arguments)
+                        }})
+                    })
+                    .collect();
+            }
+            if let Some(name) = message.tool_call_id.as_deref().and_then(|id| names.get(id)) {
+                out["tool_name"] = json!(name);
+            }
+            out
+        })
+        .collect();
+
+    let mut body = json!({ "model": model, "messages": messages, "stream": true });
+    if let Some(tools) = tools.filter(|t| !t.is_empty()) {
+        body["tools"] = json!(tools);
+    }
+    body
+}
+
+/// One JSON object per line, the last with `"done": true`. Tool calls come whole, usually
+/// without an id.
+#[derive(Default)]
+pub struct OllamaDecoder {
+    next_call: i32,
+}
+
+impl Decoder for OllamaDecoder {
+    fn line(&mut self, line: &str) -> Result<Vec<StreamEvent>> {
+        let chunk: Value = serde_json::from_str(line)?;
+        if let Some(error) = chunk["error"].as_str() {
+            return Err(anyhow!("Ollama error: {}", error));
+        }
+
+        let mut events = Vec::new();
+        let message = &chunk["message"];
+        if let Some(text) = message["content"].as_str().filter(|t| !t.is_empty()) {
+            events.push(StreamEvent::TextChunk(text.to_string()));
+        }
+        for call in message["tool_calls"].as_array().into_iter().flatten() {
+            let function = &call["function"];
+            let id = call["id"]
+                .as_str()
+                .map_or_else(fresh_call_id, str::to_string);
+            events.push(StreamEvent::ToolCallChunk(StreamDeltaToolCall::complete(
+                self.next_call,
+                Some(id),
+                function["name"].as_str().unwrap_or(""),
+                function.get("arguments").unwrap_or(&json!({})).to_string(),
+            )));
+            self.next_call += 1;
+        }
+        if chunk["done"] == true {
+            if let Some(reason) = chunk["done_reason"].as_str() {
+                events.push(StreamEvent::Finish(reason.to_string()));
+            }
+            events.push(StreamEvent::Usage(Usage {
+                input_tokens: chunk["prompt_eval_count"].as_u64().unwrap_or(0),
+                output_tokens: chunk["eval_count"].as_u64().unwrap_or(0),
+            }));
+            events.push(StreamEvent::Done);
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::sse::fixtures::{outcome, replay, text, tool_calls};
+
+    #[test]
+    fn decodes_recorded_stream_with_tool_call() {
+        let events = replay(
+            include_str!("fixtures/ollama.ndjson"),
+            OllamaDecoder::default(),
+        );
+        assert_eq!(text(&events), "Let me look at the roads.");
+        let calls = tool_calls(&events);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].2, "road_nav");
+        assert_eq!(
+            serde_json::from_str::<Value>(&calls[0].3).unwrap(),
+            json!({ "from": [0, 0], "to": [4, 4] })
+        );
+        assert_eq!(
+            outcome(&events),
+            (
+                Some("stop".to_string()),
+                Some(Usage {
+                    input_tokens: 1650,
+                    output_tokens: 38
+                })
+            )
+        );
+        assert!(matches!(events.last(), Some(StreamEvent::Done)));
+    }
+
+    #[test]
+    fn host_without_scheme_is_accepted() {
+        let provider = OllamaProvider::new("0.0.0.0:11434/".to_string(), "qwen3".to_string());
+        assert_eq!(provider.unwrap().host, "http://0.0.0.0:11434");
+    }
+}
@@ -0,0 +1,341 @@
+//! OpenAI-compatible `chat/completions`, e.g. a local proxy in front of Gemini or OpenAI itself.
+use super::sse::{self, Decoder, SseStream};
+use super::{
+    EventStream, LlmProvider, Message, StreamDeltaFunction, StreamDeltaToolCall, StreamEvent, Usage,
+};
+use anyhow::{Context as _, Result};
+use futures_util::future::BoxFuture;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Local rotation proxy the editor was first used with.
+pub const DEFAULT_BASE_URL: &str = "http://127.0.0.1:8045/v1";
+
+#[derive(Clone)]
+pub struct OpenAiProvider {
+    api_key: String,
+    base_url: String,
+    model: String,
+    client: Client,
+}
+
+#[derive(Serialize)]
+struct ChatCompletionRequest {
+    model: String,
+    messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+    /// Asks for a last chunk with the token usage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct ChatCompletionResponse {
+    pub choices: Vec<Choice>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct Choice {
+    pub message: Message,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StreamChunk {
+    #[allow(dead_code)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub choices: Vec<StreamChoice>,
+    pub usage: Option<StreamUsage>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StreamChoice {
+    #[serde(default)]
+    pub delta: StreamDelta,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct StreamDelta {
+    pub content: Option<String>,
+    pub tool_calls: Option<Vec<WireToolCall>>,
+}
+
+/// A tool call fragment as sent. Some OpenAI-compatible servers leave out `index` or reuse
+/// one index for several calls, so [`OpenAiDecoder`] assigns the index it passes on.
+#[derive(Deserialize, Debug)]
+pub struct WireToolCall {
+    pub index: Option<i32>,
+    pub id: Option<String>,
+    pub r#type: Option<String>,
+    pub function: Option<StreamDeltaFunction>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StreamUsage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+}
+
+impl OpenAiProvider {
+    pub fn new(api_key: String, base_url: String, model: String) -> Result<Self> {
+        Ok(Self {
+            api_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model,
+            client: super::http_client()?,
+        })
+    }
+
+    fn url(&self) -> String {
+        format!("{}/chat/completions", self.base_url)
+    }
+
+    pub async fn chat_completion_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<Value>>,
+    ) -> Result<EventStream> {
+        let url = self.url();
+        let request_body = ChatCompletionRequest {
+            model: self.model.clone(),
+            messages,
+            tools,
+            stream: Some(true),
+            stream_options: Some(json!({ "include_usage": true })),
+        };
+        let resp = super::post_stream(&url, || {
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .json(&request_body)
+        })
+        .await?;
+        Ok(Box::pin(SseStream::new(
+            resp.bytes_stream(),
+            OpenAiDecoder::default(),
+        )))
+    }
+}
+
+#[allow(dead_code)]
+impl OpenAiProvider {
+    pub async fn chat_completion(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<Value>>,
+    ) -> Result<ChatCompletionResponse> {
+        let url = self.url();
+        println!("Sending OpenAI-compatible request to: {}", url);
+
+        let request_body = ChatCompletionRequest {
+            model: self.model.clone(),
+            messages,
+            tools,
+            stream: None,
+            stream_options: None,
+        };
+
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&request_body)
+            .send()
+            .await
+            .context("Failed to send request to API")?;
+
+        if !response.status().is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(anyhow::anyhow!("API error: {}", error_text));
+        }
+
+        response
+            .json()
+            .await
+            .context("Failed to parse API response")
+    }
+}
+
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn chat_stream(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<Value>>,
+    ) -> BoxFuture<'_, Result<EventStream>> {
+        Box::pin(self.chat_completion_stream(messages, tools))
+    }
+}
+
+/// `data: {chunk}` lines ending with `data: [DONE]`. One chunk can carry text, fragments of
+/// several tool calls and the finish reason at once; every one of them becomes an event.
+#[derive(Default)]
+pub struct OpenAiDecoder {
+    /// Index given to each call id.
+    ids: HashMap<String, i32>,
+    /// Index the server used for a call mapped to the index passed on.
+    indices: HashMap<i32, i32>,
+    /// Call that fragments without id or index continue.
+    last: Option<i32>,
+}
+
+impl OpenAiDecoder {
+    fn index(&mut self, call: &WireToolCall) -> i32 {
+        let index = match (&call.id, call.index) {
+            (Some(id), wire) => match self.ids.get(id) {
+                Some(&index) => index,
+                None => {
+                    // A new id on an index that is taken starts another call.
+                    let taken = |i: &i32| self.ids.values().any(|v| v == i);
+                    let index = wire
+                        .filter(|w| !taken(w))
+                        .unwrap_or_else(|| self.ids.values().max().map_or(0, |m| m + 1));
+                    self.ids.insert(id.clone(), index);
+                    if let Some(wire) = wire {
+                        self.indices.insert(wire, index);
+                    }
+                    index
+                }
+            },
+            (None, Some(wire)) => self.indices.get(&wire).copied().unwrap_or(wire),
+            (None, None) => self.last.unwrap_or(0),
+        };
+        self.last = Some(index);
+        index
+    }
+}
+
+impl Decoder for OpenAiDecoder {
+    fn line(&mut self, line: &str) -> Result<Vec<StreamEvent>> {
+        let Some(data) = sse::data(line) else {
+            return Ok(Vec::new());
+        };
+        if data == "[DONE]" {
+            return Ok(vec![StreamEvent::Done]);
+        }
+
+        // Lines that are not chunks (keep-alives, vendor extras) are skipped.
+        let Ok(chunk) = serde_json::from_str::<StreamChunk>(data) else {
+            return Ok(Vec::new());
+        };
+        let mut events = Vec::new();
+        for choice in chunk.choices {
+            if let Some(content) = choice.delta.content.filter(|c| !c.is_empty()) {
+                events.push(StreamEvent::TextChunk(content));
+            }
+            for call in choice.delta.tool_calls.into_iter().flatten() {
+                let index = self.index(&call);
+                events.push(StreamEvent::ToolCallChunk(StreamDeltaToolCall {
+                    index,
+                    id: call.id,
+                    r#type: call.r#type,
+                    function: call.function,
+                }));
+            }
+            if let Some(reason) = choice.finish_reason {
+                events.push(StreamEvent::Finish(reason));
+            }
+        }
+        if let Some(usage) = chunk.usage {
+            events.push(StreamEvent::Usage(Usage {
+                input_tokens: usage.prompt_tokens,
+                output_tokens: usage.completion_tokens,
+            }));
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::sse::fixtures::{outcome, replay, text, tool_calls};
+
+    #[test]
+    fn decodes_recorded_text_and_tool_call_stream() {
+        let events = replay(
+            include_str!("fixtures/openai.sse"),
+            OpenAiDecoder::default(),
+        );
+        assert_eq!(text(&events), "Placing a lamp at the crossing. ");
+        assert_eq!(
+            tool_calls(&events),
+            [(
+                0,
+                Some("call_abc123".to_string()),
+                "bevy_spawn_primitive".to_string(),
+                r#"{"shape":"sphere","position":[2,0,2]}"#.to_string()
+            )]
+        );
+        assert!(matches!(events.last(), Some(StreamEvent::Done)));
+    }
+
+    #[test]
+    fn keeps_every_call_and_the_text_of_a_chunk() {
+        let events = replay(
+            include_str!("fixtures/openai_parallel.sse"),
+            OpenAiDecoder::default(),
+        );
+        assert_eq!(text(&events), "Spawning both lamps.");
+        let calls = tool_calls(&events);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].1.as_deref(), Some("call_lampA"));
+        assert_eq!(calls[0].3, r#"{"shape":"sphere","position":[2,0,2]}"#);
+        assert_eq!(calls[1].1.as_deref(), Some("call_lampB"));
+        assert_eq!(calls[1].3, r#"{"shape":"cube","position":[-2,0,2]}"#);
+        assert_eq!(
+            outcome(&events),
+            (
+                Some("tool_calls".to_string()),
+                Some(Usage {
+                    input_tokens: 2210,
+                    output_tokens: 64
+                })
+            )
+        );
+    }
+
+    #[test]
+    fn numbers_calls_sent_without_or_with_a_reused_index() {
+        let events = replay(
+            include_str!("fixtures/openai_unindexed.sse"),
+            OpenAiDecoder::default(),
+        );
+        let calls = tool_calls(&events);
+        let summary: Vec<(i32, &str, &str)> = calls
+            .iter()
+            .map(|c| (c.0, c.2.as_str(), c.3.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (0, "query_scene", r#"{"filter":"lamp"}"#),
+                (1, "scene_minimap", "{}"),
+                (2, "road_nav", r#"{"from":[0,0],"to":[4,4]}"#),
+            ]
+        );
+        let (finish, usage) = outcome(&events);
+        assert_eq!(finish.as_deref(), Some("tool_calls"));
+        assert_eq!(usage.unwrap().output_tokens, 51);
+    }
+}
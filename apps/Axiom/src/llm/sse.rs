@@ -0,0 +1,171 @@
+//! Splits a streamed response body into lines and hands them to the provider's decoder.
+//! Server-sent events and Ollama's JSON lines are both line based, so one stream serves all.
+use super::StreamEvent;
+use anyhow::Result;
+use futures_util::Stream;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Turns the lines of one provider's response into stream events.
+pub trait Decoder {
+    /// Events for one line, without its line break. Blank lines separate SSE events and can be
+    /// ignored.
+    fn line(&mut self, line: &str) -> Result<Vec<StreamEvent>>;
+}
+
+/// The `data:` payload of an SSE line. `event:`, `id:` and comment lines give `None`.
+pub fn data(line: &str) -> Option<&str> {
+    line.strip_prefix("data:").map(str::trim_start)
+}
+
+pub struct SseStream<S, D> {
+    inner: S,
+    decoder: D,
+    buffer: Vec<u8>,
+    pending: VecDeque<Result<StreamEvent>>,
+    finished: bool,
+}
+
+impl<S, D> SseStream<S, D> {
+    pub fn new(inner: S, decoder: D) -> Self {
+        Self {
+            inner,
+            decoder,
+            buffer: Vec::new(),
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+}
+
+impl<S, D> SseStream<S, D>
+where
+    D: Decoder,
+{
+    fn decode(&mut self, line_bytes: &[u8]) {
+        let line = String::from_utf8_lossy(line_bytes);
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        match self.decoder.line(line) {
+            Ok(events) => self.pending.extend(events.into_iter().map(Ok)),
+            Err(e) => self.pending.push_back(Err(e)),
+        }
+    }
+}
+
+impl<S, B, D> Stream for SseStream<S, D>
+where
+    S: Stream<Item = reqwest::Result<B>> + Unpin,
+    B: AsRef<[u8]>,
+    D: Decoder + Unpin,
+{
+    type Item = Result<StreamEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Poll::Ready(Some(event));
+            }
+            if self.finished {
+                return Poll::Ready(None);
+            }
+
+            // Check buffer for newline
+            if let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+                let line_bytes = self.buffer.drain(..pos + 1).collect::<Vec<u8>>();
+                self.decode(&line_bytes);
+                continue;
+            }
+
+            // No newline found, pull more data
+            match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    self.buffer.extend_from_slice(chunk.as_ref());
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    return Poll::Ready(Some(Err(anyhow::Error::from(e))));
+                }
+                Poll::Ready(None) => {
+                    // The last line may come without a line break.
+                    let rest = std::mem::take(&mut self.buffer);
+                    self.decode(&rest);
+                    self.finished = true;
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod fixtures {
+    use super::*;
+    use crate::llm::Usage;
+    use futures_util::StreamExt;
+
+    /// Feeds a recorded response through `decoder` in small chunks, so lines and UTF-8
+    /// characters are split across reads like on the wire.
+    pub fn replay<D: Decoder + Unpin>(body: &str, decoder: D) -> Vec<StreamEvent> {
+        let chunks: Vec<reqwest::Result<Vec<u8>>> =
+            body.as_bytes().chunks(7).map(|c| Ok(c.to_vec())).collect();
+        let stream = SseStream::new(futures_util::stream::iter(chunks), decoder);
+        let events: Vec<Result<StreamEvent>> = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(stream.collect());
+        events.into_iter().map(|e| e.unwrap()).collect()
+    }
+
+    /// Streamed text, joined.
+    pub fn text(events: &[StreamEvent]) -> String {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::TextChunk(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The finish reason and usage the stream reported.
+    pub fn outcome(events: &[StreamEvent]) -> (Option<String>, Option<Usage>) {
+        let finish = events.iter().find_map(|e| match e {
+            StreamEvent::Finish(reason) => Some(reason.clone()),
+            _ => None,
+        });
+        let usage = events.iter().find_map(|e| match e {
+            StreamEvent::Usage(usage) => Some(*usage),
+            _ => None,
+        });
+        (finish, usage)
+    }
+
+    /// Tool calls put together from their fragments: `(index, id, name, arguments)`.
+    pub fn tool_calls(events: &[StreamEvent]) -> Vec<(i32, Option<String>, String, String)> {
+        let mut calls: Vec<(i32, Option<String>, String, String)> = Vec::new();
+        for event in events {
+            let StreamEvent::ToolCallChunk(tc) = event else {
+                continue;
+            };
+            let pos = match calls.iter().position(|c| c.0 == tc.index) {
+                Some(pos) => pos,
+                None => {
+                    calls.push((tc.index, None, String::new(), String::new()));
+                    calls.len() - 1
+                }
+            };
+            let call = &mut calls[pos];
+            if tc.id.is_some() {
+                call.1 = tc.id.clone();
+            }
+            if let Some(f) = &tc.function {
+                call.2.push_str(f.name.as_deref().unwrap_or(""));
+                call.3.push_str(f.arguments.as_deref().unwrap_or(""));
+            }
+        }
+        calls
+    }
+}
@@ -2,6 +2,7 @@
 
 use eframe::egui;
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
 use tokio::runtime::Runtime;
 use base64::prelude::*;
 use std::io::Cursor;
@@ -12,22 +13,34 @@ mod llm;
 mod prompts;
 mod tools;
 mod agent;
+mod arrange;
+mod brp;
+mod catalog;
+mod connection;
+mod game;
+mod glb;
+mod level_export;
+mod minimap;
+mod query;
+mod scene;
+mod scene_map;
+mod schema;
+mod targets;
+mod texture;
 mod types;
 mod ui;
 // mod simulation; // Removed
 
-use crate::llm::{GeminiClient, Message, MessageContent, ContentPart, ImageUrl, StreamEvent, ToolCall, FunctionCall};
+use crate::llm::{LlmProvider, Message, MessageContent, ContentPart, ImageUrl, StreamEvent, ToolCall, FunctionCall};
 use crate::tools::Tool; // Import Tool trait only
 use crate::agent::{AgentProfile, get_default_agents};
-use crate::types::{AsyncMessage, ChannelState};
+use crate::types::{AsyncMessage, ChannelState, TurnStatus};
 use futures_util::StreamExt;
 
 // Import UI modules
-use crate::ui::{top_panel, sidebar, input, chat, file_tree};
+use crate::ui::{top_panel, sidebar, input, chat, file_tree, game_logs, minimap as minimap_panel};
 
 struct AxiomApp {
-    api_key: String,
-    
     // Current Active Configuration
     current_profile: AgentProfile,
     available_profiles: Vec<AgentProfile>,
@@ -46,12 +59,16 @@ struct AxiomApp {
     input_text: String,
     pending_image: Option<String>, 
     preview_texture: Option<egui::TextureHandle>, 
+    minimap_texture: Option<egui::TextureHandle>,
+    show_minimap: bool,
+    show_game_logs: bool,
     clipboard: Option<arboard::Clipboard>,
     
     // App State
     is_loading: bool,
+    turn_status: TurnStatus,
     waiting_for_screenshot: bool,
-    client: Option<GeminiClient>,
+    client: Option<Arc<dyn LlmProvider>>,
     // sim_started: bool, // Removed
     // multi_agent_mode: bool, // Removed
     
@@ -91,17 +108,19 @@ impl AxiomApp {
         // Initialize dotenv
         dotenv::dotenv().ok();
         
-        // Remove hardcoded key fallback to prevent leakage
-        let api_key = std::env::var("GEMINI_API_KEY").unwrap_or_default();
-
         let clipboard = arboard::Clipboard::new().ok();
 
+        let _ = targets::select("");
+        connection::spawn_monitor(&rt, cc.egui_ctx.clone(), tx.clone());
+        game::spawn_watcher(tx.clone());
+
         let mut channels = std::collections::HashMap::new();
         channels.insert("global".to_string(), ChannelState {
             id: "global".to_string(),
             name: "🌐 Global".to_string(),
             history: Vec::new(),
             assigned_agents: vec!["General Assistant".to_string()],
+            target: String::new(),
         });
         /*
         channels.insert("backend".to_string(), ChannelState {
@@ -131,8 +150,7 @@ impl AxiomApp {
         */
 
         Self {
-            api_key,
-            current_profile: AgentProfile::default(),
+            current_profile: agent::with_env_overrides(AgentProfile::default()),
             available_profiles: get_default_agents(),
             channels,
             active_channel_id: "global".to_string(),
@@ -141,8 +159,12 @@ impl AxiomApp {
             input_text: String::new(),
             pending_image: None,
             preview_texture: None,
+            minimap_texture: None,
+            show_minimap: false,
+            show_game_logs: false,
             clipboard,
             is_loading: false,
+            turn_status: TurnStatus::default(),
             waiting_for_screenshot: false,
             client: None,
             // sim_started: false,
@@ -168,6 +190,7 @@ impl AxiomApp {
                         if let Ok(_) = img_buffer.write_to(&mut Cursor::new(&mut png_bytes), image::ImageFormat::Png) {
                             let base64_string = BASE64_STANDARD.encode(&png_bytes);
                             self.pending_image = Some(base64_string);
+                            tools::sketch::remember_pasted_image(png_bytes);
 
                             let color_image = egui::ColorImage::from_rgba_unmultiplied(
                                 [width, height],
@@ -189,6 +212,24 @@ impl AxiomApp {
         false
     }
 
+    fn load_minimap(&mut self, ctx: &egui::Context, png: &[u8]) {
+        if let Ok(img) = image::load_from_memory(png) {
+            let img = img.to_rgba8();
+            let color_image = egui::ColorImage::from_rgba_unmultiplied(
+                [img.width() as usize, img.height() as usize],
+                img.as_raw(),
+            );
+            self.minimap_texture = Some(ctx.load_texture("minimap", color_image, egui::TextureOptions::NEAREST));
+        }
+    }
+
+    fn refresh_minimap(&mut self, ctx: &egui::Context) {
+        match minimap::render_scene_png() {
+            Ok(png) => self.load_minimap(ctx, &png),
+            Err(_) => self.minimap_texture = None,
+        }
+    }
+
     fn send_message(&mut self, force: bool) {
         let text = self.input_text.trim().to_string();
         println!("[DEBUG] send_message called. force={}, text_len={}, pending_image={}", force, text.len(), self.pending_image.is_some());
@@ -229,11 +270,15 @@ impl AxiomApp {
         self.pending_image = None;
         self.preview_texture = None;
         self.is_loading = true;
+        self.turn_status = TurnStatus::default();
 
         // Initialize client if not ready
         if self.client.is_none() {
-             match GeminiClient::new(self.api_key.clone(), self.current_profile.model.clone()) {
-                Ok(c) => self.client = Some(c),
+             match crate::llm::provider_for(&self.current_profile) {
+                Ok(c) => {
+                    println!("LLM provider: {} ({})", c.name(), c.model());
+                    self.client = Some(c);
+                }
                 Err(e) => {
                     if let Some(channel) = self.channels.get_mut(&self.active_channel_id) {
                         channel.history.push(("System".to_string(), MessageContent::Text(format!("Failed to init client: {}", e))));
@@ -244,7 +289,12 @@ impl AxiomApp {
              }
         }
 
-        let client = self.client.as_ref().unwrap().clone();
+        let client = Arc::clone(self.client.as_ref().unwrap());
+
+        // Aim the Bevy tools at this channel's game before the prompt describes it.
+        if let Some(channel) = self.channels.get(&self.active_channel_id) {
+            let _ = targets::select(&channel.target);
+        }
         let tx = self.tx.clone();
         
         let mut system_prompt = if self.active_channel_id == "planning" {
@@ -273,6 +323,25 @@ impl AxiomApp {
         // Inject Road Engineering Rules
         system_prompt.push_str("\n\n");
         system_prompt.push_str(include_str!("prompts/road_engineer.md"));
+
+        let profile_name = self.current_profile.name.clone();
+        let channel_id = self.active_channel_id.clone();
+        // Captured now, so switching channels mid-turn does not redirect this turn's game calls.
+        let target = targets::selected();
+        let tools = tools::get_tools_for_profile(&profile_name, &channel_id, &target, tx.clone());
+
+        // --- Scene Map Injection (Bevy context) ---
+        if self.current_profile.context_mode.starts_with("Bevy") {
+            if let Some(map) = scene_map::summarize(&scene::snapshot(), scene_map::token_budget()) {
+                system_prompt = format!("{}\n\n{}", map, system_prompt);
+            }
+        }
+        // An agent that can act on the game has to know when it is offline.
+        if tools.iter().any(|t| t.uses_game()) {
+            if let Some(note) = connection::prompt_note() {
+                system_prompt = format!("{}\n\n{}", note, system_prompt);
+            }
+        }
         
         let mut messages: Vec<Message> = Vec::new();
         
@@ -311,9 +380,10 @@ impl AxiomApp {
             });
         }
 
-        let profile_name = self.current_profile.name.clone();
-        let tools_schema: Vec<Value> = tools::get_tools_for_profile(&profile_name, tx.clone())
+        let tools_schema: Vec<Value> = tools
             .iter()
+            // Tools the connected game's plugin cannot serve are not offered at all.
+            .filter(|t| connection::offers(t.requires()))
             .map(|t| t.schema())
             .collect();
 
@@ -329,7 +399,7 @@ impl AxiomApp {
                 }
                 turn_count += 1;
 
-                match client.chat_completion_stream(messages.clone(), Some(tools_schema.clone())).await {
+                match client.chat_stream(messages.clone(), Some(tools_schema.clone())).await {
                     Ok(mut stream) => {
                         let mut full_text = String::new();
                         // let is_planning_channel = messages.iter().any(|m| m.role == "system" && m.content.as_ref().map_or(false, |c| match c { MessageContent::Text(t) => t.contains("Conductor Agent"), _ => false }));
@@ -372,6 +442,15 @@ impl AxiomApp {
                                         if let Some(a) = f.arguments { entry.args.push_str(&a); }
                                     }
                                 }
+                                Ok(StreamEvent::Finish(reason)) => {
+                                    if crate::llm::is_truncated(&reason) {
+                                        let _ = tx.send(AsyncMessage::Log("⚠️ The answer hit the model's output token limit and was cut off.".to_string()));
+                                    }
+                                    let _ = tx.send(AsyncMessage::Finish(reason));
+                                }
+                                Ok(StreamEvent::Usage(usage)) => {
+                                    let _ = tx.send(AsyncMessage::Usage(usage));
+                                }
                                 Ok(StreamEvent::Done) => {}
                                 Err(e) => {
                                     let _ = tx.send(AsyncMessage::Error(e.to_string()));
@@ -388,7 +467,7 @@ impl AxiomApp {
                                 if let Some(builder) = tool_buffer.get(&idx) {
                                     if let Some(name) = &builder.name {
                                         tool_calls.push(ToolCall {
-                                            id: builder.id.clone().unwrap_or_else(|| format!("call_{}", idx)),
+                                            id: builder.id.clone().unwrap_or_else(crate::llm::fresh_call_id),
                                             r#type: builder.r#type.clone(),
                                             function: FunctionCall {
                                                 name: name.clone(),
@@ -406,7 +485,7 @@ impl AxiomApp {
                                 tool_call_id: None,
                             });
 
-                            let all_tools = crate::tools::get_tools_for_profile(&profile_name, tx.clone());
+                            let all_tools = crate::tools::get_tools_for_profile(&profile_name, &channel_id, &target, tx.clone());
                             for tool_call in tool_calls {
                                 let _ = tx.send(AsyncMessage::Log(format!("Executing tool: {} args: {}", tool_call.function.name, tool_call.function.arguments)));
                                 
@@ -439,6 +518,30 @@ impl AxiomApp {
                                     tool_call_id: Some(tool_call.id),
                                 });
                             }
+
+                            // Tool results are text only, so a rendered minimap goes in as a user image part.
+                            if let Some(png) = crate::minimap::take_pending() {
+                                messages.push(Message {
+                                    role: "user".to_string(),
+                                    content: Some(MessageContent::Parts(vec![
+                                        ContentPart {
+                                            r#type: "text".to_string(),
+                                            text: Some("Minimap of the current scene (top-down, north up):".to_string()),
+                                            image_url: None,
+                                        },
+                                        ContentPart {
+                                            r#type: "image_url".to_string(),
+                                            text: None,
+                                            image_url: Some(ImageUrl {
+                                                url: format!("data:image/png;base64,{}", BASE64_STANDARD.encode(&png)),
+                                            }),
+                                        },
+                                    ])),
+                                    tool_calls: None,
+                                    tool_call_id: None,
+                                });
+                                let _ = tx.send(AsyncMessage::Minimap(png));
+                            }
                             continue;
                         }
 
@@ -466,6 +569,13 @@ impl AxiomApp {
     }
 }
 
+impl Drop for AxiomApp {
+    fn drop(&mut self) {
+        // Games launched from the editor go down with it.
+        game::stop_all();
+    }
+}
+
 impl eframe::App for AxiomApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         while let Ok(msg) = self.rx.try_recv() {
@@ -516,6 +626,16 @@ impl eframe::App for AxiomApp {
                     }
                     self.is_loading = false;
                 }
+                AsyncMessage::Minimap(png) => {
+                    self.load_minimap(ctx, &png);
+                    self.show_minimap = true;
+                }
+                AsyncMessage::Finish(reason) => {
+                    self.turn_status.finish = Some(reason);
+                }
+                AsyncMessage::Usage(usage) => {
+                    self.turn_status.usage = Some(usage);
+                }
             }
             ctx.request_repaint();
         }
@@ -626,11 +746,24 @@ impl eframe::App for AxiomApp {
             let action = top_panel::render_top_panel(
                 ui, 
                 &self.active_channel_id, 
+                &connection::all(),
+                &targets::selected(),
+                &self.turn_status,
             );
             
             match action {
                 top_panel::TopPanelAction::SwitchChannel(id) => {
                     self.active_channel_id = id;
+                    if let Some(channel) = self.channels.get(&self.active_channel_id) {
+                        let _ = targets::select(&channel.target);
+                    }
+                }
+                top_panel::TopPanelAction::SelectTarget(target) => {
+                    if targets::select(&target).is_ok() {
+                        if let Some(channel) = self.channels.get_mut(&self.active_channel_id) {
+                            channel.target = target;
+                        }
+                    }
                 }
                 top_panel::TopPanelAction::ClearChat => {
                     if let Some(channel) = self.channels.get_mut(&self.active_channel_id) {
@@ -666,10 +799,60 @@ impl eframe::App for AxiomApp {
                         let _ = clipboard.set_text(log_text);
                     }
                 }
+                top_panel::TopPanelAction::ToggleMinimap => {
+                    self.show_minimap = !self.show_minimap;
+                    if self.show_minimap {
+                        self.refresh_minimap(ctx);
+                    }
+                }
+                top_panel::TopPanelAction::ToggleGameLogs => {
+                    self.show_game_logs = !self.show_game_logs;
+                }
+                top_panel::TopPanelAction::CheckConnection => {
+                    let ctx = ctx.clone();
+                    let tx = self.tx.clone();
+                    self.rt.spawn(async move {
+                        for change in connection::check().await {
+                            let _ = tx.send(AsyncMessage::Log(change));
+                        }
+                        ctx.request_repaint();
+                    });
+                }
                 top_panel::TopPanelAction::None => {}
             }
         });
 
+        if self.show_minimap {
+            let action = minimap_panel::render_minimap_window(ctx, &mut self.show_minimap, &self.minimap_texture);
+            match action {
+                minimap_panel::MinimapAction::Refresh => self.refresh_minimap(ctx),
+                minimap_panel::MinimapAction::None => {}
+            }
+        }
+
+        if self.show_game_logs {
+            let endpoint = targets::selected_endpoint();
+            let status = game::status(&endpoint);
+            let lines = game::logs(&endpoint);
+            let action = game_logs::render_game_logs_window(ctx, &mut self.show_game_logs, &targets::selected(), &status, &lines);
+            match action {
+                game_logs::GameLogsAction::None => {}
+                game_logs::GameLogsAction::Clear => game::clear_logs(&endpoint),
+                // Stopping waits for the game to exit, so it runs off the UI thread.
+                action => {
+                    let tx = self.tx.clone();
+                    self.rt.spawn_blocking(move || {
+                        let message = match action {
+                            game_logs::GameLogsAction::Start => game::start(&endpoint, None).map(|_| "▶ Game launched.".to_string()),
+                            game_logs::GameLogsAction::Restart => game::restart(&endpoint).map(|_| "🔁 Game restarted.".to_string()),
+                            _ => Ok(if game::stop(&endpoint) { "⏹ Game stopped.".to_string() } else { "The game was not running.".to_string() }),
+                        };
+                        let _ = tx.send(AsyncMessage::Log(message.unwrap_or_else(|e| format!("❌ {}", e))));
+                    });
+                }
+            }
+        }
+
         egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
             let action = input::render_input_panel(
                 ui, 
@@ -682,6 +865,10 @@ impl eframe::App for AxiomApp {
 
             match action {
                 input::InputAction::Send => self.send_message(false),
+                input::InputAction::SelectProvider(provider) => {
+                    self.current_profile.provider = provider;
+                    self.client = None;
+                }
                 input::InputAction::StopLoading => {
                     self.is_loading = false;
                     if let Some(channel) = self.channels.get_mut(&self.active_channel_id) {
@@ -729,19 +916,6 @@ impl eframe::App for AxiomApp {
 }
 
 fn main() -> eframe::Result<()> {
-    let base_url = std::env::var("GEMINI_BASE_URL").unwrap_or_else(|_| "http://127.0.0.1:8045".to_string());
-    
-    if !base_url.contains("127.0.0.1") && !base_url.contains("localhost") {
-        if std::env::var("HTTPS_PROXY").is_err() && std::env::var("https_proxy").is_err() {
-            println!("Setting default HTTPS_PROXY to http://127.0.0.1:17890");
-            unsafe {
-                std::env::set_var("HTTPS_PROXY", "http://127.0.0.1:17890");
-            }
-        }
-    } else {
-        println!("Targeting localhost ({}). Skipping default proxy setup.", base_url);
-    }
-
     let options = eframe::NativeOptions {
         viewport: eframe::egui::ViewportBuilder::default()
             .with_inner_size([1200.0, 800.0])
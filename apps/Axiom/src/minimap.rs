@@ -0,0 +1,417 @@
+use crate::scene::PlacedAsset;
+use anyhow::{anyhow, Result};
+use axiom_roads::{
+    asset_stem, rotate_offset, Cell, TileKind, TilePlacement, GRID_SIZE, LEVEL_HEIGHT,
+};
+use image::{Rgba, RgbaImage};
+use std::io::Cursor;
+use std::sync::{Mutex, OnceLock};
+
+const GRASS: Rgba<u8> = Rgba([86, 122, 70, 255]);
+const GRID_LINE: Rgba<u8> = Rgba([70, 100, 58, 255]);
+const AXIS_LINE: Rgba<u8> = Rgba([220, 220, 220, 255]);
+const MARGIN_BG: Rgba<u8> = Rgba([32, 32, 36, 255]);
+const LABEL: Rgba<u8> = Rgba([230, 230, 230, 255]);
+const ROAD: Rgba<u8> = Rgba([72, 72, 78, 255]);
+const BRIDGE: Rgba<u8> = Rgba([96, 104, 150, 255]);
+const SLOPE: Rgba<u8> = Rgba([168, 120, 72, 255]);
+const LANE_MARK: Rgba<u8> = Rgba([235, 210, 90, 255]);
+const PILLAR: Rgba<u8> = Rgba([150, 150, 140, 255]);
+const OUTLINE: Rgba<u8> = Rgba([20, 20, 20, 255]);
+
+/// Largest image side in pixels; cells shrink to fit big layouts.
+const MAX_SIDE: u32 = 1024;
+const LEFT_MARGIN: u32 = 26;
+const TOP_MARGIN: u32 = 16;
+
+// Minimap rendered by `scene_minimap`, waiting to be shown to the model on its next turn.
+static PENDING_MINIMAP: OnceLock<Mutex<Option<Vec<u8>>>> = OnceLock::new();
+
+fn get_pending() -> &'static Mutex<Option<Vec<u8>>> {
+    PENDING_MINIMAP.get_or_init(|| Mutex::new(None))
+}
+
+pub fn set_pending(png: Vec<u8>) {
+    if let Ok(mut pending) = get_pending().lock() {
+        *pending = Some(png);
+    }
+}
+
+pub fn take_pending() -> Option<Vec<u8>> {
+    get_pending()
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.take())
+}
+
+/// How an asset is drawn on the map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Icon {
+    Road,
+    Slope,
+    Pillar,
+    Prop,
+}
+
+fn icon_for(placement: &TilePlacement) -> Icon {
+    match placement.spec() {
+        Some(spec) if spec.kind == TileKind::Slope => Icon::Slope,
+        Some(_) => Icon::Road,
+        None if asset_stem(&placement.asset).starts_with("bridge-pillar") => Icon::Pillar,
+        None => Icon::Prop,
+    }
+}
+
+/// Stable colour per prop type, so the same model always gets the same marker.
+fn prop_color(asset: &str) -> Rgba<u8> {
+    let hash = asset_stem(asset)
+        .bytes()
+        .fold(2166136261u32, |h, b| (h ^ b as u32).wrapping_mul(16777619));
+    let channel = |shift: u32| 90 + ((hash >> shift) & 0x7f) as u8;
+    Rgba([channel(0), channel(8), channel(16), 255])
+}
+
+/// Maps world XZ onto the image: one cell is `cell_px` pixels, north (-Z) is up.
+struct Frame {
+    min: Cell,
+    cols: u32,
+    rows: u32,
+    cell_px: u32,
+}
+
+impl Frame {
+    fn to_px(&self, x: f32, z: f32) -> (f32, f32) {
+        let cell = self.cell_px as f32;
+        (
+            LEFT_MARGIN as f32 + (x / GRID_SIZE - self.min.0 as f32 + 0.5) * cell,
+            TOP_MARGIN as f32 + (z / GRID_SIZE - self.min.1 as f32 + 0.5) * cell,
+        )
+    }
+}
+
+fn put(img: &mut RgbaImage, x: i64, y: i64, color: Rgba<u8>) {
+    if x >= 0 && y >= 0 && (x as u32) < img.width() && (y as u32) < img.height() {
+        img.put_pixel(x as u32, y as u32, color);
+    }
+}
+
+fn fill_rect(img: &mut RgbaImage, x0: f32, y0: f32, x1: f32, y1: f32, color: Rgba<u8>) {
+    for y in y0.round() as i64..y1.round() as i64 {
+        for x in x0.round() as i64..x1.round() as i64 {
+            put(img, x, y, color);
+        }
+    }
+}
+
+fn fill_disc(img: &mut RgbaImage, cx: f32, cy: f32, radius: f32, color: Rgba<u8>) {
+    let r = radius.ceil() as i64;
+    for dy in -r..=r {
+        for dx in -r..=r {
+            let (x, y) = (cx + dx as f32, cy + dy as f32);
+            if (dx * dx + dy * dy) as f32 <= radius * radius {
+                put(img, x.round() as i64, y.round() as i64, color);
+            }
+        }
+    }
+}
+
+/// Thick segment, drawn as a run of discs.
+fn stroke(img: &mut RgbaImage, from: (f32, f32), to: (f32, f32), width: f32, color: Rgba<u8>) {
+    let length = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+    let steps = length.ceil().max(1.0) as i32;
+    for i in 0..=steps {
+        let t = i as f32 / steps as f32;
+        let (x, y) = (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+        fill_disc(img, x, y, width * 0.5, color);
+    }
+}
+
+/// 3x5 bitmap glyphs for grid coordinates, one row per `u8` (low three bits, MSB left).
+fn glyph(c: char) -> Option<[u8; 5]> {
+    Some(match c {
+        '0' => [7, 5, 5, 5, 7],
+        '1' => [2, 6, 2, 2, 7],
+        '2' => [7, 1, 7, 4, 7],
+        '3' => [7, 1, 7, 1, 7],
+        '4' => [5, 5, 7, 1, 1],
+        '5' => [7, 4, 7, 1, 7],
+        '6' => [7, 4, 7, 5, 7],
+        '7' => [7, 1, 1, 1, 1],
+        '8' => [7, 5, 7, 5, 7],
+        '9' => [7, 5, 7, 1, 7],
+        '-' => [0, 0, 7, 0, 0],
+        _ => return None,
+    })
+}
+
+/// Draws `text` with its top-left corner at `(x, y)`, `scale` pixels per font pixel.
+fn draw_text(img: &mut RgbaImage, text: &str, x: i64, y: i64, scale: i64, color: Rgba<u8>) {
+    for (i, c) in text.chars().enumerate() {
+        let Some(rows) = glyph(c) else { continue };
+        let gx = x + i as i64 * 4 * scale;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (4 >> col) == 0 {
+                    continue;
+                }
+                for sy in 0..scale {
+                    for sx in 0..scale {
+                        put(
+                            img,
+                            gx + col * scale + sx,
+                            y + row as i64 * scale + sy,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn text_width(text: &str, scale: i64) -> i64 {
+    text.chars().count() as i64 * 4 * scale - scale
+}
+
+/// Renders the scene top-down: roads as asphalt strips along their openings (bridges and
+/// slopes tinted), pillars as grey squares and other models as coloured dots, over a cell grid
+/// labelled with world cell coordinates. Returns `None` for an empty scene.
+pub fn render(assets: &[PlacedAsset]) -> Option<RgbaImage> {
+    let mut placements: Vec<(Icon, TilePlacement)> = assets
+        .iter()
+        .map(|a| {
+            let placement = a.tile_placement();
+            (icon_for(&placement), placement)
+        })
+        .collect();
+    if placements.is_empty() {
+        return None;
+    }
+
+    let cells: Vec<Cell> = placements.iter().flat_map(|(_, p)| p.cells()).collect();
+    let min_x = cells.iter().map(|c| c.0).min()? - 1;
+    let max_x = cells.iter().map(|c| c.0).max()? + 1;
+    let min_z = cells.iter().map(|c| c.1).min()? - 1;
+    let max_z = cells.iter().map(|c| c.1).max()? + 1;
+    let cols = (max_x - min_x + 1) as u32;
+    let rows = (max_z - min_z + 1) as u32;
+    let cell_px = (MAX_SIDE / cols.max(rows)).clamp(6, 40);
+    let frame = Frame {
+        min: (min_x, min_z),
+        cols,
+        rows,
+        cell_px,
+    };
+
+    let mut img = RgbaImage::from_pixel(
+        LEFT_MARGIN + cols * cell_px,
+        TOP_MARGIN + rows * cell_px,
+        MARGIN_BG,
+    );
+    draw_grid(&mut img, &frame);
+
+    // Lower levels first so overpasses are drawn on top of the roads they cross.
+    placements.sort_by(|a, b| a.1.translation[1].total_cmp(&b.1.translation[1]));
+    let cell = cell_px as f32;
+    for (icon, placement) in &placements {
+        let [x, y, z] = placement.translation;
+        let centre = frame.to_px(x, z);
+        match icon {
+            Icon::Road | Icon::Slope => {
+                let color = match icon {
+                    Icon::Slope => SLOPE,
+                    _ if y >= LEVEL_HEIGHT * 0.5 => BRIDGE,
+                    _ => ROAD,
+                };
+                let width = cell * 0.55;
+                let ports = placement.world_ports().unwrap_or_default();
+                for port in &ports {
+                    let end = frame.to_px(port.offset[0], port.offset[2]);
+                    stroke(&mut img, centre, end, width, color);
+                }
+                if ports.len() != 2 {
+                    // Ends, junctions and roundabouts get a hub so the icon reads at a glance.
+                    fill_disc(&mut img, centre.0, centre.1, width * 0.6, color);
+                }
+                if cell_px >= 16 {
+                    for port in &ports {
+                        let end = frame.to_px(port.offset[0], port.offset[2]);
+                        stroke(&mut img, centre, end, 1.0, LANE_MARK);
+                    }
+                }
+                if *icon == Icon::Slope {
+                    // Small dot on the uphill side, kept inside the tile so neighbours don't hide it.
+                    let q = placement.quarter_turns();
+                    let uphill = rotate_offset([GRID_SIZE * 0.15, 0.0, 0.0], q);
+                    let tip = frame.to_px(x + uphill[0], z + uphill[2]);
+                    fill_disc(&mut img, tip.0, tip.1, cell * 0.12, LABEL);
+                }
+            }
+            Icon::Pillar => {
+                let half = cell * 0.18;
+                fill_rect(
+                    &mut img,
+                    centre.0 - half - 1.0,
+                    centre.1 - half - 1.0,
+                    centre.0 + half + 1.0,
+                    centre.1 + half + 1.0,
+                    OUTLINE,
+                );
+                fill_rect(
+                    &mut img,
+                    centre.0 - half,
+                    centre.1 - half,
+                    centre.0 + half,
+                    centre.1 + half,
+                    PILLAR,
+                );
+            }
+            Icon::Prop => {
+                let radius = (cell * 0.3).max(2.0);
+                fill_disc(&mut img, centre.0, centre.1, radius + 1.0, OUTLINE);
+                fill_disc(
+                    &mut img,
+                    centre.0,
+                    centre.1,
+                    radius,
+                    prop_color(&placement.asset),
+                );
+            }
+        }
+    }
+    Some(img)
+}
+
+fn draw_grid(img: &mut RgbaImage, frame: &Frame) {
+    let cell = frame.cell_px;
+    fill_rect(
+        img,
+        LEFT_MARGIN as f32,
+        TOP_MARGIN as f32,
+        img.width() as f32,
+        img.height() as f32,
+        GRASS,
+    );
+
+    // Label every cell when there is room, otherwise every 5th.
+    let every = if cell >= 18 { 1 } else { 5 };
+    for i in 0..=frame.cols {
+        let x = (LEFT_MARGIN + i * cell) as i64;
+        let world = frame.min.0 + i as i32;
+        for y in TOP_MARGIN as i64..img.height() as i64 {
+            put(img, x, y, GRID_LINE);
+        }
+        if i < frame.cols && world.rem_euclid(every) == 0 {
+            let text = world.to_string();
+            let cx = x + cell as i64 / 2 - text_width(&text, 2) / 2;
+            draw_text(img, &text, cx, 3, 2, LABEL);
+        }
+    }
+    for j in 0..=frame.rows {
+        let y = (TOP_MARGIN + j * cell) as i64;
+        let world = frame.min.1 + j as i32;
+        for x in LEFT_MARGIN as i64..img.width() as i64 {
+            put(img, x, y, GRID_LINE);
+        }
+        if j < frame.rows && world.rem_euclid(every) == 0 {
+            let text = world.to_string();
+            let cx = LEFT_MARGIN as i64 - 3 - text_width(&text, 2);
+            draw_text(img, &text, cx, y + cell as i64 / 2 - 5, 2, LABEL);
+        }
+    }
+
+    // World axes run through the centres of cell column / row 0.
+    let (ax, az) = frame.to_px(0.0, 0.0);
+    if (frame.min.0..frame.min.0 + frame.cols as i32).contains(&0) {
+        for y in TOP_MARGIN as i64..img.height() as i64 {
+            put(img, ax as i64, y, AXIS_LINE);
+        }
+    }
+    if (frame.min.1..frame.min.1 + frame.rows as i32).contains(&0) {
+        for x in LEFT_MARGIN as i64..img.width() as i64 {
+            put(img, x, az as i64, AXIS_LINE);
+        }
+    }
+}
+
+/// Renders the current scene mirror to PNG bytes.
+pub fn render_scene_png() -> Result<Vec<u8>> {
+    let img = render(&crate::scene::snapshot())
+        .ok_or_else(|| anyhow!("The scene is empty, nothing to draw yet"))?;
+    let mut png = Vec::new();
+    img.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Text legend that goes along with the image, so the model knows what the colours mean.
+pub fn legend(assets: &[PlacedAsset]) -> String {
+    let mut props: Vec<String> = assets
+        .iter()
+        .map(|a| a.tile_placement())
+        .filter(|p| icon_for(p) == Icon::Prop)
+        .map(|p| asset_stem(&p.asset))
+        .collect();
+    props.sort();
+    props.dedup();
+
+    let mut out = String::from(
+        "Minimap legend: north (-Z) is up, +X is right, labels are world cell coordinates and the thin white lines run along x=0 and z=0. Dark grey strips = ground roads (yellow line = lane centre), blue = elevated road/bridge, brown = slope (white dot on the uphill side), grey squares = bridge pillars.",
+    );
+    if !props.is_empty() {
+        out.push_str(&format!(
+            " Coloured dots = other models ({}).",
+            props.join(", ")
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn asset(name: &str, x: f32, z: f32) -> PlacedAsset {
+        PlacedAsset::new(
+            None,
+            name.to_string(),
+            PathBuf::from(name),
+            [x, 0.0, z],
+            [0.0; 3],
+        )
+    }
+
+    #[test]
+    fn renders_occupied_cells_over_the_grid() {
+        let img = render(&[
+            asset("road-straight.glb", 0.0, 0.0),
+            asset("tree.glb", 3.0, 2.0),
+        ])
+        .unwrap();
+        // Cells -1..=4 by -1..=3 with a one-cell border, 40 px each.
+        assert_eq!(
+            img.dimensions(),
+            (LEFT_MARGIN + 6 * 40, TOP_MARGIN + 5 * 40)
+        );
+
+        let frame = Frame {
+            min: (-1, -1),
+            cols: 6,
+            rows: 5,
+            cell_px: 40,
+        };
+        let pixel = |x: f32, z: f32| {
+            let (px, py) = frame.to_px(x, z);
+            *img.get_pixel(px as u32, py as u32)
+        };
+        // Beside the lane marking, on the asphalt.
+        assert_eq!(pixel(0.3, 0.1), ROAD);
+        assert_eq!(pixel(3.0, 2.0), prop_color("tree.glb"));
+        assert_eq!(pixel(2.0, 0.1), GRASS);
+    }
+
+    #[test]
+    fn empty_scene_has_no_image() {
+        assert!(render(&[]).is_none());
+    }
+}
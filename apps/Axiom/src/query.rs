@@ -0,0 +1,612 @@
+//! Small query language for picking spawned entities, e.g.
+//! `asset ~ "road-bend*" within 3 of [2,0,2]`, `group = "roundabout"` or `y > 0 or label = gate`.
+//!
+//! Conditions next to each other (or joined by `and`) must all hold, `or` binds weaker than
+//! `and`, and `not` / parentheses work as usual. An empty query, `*` or `all` matches everything.
+use crate::scene::PlacedAsset;
+use anyhow::{anyhow, Result};
+use axiom_roads::cell_at;
+use glam::{Vec2, Vec3};
+
+/// Entity property a condition looks at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Id,
+    Asset,
+    Label,
+    Group,
+    X,
+    Y,
+    Z,
+    Yaw,
+    Cell,
+}
+
+impl Field {
+    fn parse(s: &str) -> Option<Field> {
+        match s.to_lowercase().as_str() {
+            "id" | "entity" => Some(Field::Id),
+            "asset" | "model" => Some(Field::Asset),
+            "label" | "name" => Some(Field::Label),
+            "group" => Some(Field::Group),
+            "x" => Some(Field::X),
+            "y" => Some(Field::Y),
+            "z" => Some(Field::Z),
+            "yaw" | "rotation" => Some(Field::Yaw),
+            "cell" => Some(Field::Cell),
+            _ => None,
+        }
+    }
+
+    fn is_text(self) -> bool {
+        matches!(self, Field::Asset | Field::Label | Field::Group)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Like,
+    NotLike,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    Text(String),
+    Number(f32),
+    /// Entity ids are 64-bit and lose precision as floats, so they keep their own type.
+    Id(u64),
+    Vector(Vec<f32>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    All,
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Compare {
+        field: Field,
+        op: Op,
+        value: Literal,
+    },
+    /// Distance to a point: `[x, z]` measures on the ground, `[x, y, z]` in 3D.
+    Within {
+        radius: f32,
+        point: Vec<f32>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Text(String),
+    /// Source text of a number; what it parses to depends on the field it is compared with.
+    Number(String),
+    Word(String),
+    Op(Op),
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+    Comma,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '(' | ')' | '[' | ']' | ',' => {
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    '[' => Token::OpenBracket,
+                    ']' => Token::CloseBracket,
+                    _ => Token::Comma,
+                });
+                i += 1;
+            }
+            '"' | '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&q| q == c)
+                    .ok_or_else(|| anyhow!("Unclosed quote at position {}", i))?;
+                tokens.push(Token::Text(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            '=' | '!' | '<' | '>' | '~' => {
+                let (op, len) = match (c, next) {
+                    ('=', Some('=')) => (Op::Eq, 2),
+                    ('=', _) => (Op::Eq, 1),
+                    ('!', Some('=')) => (Op::Ne, 2),
+                    ('!', Some('~')) => (Op::NotLike, 2),
+                    ('<', Some('=')) => (Op::Le, 2),
+                    ('<', _) => (Op::Lt, 1),
+                    ('>', Some('=')) => (Op::Ge, 2),
+                    ('>', _) => (Op::Gt, 1),
+                    ('~', _) => (Op::Like, 1),
+                    _ => return Err(anyhow!("Unexpected '{}' at position {}", c, i)),
+                };
+                tokens.push(Token::Op(op));
+                i += len;
+            }
+            _ if c.is_ascii_digit()
+                || (c == '-' || c == '.') && next.is_some_and(|n| n.is_ascii_digit()) =>
+            {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                if text.parse::<f32>().is_err() {
+                    return Err(anyhow!("Bad number '{}' at position {}", text, start));
+                }
+                tokens.push(Token::Number(text));
+            }
+            _ if c.is_alphanumeric() || matches!(c, '_' | '*' | '?' | '.' | '-') => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric()
+                        || matches!(chars[i], '_' | '*' | '?' | '.' | '-'))
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+            _ => return Err(anyhow!("Unexpected '{}' at position {}", c, i)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case(word) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut terms = vec![self.and()?];
+        while self.keyword("or") {
+            terms.push(self.and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::Or(terms)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut terms = vec![self.condition()?];
+        loop {
+            if self.keyword("and") {
+                terms.push(self.condition()?);
+                continue;
+            }
+            match self.peek() {
+                None | Some(Token::Close) => break,
+                Some(Token::Word(w)) if w.eq_ignore_ascii_case("or") => break,
+                // Juxtaposed conditions: `asset ~ x within 3 of [0,0]`.
+                _ => terms.push(self.condition()?),
+            }
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::And(terms)
+        })
+    }
+
+    fn condition(&mut self) -> Result<Expr> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.condition()?)));
+        }
+        if self.keyword("within") {
+            let radius = match self.next() {
+                Some(Token::Number(n)) => number(&n),
+                other => {
+                    return Err(anyhow!(
+                        "Expected a distance after 'within', got {:?}",
+                        other
+                    ))
+                }
+            };
+            if !self.keyword("of") {
+                return Err(anyhow!("Expected 'of' in 'within {} of [x, z]'", radius));
+            }
+            let point = self.vector()?;
+            if !(2..=3).contains(&point.len()) {
+                return Err(anyhow!("'within' needs a point [x, z] or [x, y, z]"));
+            }
+            return Ok(Expr::Within { radius, point });
+        }
+        match self.next() {
+            Some(Token::Open) => {
+                let inner = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(inner),
+                    _ => Err(anyhow!("Missing ')'")),
+                }
+            }
+            Some(Token::Word(w)) => {
+                let field = Field::parse(&w).ok_or_else(|| {
+                    anyhow!(
+                        "Unknown field '{}' (use id, asset, label, group, x, y, z, yaw or cell)",
+                        w
+                    )
+                })?;
+                let op = match self.next() {
+                    Some(Token::Op(op)) => op,
+                    other => {
+                        return Err(anyhow!(
+                            "Expected an operator after '{}', got {:?}",
+                            w,
+                            other
+                        ))
+                    }
+                };
+                let value = match self.next() {
+                    Some(Token::Text(s)) | Some(Token::Word(s)) => Literal::Text(s),
+                    Some(Token::Number(n)) if field == Field::Id => {
+                        Literal::Id(n.parse().map_err(|_| anyhow!("Bad entity id '{}'", n))?)
+                    }
+                    Some(Token::Number(n)) => Literal::Number(number(&n)),
+                    Some(Token::OpenBracket) => {
+                        self.pos -= 1;
+                        Literal::Vector(self.vector()?)
+                    }
+                    other => {
+                        return Err(anyhow!("Expected a value after '{}', got {:?}", w, other))
+                    }
+                };
+                check(field, op, &value)?;
+                Ok(Expr::Compare { field, op, value })
+            }
+            other => Err(anyhow!("Expected a condition, got {:?}", other)),
+        }
+    }
+
+    fn vector(&mut self) -> Result<Vec<f32>> {
+        if self.next() != Some(Token::OpenBracket) {
+            return Err(anyhow!("Expected '['"));
+        }
+        let mut values = Vec::new();
+        loop {
+            match self.next() {
+                Some(Token::Number(n)) => values.push(number(&n)),
+                Some(Token::Comma) => {}
+                Some(Token::CloseBracket) => return Ok(values),
+                other => return Err(anyhow!("Expected a number in [...], got {:?}", other)),
+            }
+        }
+    }
+}
+
+/// Value of a number token, which the tokenizer already checked.
+fn number(text: &str) -> f32 {
+    text.parse().unwrap_or_default()
+}
+
+/// Rejects comparisons that can never be true, so typos surface as errors instead of empty results.
+fn check(field: Field, op: Op, value: &Literal) -> Result<()> {
+    let ok = match (field, value) {
+        (Field::Cell, Literal::Vector(v)) => v.len() == 2 && matches!(op, Op::Eq | Op::Ne),
+        (Field::Id, Literal::Id(_)) => !matches!(op, Op::Like | Op::NotLike),
+        (f, Literal::Text(_)) if f.is_text() => {
+            matches!(op, Op::Eq | Op::Ne | Op::Like | Op::NotLike)
+        }
+        (Field::X | Field::Y | Field::Z | Field::Yaw, Literal::Number(_)) => {
+            !matches!(op, Op::Like | Op::NotLike)
+        }
+        _ => false,
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(anyhow!("Cannot compare {:?} {:?} {:?}", field, op, value))
+    }
+}
+
+/// Parses a query. See the module docs for the syntax.
+pub fn parse(input: &str) -> Result<Expr> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() || trimmed == "*" || trimmed.eq_ignore_ascii_case("all") {
+        return Ok(Expr::All);
+    }
+    let mut parser = Parser {
+        tokens: tokenize(trimmed)?,
+        pos: 0,
+    };
+    let expr = parser.or()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(anyhow!("Unexpected {:?}", parser.tokens[parser.pos]));
+    }
+    Ok(expr)
+}
+
+fn model_stem(filename: &str) -> &str {
+    filename.rsplit_once('.').map_or(filename, |(stem, _)| stem)
+}
+
+/// Case-insensitive glob match (`*`, `?`, `[...]`).
+fn like(pattern: &str, text: &str) -> bool {
+    let options = glob::MatchOptions {
+        case_sensitive: false,
+        ..Default::default()
+    };
+    glob::Pattern::new(pattern).is_ok_and(|p| p.matches_with(text, options))
+}
+
+fn compare_text(op: Op, pattern: &str, texts: &[&str]) -> bool {
+    let any = |f: &dyn Fn(&str) -> bool| texts.iter().any(|t| f(t));
+    match op {
+        Op::Eq => any(&|t| t.eq_ignore_ascii_case(pattern)),
+        Op::Ne => !any(&|t| t.eq_ignore_ascii_case(pattern)),
+        Op::Like => any(&|t| like(pattern, t)),
+        Op::NotLike => !any(&|t| like(pattern, t)),
+        _ => false,
+    }
+}
+
+fn compare_number(op: Op, a: f32, b: f32) -> bool {
+    const EPS: f32 = 1e-3;
+    match op {
+        Op::Eq => (a - b).abs() < EPS,
+        Op::Ne => (a - b).abs() >= EPS,
+        Op::Lt => a < b - EPS,
+        Op::Le => a <= b + EPS,
+        Op::Gt => a > b + EPS,
+        Op::Ge => a >= b - EPS,
+        Op::Like | Op::NotLike => false,
+    }
+}
+
+fn compare_id(op: Op, a: u64, b: u64) -> bool {
+    match op {
+        Op::Eq => a == b,
+        Op::Ne => a != b,
+        Op::Lt => a < b,
+        Op::Le => a <= b,
+        Op::Gt => a > b,
+        Op::Ge => a >= b,
+        Op::Like | Op::NotLike => false,
+    }
+}
+
+impl Expr {
+    pub fn matches(&self, asset: &PlacedAsset) -> bool {
+        match self {
+            Expr::All => true,
+            Expr::And(terms) => terms.iter().all(|t| t.matches(asset)),
+            Expr::Or(terms) => terms.iter().any(|t| t.matches(asset)),
+            Expr::Not(inner) => !inner.matches(asset),
+            Expr::Within { radius, point } => {
+                let [x, y, z] = asset.translation;
+                let distance = match point[..] {
+                    [px, pz] => Vec2::new(x, z).distance(Vec2::new(px, pz)),
+                    [px, py, pz] => Vec3::new(x, y, z).distance(Vec3::new(px, py, pz)),
+                    _ => return false,
+                };
+                distance <= radius + 1e-3
+            }
+            Expr::Compare { field, op, value } => match (field, value) {
+                (Field::Asset, Literal::Text(p)) => {
+                    compare_text(*op, p, &[&asset.filename, model_stem(&asset.filename)])
+                }
+                (Field::Label, Literal::Text(p)) => compare_text(
+                    *op,
+                    p,
+                    &asset.label.as_deref().into_iter().collect::<Vec<_>>(),
+                ),
+                (Field::Group, Literal::Text(p)) => compare_text(
+                    *op,
+                    p,
+                    &asset.group.as_deref().into_iter().collect::<Vec<_>>(),
+                ),
+                (Field::Id, Literal::Id(n)) => asset.entity.is_some_and(|e| compare_id(*op, e, *n)),
+                (Field::X, Literal::Number(n)) => compare_number(*op, asset.translation[0], *n),
+                (Field::Y, Literal::Number(n)) => compare_number(*op, asset.translation[1], *n),
+                (Field::Z, Literal::Number(n)) => compare_number(*op, asset.translation[2], *n),
+                (Field::Yaw, Literal::Number(n)) => {
+                    let yaw = asset.rotation[1].rem_euclid(360.0);
+                    compare_number(*op, yaw, n.rem_euclid(360.0))
+                }
+                (Field::Cell, Literal::Vector(v)) => {
+                    let cell = cell_at(asset.translation[0], asset.translation[2]);
+                    let same = cell == (v[0].round() as i32, v[1].round() as i32);
+                    (*op == Op::Eq) == same
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Items matching `expr`, in the given order.
+pub fn filter(items: &[PlacedAsset], expr: &Expr) -> Vec<PlacedAsset> {
+    items.iter().filter(|a| expr.matches(a)).cloned().collect()
+}
+
+fn fmt_number(v: f32) -> String {
+    let v = (v * 100.0).round() / 100.0 + 0.0;
+    if v.fract() == 0.0 {
+        format!("{}", v as i64)
+    } else {
+        format!("{}", v)
+    }
+}
+
+/// Compact table (id, label, asset, cell, rotation), one row per entity. Elevated entities show
+/// their height after the cell, and rotations other than a plain yaw are printed in full.
+pub fn table(items: &[PlacedAsset], limit: usize) -> String {
+    let mut out = String::from("id | label | asset | cell | rot\n");
+    for a in items.iter().take(limit) {
+        let [x, y, z] = a.translation;
+        let (cx, cz) = cell_at(x, z);
+        let mut cell = format!("{},{}", cx, cz);
+        if y.abs() > 1e-3 {
+            cell.push_str(&format!(" y{}", fmt_number(y)));
+        }
+        let rot = match a.rotation {
+            [rx, yaw, rz] if rx == 0.0 && rz == 0.0 => fmt_number(yaw),
+            r => format!("[{}]", r.map(fmt_number).join(",")),
+        };
+        out.push_str(&format!(
+            "{} | {} | {} | {} | {}\n",
+            a.entity.map_or("-".to_string(), |e| e.to_string()),
+            a.label.as_deref().unwrap_or("-"),
+            model_stem(&a.filename),
+            cell,
+            rot
+        ));
+    }
+    if items.len() > limit {
+        out.push_str(&format!("... {} more\n", items.len() - limit));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn asset(filename: &str, translation: [f32; 3], yaw: f32) -> PlacedAsset {
+        PlacedAsset {
+            entity: None,
+            filename: filename.to_string(),
+            local_path: PathBuf::from(filename),
+            translation,
+            rotation: [0.0, yaw, 0.0],
+            scale: [1.0; 3],
+            label: None,
+            group: None,
+        }
+    }
+
+    fn matching(query: &str, items: &[PlacedAsset]) -> Vec<String> {
+        filter(items, &parse(query).unwrap())
+            .into_iter()
+            .map(|a| a.filename)
+            .collect()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let compare = |field, value: f32| Expr::Compare {
+            field,
+            op: Op::Eq,
+            value: Literal::Number(value),
+        };
+        assert_eq!(
+            parse("x = 1 or y = 2 and z = 3").unwrap(),
+            Expr::Or(vec![
+                compare(Field::X, 1.0),
+                Expr::And(vec![compare(Field::Y, 2.0), compare(Field::Z, 3.0)]),
+            ])
+        );
+        assert_eq!(
+            parse("(x = 1 or y = 2) z = 3").unwrap(),
+            Expr::And(vec![
+                Expr::Or(vec![compare(Field::X, 1.0), compare(Field::Y, 2.0)]),
+                compare(Field::Z, 3.0),
+            ])
+        );
+        assert_eq!(
+            parse("not x = 1").unwrap(),
+            Expr::Not(Box::new(compare(Field::X, 1.0)))
+        );
+    }
+
+    #[test]
+    fn quoted_text_and_not_like() {
+        let mut items = vec![
+            asset("road-bend.glb", [0.0; 3], 0.0),
+            asset("road-end.glb", [0.0; 3], 0.0),
+        ];
+        items[1].label = Some("north gate".to_string());
+        assert_eq!(matching("asset !~ 'road-b*'", &items), ["road-end.glb"]);
+        assert_eq!(matching("label = \"North Gate\"", &items), ["road-end.glb"]);
+        assert_eq!(matching("asset = road-bend", &items), ["road-bend.glb"]);
+    }
+
+    #[test]
+    fn rejects_unclosed_quotes_and_bad_tokens() {
+        let err = |q: &str| parse(q).unwrap_err().to_string();
+        assert_eq!(err("label = \"gate"), "Unclosed quote at position 8");
+        assert_eq!(err("x = 1.2.3"), "Bad number '1.2.3' at position 4");
+        assert_eq!(err("x = 1 ; y = 2"), "Unexpected ';' at position 6");
+        assert!(err("height > 2").starts_with("Unknown field 'height'"));
+        assert!(err("x ~ 2").starts_with("Cannot compare X Like"));
+        assert_eq!(err("(x = 1"), "Missing ')'");
+    }
+
+    #[test]
+    fn within_measures_on_the_ground_or_in_3d() {
+        let items = [
+            asset("near.glb", [3.0, 0.0, 4.0], 0.0),
+            asset("raised.glb", [3.0, 4.0, 4.0], 0.0),
+        ];
+        assert_eq!(
+            matching("within 5 of [0, 0]", &items),
+            ["near.glb", "raised.glb"]
+        );
+        assert_eq!(matching("within 5 of [0, 0, 0]", &items), ["near.glb"]);
+        assert!(parse("within 5 of [0]").is_err());
+    }
+
+    #[test]
+    fn cell_and_yaw() {
+        let items = [
+            asset("a.glb", [2.2, 0.0, -0.8], -90.0),
+            asset("b.glb", [5.0, 0.0, 5.0], 180.0),
+        ];
+        assert_eq!(matching("cell = [2, -1]", &items), ["a.glb"]);
+        assert_eq!(matching("cell != [2, -1]", &items), ["b.glb"]);
+        assert_eq!(matching("yaw = 270", &items), ["a.glb"]);
+        assert_eq!(matching("rotation >= 180", &items), ["a.glb", "b.glb"]);
+        assert!(parse("cell = [1, 2, 3]").is_err());
+    }
+
+    #[test]
+    fn ids_compare_exactly() {
+        let mut items = vec![asset("a.glb", [0.0; 3], 0.0), asset("b.glb", [0.0; 3], 0.0)];
+        // Neighbouring ids that are the same number as f32.
+        items[0].entity = Some(4294967297);
+        items[1].entity = Some(4294967298);
+        assert_eq!(matching("id = 4294967298", &items), ["b.glb"]);
+        assert_eq!(matching("id < 4294967298", &items), ["a.glb"]);
+        assert!(parse("id = 1.5").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use axiom_roads::TilePlacement;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+/// One asset Axiom has spawned in the running game.
+#[derive(Clone, Debug)]
+pub struct PlacedAsset {
+    /// Entity id returned by `world.spawn_entity`, if the game reported one.
+    pub entity: Option<u64>,
+    pub filename: String,
+    pub local_path: PathBuf,
+    pub translation: [f32; 3],
+    /// Euler angles in degrees, as passed to `bevy_upload_asset`.
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+    /// Name the agent gave this entity, to select it again later.
+    pub label: Option<String>,
+    /// Set of entities this one belongs to, e.g. `street-lights`.
+    pub group: Option<String>,
+}
+
+impl PlacedAsset {
+    pub fn new(
+        entity: Option<u64>,
+        filename: String,
+        local_path: PathBuf,
+        translation: [f32; 3],
+        rotation: [f32; 3],
+    ) -> Self {
+        Self {
+            entity,
+            filename,
+            local_path,
+            translation,
+            rotation,
+            scale: [1.0; 3],
+            label: None,
+            group: None,
+        }
+    }
+
+    pub fn tile_placement(&self) -> TilePlacement {
+        TilePlacement::new(self.filename.clone(), self.translation, self.rotation[1])
+    }
+}
+
+// Editor-side mirror of each game's scene, keyed by BRP endpoint and filled by the Bevy tools
+// as they spawn things.
+static SCENE_MIRROR: OnceLock<Mutex<HashMap<String, Vec<PlacedAsset>>>> = OnceLock::new();
+
+fn get_mirror() -> &'static Mutex<HashMap<String, Vec<PlacedAsset>>> {
+    SCENE_MIRROR.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Runs `f` on the mirror of the game the BRP calls currently go to.
+fn with_scene<R>(f: impl FnOnce(&mut Vec<PlacedAsset>) -> R) -> Option<R> {
+    let mut mirrors = get_mirror().lock().ok()?;
+    Some(f(mirrors.entry(crate::brp::endpoint()).or_default()))
+}
+
+pub fn record(asset: PlacedAsset) {
+    with_scene(|scene| scene.push(asset));
+}
+
+/// Changes the mirrored state of one entity. Returns false if it is not in the mirror.
+pub fn update(entity: u64, change: impl FnOnce(&mut PlacedAsset)) -> bool {
+    with_scene(
+        |scene| match scene.iter_mut().find(|asset| asset.entity == Some(entity)) {
+            Some(asset) => {
+                change(asset);
+                true
+            }
+            None => false,
+        },
+    )
+    .unwrap_or(false)
+}
+
+/// Drops an entity that was despawned in the game.
+pub fn forget(entity: u64) {
+    with_scene(|scene| scene.retain(|asset| asset.entity != Some(entity)));
+}
+
+pub fn clear() {
+    with_scene(|scene| scene.clear());
+}
+
+/// Forgets everything mirrored for `endpoint`, e.g. once that game restarted with an empty world.
+pub fn clear_game(endpoint: &str) {
+    if let Ok(mut mirrors) = get_mirror().lock() {
+        mirrors.remove(endpoint);
+    }
+}
+
+pub fn snapshot() -> Vec<PlacedAsset> {
+    with_scene(|scene| scene.clone()).unwrap_or_default()
+}
+
+pub fn tile_placements() -> Vec<TilePlacement> {
+    snapshot().iter().map(PlacedAsset::tile_placement).collect()
+}
@@ -0,0 +1,341 @@
+use crate::scene::PlacedAsset;
+use axiom_roads::{asset_stem, cell_at, Cell, Dir, TileKind, TilePlacement, LEVEL_HEIGHT};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Token budget for the scene map when `AXIOM_SCENE_MAP_TOKENS` is not set.
+pub const DEFAULT_TOKEN_BUDGET: usize = 1500;
+
+pub fn token_budget() -> usize {
+    std::env::var("AXIOM_SCENE_MAP_TOKENS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_TOKEN_BUDGET)
+}
+
+/// Rough token count: box-drawing characters cost about a token each, plain ASCII about a quarter.
+pub fn estimate_tokens(text: &str) -> usize {
+    let (ascii, other) =
+        text.chars().fold(
+            (0, 0),
+            |(a, o), c| if c.is_ascii() { (a + 1, o) } else { (a, o + 1) },
+        );
+    ascii / 4 + other
+}
+
+/// What ends up in one map cell. Higher levels win over lower ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Glyph {
+    Road { mask: u8, elevated: bool },
+    Slope(Dir),
+    Wide,
+    Pillar,
+}
+
+/// Road glyph for an opening mask (N=1, E=2, S=4, W=8). Ground roads use double lines,
+/// elevated roads single lines.
+fn road_char(mask: u8, elevated: bool) -> char {
+    let (double, single) = match mask {
+        0b0101 => ('║', '│'),
+        0b1010 => ('═', '─'),
+        0b0110 => ('╔', '┌'),
+        0b1100 => ('╗', '┐'),
+        0b0011 => ('╚', '└'),
+        0b1001 => ('╝', '┘'),
+        0b1110 => ('╦', '┬'),
+        0b1011 => ('╩', '┴'),
+        0b1101 => ('╣', '┤'),
+        0b0111 => ('╠', '├'),
+        0b1111 => ('╬', '┼'),
+        0b0001 => ('╨', '╵'),
+        0b0010 => ('╞', '╶'),
+        0b0100 => ('╥', '╷'),
+        0b1000 => ('╡', '╴'),
+        _ => ('?', '?'),
+    };
+    if elevated {
+        single
+    } else {
+        double
+    }
+}
+
+fn glyph_char(glyph: Glyph) -> char {
+    match glyph {
+        Glyph::Road { mask, elevated } => road_char(mask, elevated),
+        Glyph::Slope(Dir::North) => '^',
+        Glyph::Slope(Dir::East) => '>',
+        Glyph::Slope(Dir::South) => 'v',
+        Glyph::Slope(Dir::West) => '<',
+        Glyph::Wide => '~',
+        Glyph::Pillar => 'I',
+    }
+}
+
+fn glyph_legend(c: char) -> &'static str {
+    match c {
+        '═' | '║' => "straight road",
+        '╔' | '╗' | '╚' | '╝' => "bend",
+        '╦' | '╩' | '╣' | '╠' => "T-junction",
+        '╬' => "crossroad",
+        '╨' | '╞' | '╥' | '╡' => "dead end (open towards the line)",
+        '─' | '│' | '┌' | '┐' | '└' | '┘' | '┬' | '┴' | '┤' | '├' | '┼' | '╵' | '╶' | '╷' | '╴' => {
+            "elevated road / bridge (same shapes, single line)"
+        }
+        '^' | '>' | 'v' | '<' => "slope, arrow points uphill",
+        '~' => "part of a multi-cell piece (wide curve, roundabout)",
+        'I' => "bridge pillar with no road on top",
+        '?' => "road piece with unusual openings",
+        _ => "",
+    }
+}
+
+/// Splits the scene into grid glyphs (roads, slopes, pillars) and everything else.
+fn rasterize(assets: &[PlacedAsset]) -> (BTreeMap<Cell, (i32, Glyph)>, Vec<&PlacedAsset>) {
+    let mut grid: BTreeMap<Cell, (i32, Glyph)> = BTreeMap::new();
+    let mut masks: HashMap<(Cell, i32), u8> = HashMap::new();
+    let mut others = Vec::new();
+
+    let mut put = |cell: Cell, level: i32, glyph: Glyph| {
+        let entry = grid.entry(cell).or_insert((level, glyph));
+        // Pillars never hide a road, roads on top hide what is below.
+        if entry.1 == Glyph::Pillar || (level > entry.0 && glyph != Glyph::Pillar) {
+            *entry = (level, glyph);
+        }
+    };
+
+    for asset in assets {
+        let placement: TilePlacement = asset.tile_placement();
+        let level = (placement.translation[1] / LEVEL_HEIGHT).round() as i32;
+        match placement.spec() {
+            Some(spec) if spec.kind == TileKind::Slope => {
+                let uphill = Dir::East.rotate(placement.quarter_turns());
+                for cell in placement.cells() {
+                    put(cell, level, Glyph::Slope(uphill));
+                }
+            }
+            Some(spec) if spec.size != [1, 1] => {
+                for cell in placement.cells() {
+                    put(cell, level, Glyph::Wide);
+                }
+            }
+            Some(_) => {
+                let cell = cell_at(placement.translation[0], placement.translation[2]);
+                let mask = masks.entry((cell, level)).or_insert(0);
+                for port in placement.world_ports().unwrap_or_default() {
+                    *mask |= port.dir.bit();
+                }
+                let mask = *mask;
+                put(
+                    cell,
+                    level,
+                    Glyph::Road {
+                        mask,
+                        elevated: level > 0,
+                    },
+                );
+            }
+            None if asset_stem(&placement.asset).starts_with("bridge-pillar") => {
+                let cell = cell_at(placement.translation[0], placement.translation[2]);
+                put(cell, level, Glyph::Pillar);
+            }
+            None => others.push(asset),
+        }
+    }
+    (grid, others)
+}
+
+fn render_grid(grid: &BTreeMap<Cell, (i32, Glyph)>) -> (String, BTreeSet<char>) {
+    let mut used = BTreeSet::new();
+    let min_x = grid.keys().map(|c| c.0).min().unwrap_or(0);
+    let max_x = grid.keys().map(|c| c.0).max().unwrap_or(0);
+    let min_z = grid.keys().map(|c| c.1).min().unwrap_or(0);
+    let max_z = grid.keys().map(|c| c.1).max().unwrap_or(0);
+
+    let label_width = min_z.to_string().len().max(max_z.to_string().len());
+    let mut out = String::new();
+
+    // Column ruler: a tick every 5 cells, labelled with the world x of that column.
+    let mut ruler: Vec<char> = vec![' '; (max_x - min_x + 1) as usize + 8];
+    for x in min_x..=max_x {
+        if x.rem_euclid(5) == 0 {
+            for (i, c) in x.to_string().chars().enumerate() {
+                if let Some(slot) = ruler.get_mut((x - min_x) as usize + i) {
+                    *slot = c;
+                }
+            }
+        }
+    }
+    out.push_str(&format!(
+        "{:>w$} {}\n",
+        "x:",
+        ruler.iter().collect::<String>().trim_end(),
+        w = label_width + 1
+    ));
+
+    for z in min_z..=max_z {
+        out.push_str(&format!("{:>w$}| ", z, w = label_width));
+        for x in min_x..=max_x {
+            let c = grid.get(&(x, z)).map_or('.', |&(_, g)| glyph_char(g));
+            if c != '.' {
+                used.insert(c);
+            }
+            out.push(c);
+        }
+        out.push('\n');
+    }
+    (out, used)
+}
+
+fn legend(used: &BTreeSet<char>) -> String {
+    let mut groups: Vec<(&str, String)> = Vec::new();
+    for &c in used {
+        let meaning = glyph_legend(c);
+        match groups.iter_mut().find(|(m, _)| *m == meaning) {
+            Some((_, chars)) => chars.push(c),
+            None => groups.push((meaning, c.to_string())),
+        }
+    }
+    groups
+        .iter()
+        .map(|(meaning, chars)| format!("{} {}", chars, meaning))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn object_line(asset: &PlacedAsset) -> String {
+    let [x, y, z] = asset.translation;
+    let mut line = format!(
+        "- {} at ({:.2}, {:.2}, {:.2}) yaw {:.0}°",
+        asset.filename, x, y, z, asset.rotation[1]
+    );
+    if let Some(entity) = asset.entity {
+        line.push_str(&format!(", entity {}", entity));
+    }
+    line
+}
+
+/// Compact text picture of the scene for the system prompt: roads, slopes and pillars drawn on the
+/// XZ grid (one character per cell, north up) followed by the models that are not grid pieces.
+/// Stays under `max_tokens` by shortening the object list first and dropping the map last.
+/// Returns `None` when nothing has been placed.
+pub fn summarize(assets: &[PlacedAsset], max_tokens: usize) -> Option<String> {
+    if assets.is_empty() {
+        return None;
+    }
+    let (grid, others) = rasterize(assets);
+
+    let mut header = format!(
+        "# 🗺️ CURRENT SCENE ({} assets placed by the editor)\n",
+        assets.len()
+    );
+    let map = if grid.is_empty() {
+        None
+    } else {
+        let (map, used) = render_grid(&grid);
+        Some(format!(
+            "Road grid, one character per cell, north (-Z) up, rows labelled with z:\n```\n{}```\nLegend: {}\n",
+            map,
+            legend(&used)
+        ))
+    };
+
+    let objects: Vec<String> = others.iter().map(|a| object_line(a)).collect();
+    if let Some(map) = &map {
+        if let Some(out) = fit(&header, Some(map), &objects, max_tokens) {
+            return Some(out);
+        }
+        header.push_str(&format!(
+            "(Road map of {} cells left out to save context, call `scene_minimap` to see it.)\n",
+            grid.len()
+        ));
+    }
+    Some(fit(&header, None, &objects, max_tokens).unwrap_or(header))
+}
+
+/// Header, optional map and as much of the object list as fits in `max_tokens`.
+fn fit(
+    header: &str,
+    map: Option<&String>,
+    objects: &[String],
+    max_tokens: usize,
+) -> Option<String> {
+    let mut shown = objects.len();
+    loop {
+        let mut out = header.to_string();
+        if let Some(map) = map {
+            out.push_str(map);
+        }
+        if !objects.is_empty() {
+            out.push_str("Other objects:\n");
+            for line in &objects[..shown] {
+                out.push_str(line);
+                out.push('\n');
+            }
+            if shown < objects.len() {
+                out.push_str(&format!("- ... and {} more\n", objects.len() - shown));
+            }
+        }
+        if estimate_tokens(&out) <= max_tokens {
+            return Some(out);
+        }
+        if shown == 0 {
+            return None;
+        }
+        shown /= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn asset(name: &str, x: f32, z: f32) -> PlacedAsset {
+        PlacedAsset::new(
+            None,
+            name.to_string(),
+            PathBuf::from(name),
+            [x, 0.0, z],
+            [0.0; 3],
+        )
+    }
+
+    /// A road along x from 0 to `length` and `trees` trees beside it.
+    fn scene(length: i32, trees: usize) -> Vec<PlacedAsset> {
+        let mut assets: Vec<PlacedAsset> = (0..length)
+            .map(|x| asset("road-straight.glb", x as f32, 0.0))
+            .collect();
+        assets.extend((0..trees).map(|i| asset("tree.glb", i as f32, 3.0)));
+        assets
+    }
+
+    #[test]
+    fn small_scenes_are_shown_whole() {
+        assert!(summarize(&[], DEFAULT_TOKEN_BUDGET).is_none());
+        let map = summarize(&scene(5, 2), DEFAULT_TOKEN_BUDGET).unwrap();
+        assert!(map.contains("═════"), "{}", map);
+        assert!(map.contains("straight road"), "{}", map);
+        assert!(map.contains("- tree.glb at (1.00, 0.00, 3.00)"), "{}", map);
+        assert!(!map.contains("more"), "{}", map);
+    }
+
+    #[test]
+    fn object_list_shrinks_before_the_map() {
+        let assets = scene(5, 200);
+        let whole = summarize(&assets, usize::MAX).unwrap();
+        let budget = estimate_tokens(&whole) / 3;
+
+        let map = summarize(&assets, budget).unwrap();
+        assert!(estimate_tokens(&map) <= budget);
+        assert!(map.contains("═════"), "{}", map);
+        assert!(map.contains("more\n"), "{}", map);
+    }
+
+    #[test]
+    fn map_is_left_out_when_it_cannot_fit() {
+        let assets = scene(400, 0);
+        let map = summarize(&assets, 20).unwrap();
+        assert!(map.contains("Road map of 400 cells left out"), "{}", map);
+        assert!(!map.contains('═'), "{}", map);
+    }
+}
@@ -0,0 +1,981 @@
+//! Type schemas from the game's registry (`registry.schema`), cached per connection.
+//!
+//! The agent asks for the fields of a component through `bevy_schema` instead of guessing them,
+//! and `bevy_rpc` payloads are checked here before they are sent, so a wrong type path or field
+//! name is answered with what the game actually expects.
+use crate::brp::{self, SchemaFilter};
+use crate::connection::{self, LinkState};
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+
+/// How deep descriptions expand nested types, and how deep validation follows them.
+const DESCRIBE_DEPTH: usize = 2;
+const VALIDATE_DEPTH: usize = 32;
+
+/// One entry of `registry.schema`, reduced to what Axiom reads.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TypeSchema {
+    pub short_path: String,
+    pub type_path: String,
+    /// Reflected traits, e.g. `Component`, `Resource`, `Default`, `Deserialize`.
+    pub reflect_types: Vec<String>,
+    /// `Struct`, `Enum`, `TupleStruct`, `List`, `Value`, ...
+    pub kind: String,
+    /// JSON type of the value: `object`, `array`, `float`, `uint`, `string`, ...
+    #[serde(rename = "type")]
+    pub json_type: String,
+    pub properties: Map<String, Value>,
+    pub required: Vec<String>,
+    pub one_of: Vec<Value>,
+    pub prefix_items: Vec<Value>,
+    pub items: Option<Value>,
+    pub value_type: Option<Value>,
+}
+
+impl TypeSchema {
+    pub fn reflects(&self, name: &str) -> bool {
+        self.reflect_types.iter().any(|r| r == name)
+    }
+
+    /// glam vectors and quaternions: reflected as structs but sent as `[x, y, z]`.
+    fn is_vector(&self) -> bool {
+        self.kind == "Struct"
+            && self.reflects("Deserialize")
+            && !self.properties.is_empty()
+            && self
+                .properties
+                .keys()
+                .all(|k| matches!(k.as_str(), "x" | "y" | "z" | "w"))
+    }
+
+    /// Field names, vector components in `x y z w` order and the rest sorted.
+    fn field_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.properties.keys().map(String::as_str).collect();
+        if self.is_vector() {
+            names.sort_by_key(|n| "xyzw".find(n));
+        } else {
+            names.sort();
+        }
+        names
+    }
+}
+
+/// Problems found in a payload.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// The game would reject the payload.
+    pub errors: Vec<String>,
+    /// Probably fine, but worth mentioning if the call fails (e.g. omitted fields).
+    pub warnings: Vec<String>,
+}
+
+fn ref_path(value: &Value) -> Option<&str> {
+    value["type"]["$ref"].as_str()?.strip_prefix("#/$defs/")
+}
+
+fn short_name(type_path: &str) -> &str {
+    // Generic arguments can contain `::`, so only the part before `<` is split.
+    let base = type_path.split('<').next().unwrap_or(type_path);
+    base.rsplit("::").next().unwrap_or(base)
+}
+
+/// The game's type registry.
+#[derive(Debug, Default)]
+pub struct Registry {
+    types: HashMap<String, TypeSchema>,
+}
+
+impl Registry {
+    pub fn from_schemas(schemas: HashMap<String, Value>) -> Self {
+        let types = schemas
+            .into_iter()
+            .filter_map(|(path, schema)| {
+                let mut schema: TypeSchema = serde_json::from_value(schema).ok()?;
+                if schema.type_path.is_empty() {
+                    schema.type_path = path.clone();
+                }
+                Some((path, schema))
+            })
+            .collect();
+        Self { types }
+    }
+
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    pub fn get(&self, type_path: &str) -> Option<&TypeSchema> {
+        self.types.get(type_path)
+    }
+
+    /// Components and resources whose name contains `query` (case-insensitive), sorted by
+    /// short name. `reflect` limits them to `Component` or `Resource`.
+    pub fn search(&self, query: &str, reflect: Option<&str>) -> Vec<&TypeSchema> {
+        let query = query.to_lowercase();
+        let mut found: Vec<&TypeSchema> = self
+            .types
+            .values()
+            .filter(|t| match reflect {
+                Some(reflect) => t.reflects(reflect),
+                None => t.reflects("Component") || t.reflects("Resource"),
+            })
+            .filter(|t| t.type_path.to_lowercase().contains(&query))
+            .collect();
+        found.sort_by(|a, b| {
+            a.short_path
+                .len()
+                .cmp(&b.short_path.len())
+                .then(a.short_path.cmp(&b.short_path))
+        });
+        found
+    }
+
+    /// A type by full path, or by short name (`Transform`, `PointLight`) when that is unique.
+    pub fn resolve(&self, name: &str) -> Result<&TypeSchema> {
+        let name = name.trim();
+        if let Some(schema) = self.get(name) {
+            return Ok(schema);
+        }
+        let mut matches: Vec<&TypeSchema> = self
+            .types
+            .values()
+            .filter(|t| t.short_path.eq_ignore_ascii_case(name))
+            .collect();
+        // Prefer what can be put on an entity or into the world.
+        if matches.len() > 1 {
+            let placeable: Vec<&TypeSchema> = matches
+                .iter()
+                .copied()
+                .filter(|t| t.reflects("Component") || t.reflects("Resource"))
+                .collect();
+            if !placeable.is_empty() {
+                matches = placeable;
+            }
+        }
+        match matches.as_slice() {
+            [schema] => Ok(schema),
+            [] => {
+                let similar: Vec<String> = self
+                    .search(short_name(name), None)
+                    .into_iter()
+                    .take(8)
+                    .map(|t| t.type_path.clone())
+                    .collect();
+                if similar.is_empty() {
+                    Err(anyhow!("No type `{}` in the game's registry", name))
+                } else {
+                    Err(anyhow!(
+                        "No type `{}` in the game's registry. Similar: {}",
+                        name,
+                        similar.join(", ")
+                    ))
+                }
+            }
+            several => {
+                let mut paths: Vec<&str> = several.iter().map(|t| t.type_path.as_str()).collect();
+                paths.sort();
+                Err(anyhow!(
+                    "`{}` is ambiguous, use the full path: {}",
+                    name,
+                    paths.join(", ")
+                ))
+            }
+        }
+    }
+
+    /// Compact shape of a value of `type_path`, e.g. `[x, y, z]` or `{ color: Color, .. }`.
+    fn shape(&self, type_path: &str, depth: usize) -> String {
+        let Some(t) = self.get(type_path) else {
+            return short_name(type_path).to_string();
+        };
+        if let Some(inner) = option_inner(t) {
+            return format!("{} | null", self.shape(inner, depth));
+        }
+        match t.kind.as_str() {
+            "Value" => t.short_path.clone(),
+            "Struct" if t.is_vector() => format!("[{}]", t.field_names().join(", ")),
+            "Struct" if depth == 0 => t.short_path.clone(),
+            "Struct" => {
+                let fields: Vec<String> = t
+                    .field_names()
+                    .into_iter()
+                    .map(|name| {
+                        let shape = ref_path(&t.properties[name])
+                            .map(|p| self.shape(p, depth - 1))
+                            .unwrap_or_else(|| "?".to_string());
+                        format!("{}: {}", name, shape)
+                    })
+                    .collect();
+                format!("{{ {} }}", fields.join(", "))
+            }
+            "TupleStruct" | "Tuple" => {
+                let items: Vec<String> = t
+                    .prefix_items
+                    .iter()
+                    .map(|item| {
+                        ref_path(item)
+                            .map(|p| self.shape(p, depth.saturating_sub(1)))
+                            .unwrap_or_else(|| "?".to_string())
+                    })
+                    .collect();
+                // A single-field tuple struct is sent as its field.
+                if t.kind == "TupleStruct" && items.len() == 1 {
+                    items[0].clone()
+                } else {
+                    format!("[{}]", items.join(", "))
+                }
+            }
+            "List" | "Array" | "Set" => {
+                let item = t
+                    .items
+                    .as_ref()
+                    .and_then(ref_path)
+                    .map(|p| self.shape(p, depth.saturating_sub(1)))
+                    .unwrap_or_else(|| "?".to_string());
+                format!("[{}, ..]", item)
+            }
+            "Map" => {
+                let value = t
+                    .value_type
+                    .as_ref()
+                    .and_then(ref_path)
+                    .map(|p| self.shape(p, depth.saturating_sub(1)))
+                    .unwrap_or_else(|| "?".to_string());
+                format!("{{ <key>: {} }}", value)
+            }
+            "Enum" if depth == 0 => t.short_path.clone(),
+            "Enum" => {
+                let variants: Vec<String> = t
+                    .one_of
+                    .iter()
+                    .map(|v| match v {
+                        Value::String(name) => format!("\"{}\"", name),
+                        variant => {
+                            let name = variant["shortPath"].as_str().unwrap_or("?");
+                            match variant["kind"].as_str() {
+                                Some("Struct") => format!("{{ \"{}\": {{..}} }}", name),
+                                Some("Tuple") => {
+                                    let inner: Vec<String> = variant["prefixItems"]
+                                        .as_array()
+                                        .map(|items| {
+                                            items
+                                                .iter()
+                                                .map(|i| {
+                                                    ref_path(i)
+                                                        .map(|p| self.shape(p, 0))
+                                                        .unwrap_or_else(|| "?".to_string())
+                                                })
+                                                .collect()
+                                        })
+                                        .unwrap_or_default();
+                                    if inner.len() == 1 {
+                                        format!("{{ \"{}\": {} }}", name, inner[0])
+                                    } else {
+                                        format!("{{ \"{}\": [{}] }}", name, inner.join(", "))
+                                    }
+                                }
+                                _ => format!("\"{}\"", name),
+                            }
+                        }
+                    })
+                    .collect();
+                variants.join(" | ")
+            }
+            _ => t.short_path.clone(),
+        }
+    }
+
+    /// Fields and JSON shape of one type, a few lines for the agent.
+    pub fn describe(&self, t: &TypeSchema) -> String {
+        let mut out = format!("{} ({})", t.short_path, t.type_path);
+        let traits: Vec<&str> = t
+            .reflect_types
+            .iter()
+            .map(String::as_str)
+            .filter(|r| matches!(*r, "Component" | "Resource" | "Default"))
+            .collect();
+        if !traits.is_empty() {
+            out.push_str(&format!(" [{}]", traits.join(", ")));
+        }
+        out.push('\n');
+
+        if t.kind == "Struct" && !t.is_vector() {
+            if t.properties.is_empty() {
+                out.push_str("  (no fields, send {})\n");
+            }
+            for name in t.field_names() {
+                let field_type = ref_path(&t.properties[name]).unwrap_or("?");
+                let optional = if t.required.iter().any(|r| r == name) {
+                    ""
+                } else {
+                    " (optional)"
+                };
+                out.push_str(&format!(
+                    "  {}: {} = {}{}\n",
+                    name,
+                    short_name(field_type),
+                    self.shape(field_type, DESCRIBE_DEPTH - 1),
+                    optional
+                ));
+            }
+        } else {
+            out.push_str(&format!(
+                "  = {}\n",
+                self.shape(&t.type_path, DESCRIBE_DEPTH)
+            ));
+        }
+        out
+    }
+
+    /// Checks the components of `world.spawn_entity` / `world.insert_components`.
+    pub fn validate_components(&self, components: &Map<String, Value>, report: &mut Report) {
+        for (path, value) in components {
+            self.validate_placeable(path, "Component", value, report);
+        }
+    }
+
+    /// Checks a value of the component or resource `path`.
+    pub fn validate_placeable(
+        &self,
+        path: &str,
+        reflect: &str,
+        value: &Value,
+        report: &mut Report,
+    ) {
+        match self.get(path) {
+            Some(t) if t.reflects(reflect) => self.check(path, value, path, 0, report),
+            Some(_) => report.errors.push(format!(
+                "`{}` is registered but not as a reflected {}",
+                path, reflect
+            )),
+            None => report.errors.push(match self.resolve(path) {
+                Ok(t) => format!(
+                    "Unknown type path `{}`, the game registers it as `{}`",
+                    path, t.type_path
+                ),
+                Err(e) => e.to_string(),
+            }),
+        }
+    }
+
+    /// Checks `value` for the field at reflect `field_path` (`.translation.x`) of `type_path`,
+    /// as sent to `world.mutate_components`. Paths it cannot follow are not checked.
+    pub fn validate_field(
+        &self,
+        type_path: &str,
+        field_path: &str,
+        value: &Value,
+        report: &mut Report,
+    ) {
+        let mut current = type_path.to_string();
+        for field in field_path.split('.').filter(|f| !f.is_empty()) {
+            let Some(next) = self
+                .get(&current)
+                .filter(|t| t.kind == "Struct")
+                .and_then(|t| {
+                    if !t.properties.contains_key(field) && !t.properties.is_empty() {
+                        report.errors.push(format!(
+                            "`{}` has no field `{}` (fields: {})",
+                            t.short_path,
+                            field,
+                            t.field_names().join(", ")
+                        ));
+                    }
+                    t.properties.get(field).and_then(ref_path)
+                })
+            else {
+                return;
+            };
+            current = next.to_string();
+        }
+        self.check(&current, value, field_path, 0, report);
+    }
+
+    fn check(&self, type_path: &str, value: &Value, at: &str, depth: usize, report: &mut Report) {
+        if depth > VALIDATE_DEPTH {
+            return;
+        }
+        // Types the registry does not describe are left to the game.
+        let Some(t) = self.get(type_path) else {
+            return;
+        };
+        if let Some(inner) = option_inner(t) {
+            if !value.is_null() {
+                self.check(inner, value, at, depth + 1, report);
+            }
+            return;
+        }
+        let mut wrong = |expected: &str| {
+            report.errors.push(format!(
+                "{}: expected {} for {}, got {}",
+                at, expected, t.short_path, value
+            ));
+        };
+
+        match t.kind.as_str() {
+            "Value" => {
+                let ok = match t.json_type.as_str() {
+                    "float" => value.is_number(),
+                    "int" => value.is_i64(),
+                    "uint" => value.is_u64(),
+                    "string" => value.is_string(),
+                    "boolean" => value.is_boolean(),
+                    _ => true,
+                };
+                if !ok {
+                    wrong(&t.json_type);
+                }
+            }
+            "Struct" if t.is_vector() => {
+                let names = t.field_names();
+                match value.as_array() {
+                    Some(items) if items.len() == names.len() => {
+                        if !items.iter().all(Value::is_number) {
+                            wrong(&format!("[{}] numbers", names.join(", ")));
+                        }
+                    }
+                    _ => wrong(&format!("[{}]", names.join(", "))),
+                }
+            }
+            "Struct" => match value.as_object() {
+                Some(fields) => {
+                    self.check_fields(t, &t.properties, &t.required, fields, at, depth, report)
+                }
+                None => wrong("an object"),
+            },
+            "TupleStruct" | "Tuple" => {
+                let items: Vec<&str> = t.prefix_items.iter().filter_map(ref_path).collect();
+                if t.kind == "TupleStruct" && items.len() == 1 {
+                    self.check(items[0], value, at, depth + 1, report);
+                    return;
+                }
+                match value.as_array() {
+                    Some(values) if values.len() == items.len() => {
+                        for (i, (item, value)) in items.iter().zip(values).enumerate() {
+                            self.check(item, value, &format!("{}[{}]", at, i), depth + 1, report);
+                        }
+                    }
+                    _ => wrong(&format!("an array of {}", items.len())),
+                }
+            }
+            "List" | "Array" | "Set" => match value.as_array() {
+                Some(values) => {
+                    if let Some(item) = t.items.as_ref().and_then(ref_path) {
+                        for (i, value) in values.iter().enumerate() {
+                            self.check(item, value, &format!("{}[{}]", at, i), depth + 1, report);
+                        }
+                    }
+                }
+                None => wrong("an array"),
+            },
+            "Map" => match value.as_object() {
+                Some(entries) => {
+                    if let Some(item) = t.value_type.as_ref().and_then(ref_path) {
+                        for (key, value) in entries {
+                            self.check(item, value, &format!("{}.{}", at, key), depth + 1, report);
+                        }
+                    }
+                }
+                None => wrong("an object"),
+            },
+            "Enum" => self.check_enum(t, value, at, depth, report),
+            _ => {}
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_fields(
+        &self,
+        t: &TypeSchema,
+        properties: &Map<String, Value>,
+        required: &[String],
+        fields: &Map<String, Value>,
+        at: &str,
+        depth: usize,
+        report: &mut Report,
+    ) {
+        for (name, value) in fields {
+            match properties.get(name) {
+                Some(field) => {
+                    if let Some(field_type) = ref_path(field) {
+                        self.check(
+                            field_type,
+                            value,
+                            &format!("{}.{}", at, name),
+                            depth + 1,
+                            report,
+                        );
+                    }
+                }
+                None => {
+                    let mut known: Vec<&str> = properties.keys().map(String::as_str).collect();
+                    known.sort();
+                    report.errors.push(format!(
+                        "{}: `{}` has no field `{}` (fields: {})",
+                        at,
+                        t.short_path,
+                        name,
+                        known.join(", ")
+                    ));
+                }
+            }
+        }
+        let missing: Vec<&str> = required
+            .iter()
+            .map(String::as_str)
+            .filter(|name| !fields.contains_key(*name))
+            .collect();
+        if !missing.is_empty() {
+            report.warnings.push(format!(
+                "{}: `{}` fields not given: {}",
+                at,
+                t.short_path,
+                missing.join(", ")
+            ));
+        }
+    }
+
+    fn check_enum(
+        &self,
+        t: &TypeSchema,
+        value: &Value,
+        at: &str,
+        depth: usize,
+        report: &mut Report,
+    ) {
+        let variant_name = |v: &Value| -> String {
+            match v {
+                Value::String(name) => name.clone(),
+                v => v["shortPath"].as_str().unwrap_or("?").to_string(),
+            }
+        };
+        let names: Vec<String> = t.one_of.iter().map(variant_name).collect();
+        let (name, payload) = match value {
+            Value::String(name) => (name.as_str(), None),
+            Value::Object(map) if map.len() == 1 => {
+                let (name, payload) = map.iter().next().unwrap();
+                (name.as_str(), Some(payload))
+            }
+            _ => {
+                report.errors.push(format!(
+                    "{}: expected a variant of {} (\"Name\" or {{\"Name\": ..}}): {}",
+                    at,
+                    t.short_path,
+                    names.join(", ")
+                ));
+                return;
+            }
+        };
+        let Some(variant) = t.one_of.iter().find(|v| variant_name(v) == name) else {
+            report.errors.push(format!(
+                "{}: {} has no variant `{}` (variants: {})",
+                at,
+                t.short_path,
+                name,
+                names.join(", ")
+            ));
+            return;
+        };
+        let Some(payload) = payload else {
+            return;
+        };
+        let at = format!("{}.{}", at, name);
+        match variant["kind"].as_str() {
+            Some("Struct") => match payload.as_object() {
+                Some(fields) => {
+                    let empty = Map::new();
+                    let properties = variant["properties"].as_object().unwrap_or(&empty);
+                    let required: Vec<String> = variant["required"]
+                        .as_array()
+                        .map(|r| {
+                            r.iter()
+                                .filter_map(|n| n.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    self.check_fields(t, properties, &required, fields, &at, depth, report);
+                }
+                None => report.errors.push(format!("{}: expected an object", at)),
+            },
+            Some("Tuple") => {
+                let items: Vec<&str> = variant["prefixItems"]
+                    .as_array()
+                    .map(|items| items.iter().filter_map(ref_path).collect())
+                    .unwrap_or_default();
+                match (items.as_slice(), payload.as_array()) {
+                    ([item], _) => self.check(item, payload, &at, depth + 1, report),
+                    (items, Some(values)) if values.len() == items.len() => {
+                        for (i, (item, value)) in items.iter().zip(values).enumerate() {
+                            self.check(item, value, &format!("{}[{}]", at, i), depth + 1, report);
+                        }
+                    }
+                    (items, _) => {
+                        report
+                            .errors
+                            .push(format!("{}: expected an array of {}", at, items.len()))
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Placeholder value of `type_path` in the shape the game accepts.
+    pub fn example(&self, type_path: &str, depth: usize) -> Value {
+        let Some(t) = self.get(type_path) else {
+            return Value::Null;
+        };
+        if option_inner(t).is_some() || depth > DESCRIBE_DEPTH + 2 {
+            return Value::Null;
+        }
+        let of = |item: Option<&Value>| {
+            item.and_then(ref_path)
+                .map(|p| self.example(p, depth + 1))
+                .unwrap_or(Value::Null)
+        };
+        match t.kind.as_str() {
+            "Value" => match t.json_type.as_str() {
+                "float" => json!(0.0),
+                "int" | "uint" => json!(0),
+                "string" => json!(""),
+                "boolean" => json!(false),
+                _ => Value::Null,
+            },
+            "Struct" if t.is_vector() => json!(vec![0.0; t.properties.len()]),
+            "Struct" => Value::Object(
+                t.field_names()
+                    .into_iter()
+                    .filter(|name| t.required.iter().any(|r| r == name))
+                    .map(|name| (name.to_string(), of(t.properties.get(name))))
+                    .collect(),
+            ),
+            "TupleStruct" if t.prefix_items.len() == 1 => of(t.prefix_items.first()),
+            "TupleStruct" | "Tuple" => {
+                Value::Array(t.prefix_items.iter().map(|i| of(Some(i))).collect())
+            }
+            "List" | "Array" | "Set" => json!([]),
+            "Map" => json!({}),
+            "Enum" => match t.one_of.first() {
+                Some(Value::String(name)) => json!(name),
+                Some(variant) => {
+                    let name = variant["shortPath"].as_str().unwrap_or("?");
+                    match variant["kind"].as_str() {
+                        Some("Tuple") => {
+                            let items = variant["prefixItems"]
+                                .as_array()
+                                .cloned()
+                                .unwrap_or_default();
+                            let inner: Vec<Value> = items.iter().map(|i| of(Some(i))).collect();
+                            if inner.len() == 1 {
+                                json!({ name: inner[0] })
+                            } else {
+                                json!({ name: inner })
+                            }
+                        }
+                        Some("Struct") => json!({ name: {} }),
+                        _ => json!(name),
+                    }
+                }
+                None => Value::Null,
+            },
+            _ => Value::Null,
+        }
+    }
+}
+
+/// `T` of an `Option<T>`, which is sent as the value or `null`.
+fn option_inner(t: &TypeSchema) -> Option<&str> {
+    if !t.type_path.starts_with("core::option::Option<") {
+        return None;
+    }
+    t.one_of
+        .iter()
+        .find(|v| v["shortPath"] == "Some")
+        .and_then(|v| v["prefixItems"].get(0))
+        .and_then(ref_path)
+}
+
+/// BRP methods whose payload [`validate_call`] checks.
+pub const CHECKED_METHODS: &[&str] = &[
+    "world.spawn_entity",
+    "world.insert_components",
+    "world.insert_resources",
+    "world.mutate_components",
+    "world.mutate_resources",
+];
+
+/// Checks the payload of a world-changing BRP call. `None` for methods without one.
+pub fn validate_call(registry: &Registry, method: &str, params: &Value) -> Option<Report> {
+    let mut report = Report::default();
+    match method {
+        "world.spawn_entity" | "world.insert_components" => {
+            registry.validate_components(params["components"].as_object()?, &mut report);
+        }
+        "world.insert_resources" => {
+            let resource = params["resource"].as_str()?;
+            registry.validate_placeable(resource, "Resource", &params["value"], &mut report);
+        }
+        "world.mutate_components" | "world.mutate_resources" => {
+            let (key, reflect) = if method == "world.mutate_components" {
+                ("component", "Component")
+            } else {
+                ("resource", "Resource")
+            };
+            let path = params[key].as_str()?;
+            if registry.get(path).is_some_and(|t| t.reflects(reflect)) {
+                let field_path = params["path"].as_str().unwrap_or("");
+                registry.validate_field(path, field_path, &params["value"], &mut report);
+            } else {
+                registry.validate_placeable(path, reflect, &Value::Null, &mut report);
+            }
+        }
+        _ => return None,
+    }
+    Some(report)
+}
+
+struct Cached {
+    /// When the connection the registry was read over came up; a restarted game is re-read.
+    online_since: Option<Instant>,
+    registry: Arc<Registry>,
+}
+
+// Registry of every game target, keyed by endpoint.
+static SCHEMA_CACHE: OnceLock<Mutex<HashMap<String, Cached>>> = OnceLock::new();
+
+fn get_cache() -> &'static Mutex<HashMap<String, Cached>> {
+    SCHEMA_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn online_since(endpoint: &str) -> Option<Instant> {
+    let status = connection::status_of(endpoint);
+    (status.state == LinkState::Online).then_some(status.since)
+}
+
+/// Registry of the current game, read once per connection or again with `refresh`.
+pub fn registry(refresh: bool) -> Result<Arc<Registry>> {
+    let endpoint = brp::endpoint();
+    let since = online_since(&endpoint);
+    if !refresh {
+        if let Some(cached) = get_cache().lock().ok().and_then(|cache| {
+            cache
+                .get(&endpoint)
+                .filter(|c| c.online_since == since)
+                .map(|c| c.registry.clone())
+        }) {
+            return Ok(cached);
+        }
+    }
+
+    let client = brp::client().with_timeout(brp::UPLOAD_TIMEOUT);
+    let schemas = brp::block_on(client.registry_schema(&SchemaFilter::default()))
+        .map_err(|e| anyhow!("Could not read the game's type registry: {}", e))?;
+    let registry = Arc::new(Registry::from_schemas(schemas));
+    if let Ok(mut cache) = get_cache().lock() {
+        cache.insert(
+            endpoint,
+            Cached {
+                online_since: since,
+                registry: registry.clone(),
+            },
+        );
+    }
+    Ok(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brp::mock::MockBrp;
+    use crate::tools::bevy::BevyRpcTool;
+    use crate::tools::schema::BevySchemaTool;
+    use crate::tools::Tool;
+
+    const TRANSFORM: &str = "bevy_transform::components::transform::Transform";
+    const LIGHT: &str = "my_game::Light";
+
+    fn field(type_path: &str) -> Value {
+        json!({ "type": { "$ref": format!("#/$defs/{}", type_path) }, "typePath": type_path })
+    }
+
+    fn vector(path: &str, short: &str, axes: &[&str]) -> Value {
+        let properties: Map<String, Value> =
+            axes.iter().map(|a| (a.to_string(), field("f32"))).collect();
+        json!({
+            "shortPath": short, "typePath": path, "kind": "Struct", "type": "object",
+            "reflectTypes": ["Default", "Deserialize", "Serialize"],
+            "properties": properties, "required": axes
+        })
+    }
+
+    /// Transform, glam vectors, an enum and a component with an optional field, as Bevy 0.18
+    /// reports them.
+    fn fixture() -> Value {
+        json!({
+            "f32": { "shortPath": "f32", "typePath": "f32", "kind": "Value", "type": "float" },
+            "glam::Vec3": vector("glam::Vec3", "Vec3", &["x", "y", "z"]),
+            "glam::Quat": vector("glam::Quat", "Quat", &["x", "y", "z", "w"]),
+            TRANSFORM: {
+                "shortPath": "Transform", "typePath": TRANSFORM, "kind": "Struct", "type": "object",
+                "reflectTypes": ["Component", "Default"],
+                "properties": {
+                    "translation": field("glam::Vec3"),
+                    "rotation": field("glam::Quat"),
+                    "scale": field("glam::Vec3")
+                },
+                "required": ["translation", "rotation", "scale"]
+            },
+            "my_game::Mode": {
+                "shortPath": "Mode", "typePath": "my_game::Mode", "kind": "Enum", "type": "string",
+                "reflectTypes": ["Default"],
+                "oneOf": ["Steady", "Flicker"]
+            },
+            "core::option::Option<f32>": {
+                "shortPath": "Option<f32>", "typePath": "core::option::Option<f32>",
+                "kind": "Enum", "type": "object",
+                "oneOf": [
+                    { "kind": "Unit", "shortPath": "None" },
+                    { "kind": "Tuple", "shortPath": "Some", "prefixItems": [field("f32")] }
+                ]
+            },
+            LIGHT: {
+                "shortPath": "Light", "typePath": LIGHT, "kind": "Struct", "type": "object",
+                "reflectTypes": ["Component"],
+                "properties": {
+                    "intensity": field("f32"),
+                    "mode": field("my_game::Mode"),
+                    "range": field("core::option::Option<f32>")
+                },
+                "required": ["intensity", "mode"]
+            }
+        })
+    }
+
+    fn registry() -> Registry {
+        Registry::from_schemas(serde_json::from_value(fixture()).unwrap())
+    }
+
+    #[test]
+    fn describes_fields_in_the_shape_they_are_sent() {
+        let registry = registry();
+        let light = registry.resolve("light").unwrap();
+        let text = registry.describe(light);
+        assert!(
+            text.starts_with("Light (my_game::Light) [Component]"),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains("mode: Mode = \"Steady\" | \"Flicker\""),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains("range: Option = f32 | null (optional)"),
+            "{}",
+            text
+        );
+
+        let transform = registry.resolve("Transform").unwrap();
+        assert!(registry
+            .describe(transform)
+            .contains("rotation: Quat = [x, y, z, w]"));
+        assert_eq!(
+            registry.example(TRANSFORM, 0),
+            json!({
+                "rotation": [0.0, 0.0, 0.0, 0.0],
+                "scale": [0.0, 0.0, 0.0],
+                "translation": [0.0, 0.0, 0.0]
+            })
+        );
+
+        let missing = registry.resolve("Transfrom").unwrap_err().to_string();
+        assert!(missing.contains("No type `Transfrom`"), "{}", missing);
+        let similar = registry.resolve("bevy::Transform").unwrap_err().to_string();
+        assert!(similar.contains(TRANSFORM), "{}", similar);
+    }
+
+    #[test]
+    fn reports_unknown_fields_wrong_types_and_short_paths() {
+        let registry = registry();
+        let params = json!({ "components": {
+            TRANSFORM: { "translation": { "x": 1.0, "y": 0.0, "z": 0.0 } },
+            LIGHT: { "intensity": "bright", "mode": "Strobe", "colour": 1 },
+            "Transform": {}
+        }});
+        let report = validate_call(&registry, "world.spawn_entity", &params).unwrap();
+        let errors = report.errors.join("\n");
+        assert!(errors.contains("expected [x, y, z] for Vec3"), "{}", errors);
+        assert!(errors.contains("expected float for f32"), "{}", errors);
+        assert!(errors.contains("no variant `Strobe`"), "{}", errors);
+        assert!(errors.contains("no field `colour`"), "{}", errors);
+        assert!(
+            errors.contains(&format!("registers it as `{}`", TRANSFORM)),
+            "{}",
+            errors
+        );
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("rotation, scale")));
+
+        let ok = json!({ "components": {
+            LIGHT: { "intensity": 2.0, "mode": "Flicker", "range": null }
+        }});
+        let report = validate_call(&registry, "world.insert_components", &ok).unwrap();
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+        let mutate = json!({ "component": TRANSFORM, "path": ".translation.w", "value": 1.0 });
+        let report = validate_call(&registry, "world.mutate_components", &mutate).unwrap();
+        assert!(
+            report.errors[0].contains("no field `w`"),
+            "{:?}",
+            report.errors
+        );
+        assert!(validate_call(&registry, "world.query", &json!({})).is_none());
+    }
+
+    #[test]
+    fn bad_payloads_are_not_sent_and_the_registry_is_read_once() {
+        let game = MockBrp::start();
+        game.with_registry(fixture());
+
+        let text = BevySchemaTool
+            .execute(json!({ "types": ["Light"], "search": "trans" }))
+            .unwrap();
+        assert!(text.contains("1 types match 'trans'"), "{}", text);
+        assert!(
+            text.contains("Example: {\"intensity\":0.0,\"mode\":\"Steady\"}"),
+            "{}",
+            text
+        );
+
+        let err = BevyRpcTool
+            .execute(json!({
+                "method": "world.spawn_entity",
+                "params": { "components": { LIGHT: { "intensity": 1.0, "mode": 3 } } }
+            }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("nothing was sent"), "{}", err);
+        assert!(!game.methods().iter().any(|m| m == "world.spawn_entity"));
+
+        BevyRpcTool
+            .execute(json!({
+                "method": "world.spawn_entity",
+                "params": { "components": { LIGHT: { "intensity": 1.0, "mode": "Steady" } } }
+            }))
+            .unwrap();
+        let schema_reads = game
+            .methods()
+            .iter()
+            .filter(|m| *m == "registry.schema")
+            .count();
+        assert_eq!(schema_reads, 1);
+    }
+}
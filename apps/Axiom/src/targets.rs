@@ -0,0 +1,210 @@
+//! Named game instances Axiom can talk to, e.g. several builds of one game on different ports,
+//! and the one the active chat channel is aimed at.
+//!
+//! Targets come from `AXIOM_BRP_TARGETS` (`name=url` pairs separated by commas), or are a single
+//! `default` target at [`brp::configured_endpoint`]. The `game_targets` tool adds more at runtime.
+use crate::brp;
+use anyhow::{anyhow, Result};
+use std::sync::{Mutex, OnceLock};
+
+/// Target name that runs a tool call on every connected instance.
+pub const BROADCAST: &str = "all";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameTarget {
+    pub name: String,
+    pub endpoint: String,
+}
+
+static GAME_TARGETS: OnceLock<Mutex<Vec<GameTarget>>> = OnceLock::new();
+// Name of the target the active channel uses, or `BROADCAST`. Empty means the first target.
+static SELECTED_TARGET: OnceLock<Mutex<String>> = OnceLock::new();
+
+fn get_targets() -> &'static Mutex<Vec<GameTarget>> {
+    GAME_TARGETS.get_or_init(|| {
+        let configured = std::env::var("AXIOM_BRP_TARGETS")
+            .ok()
+            .and_then(|spec| match parse_targets(&spec) {
+                Ok(targets) => Some(targets),
+                Err(e) => {
+                    eprintln!("Ignoring AXIOM_BRP_TARGETS: {}", e);
+                    None
+                }
+            })
+            .filter(|targets| !targets.is_empty());
+        Mutex::new(configured.unwrap_or_else(|| {
+            vec![GameTarget {
+                name: "default".to_string(),
+                endpoint: brp::configured_endpoint(),
+            }]
+        }))
+    })
+}
+
+fn get_selected() -> &'static Mutex<String> {
+    SELECTED_TARGET.get_or_init(|| Mutex::new(String::new()))
+}
+
+/// Full URL from `http://host:port`, `host:port` or a bare port on localhost.
+pub fn normalize_endpoint(endpoint: &str) -> String {
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    if endpoint.parse::<u16>().is_ok() {
+        format!("http://127.0.0.1:{}", endpoint)
+    } else if endpoint.contains("://") {
+        endpoint.to_string()
+    } else {
+        format!("http://{}", endpoint)
+    }
+}
+
+/// Parses `main=http://127.0.0.1:15721, beta=15722`. An entry without a name is named after
+/// its port.
+pub fn parse_targets(spec: &str) -> Result<Vec<GameTarget>> {
+    let mut targets: Vec<GameTarget> = Vec::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, endpoint) = match entry.split_once('=') {
+            Some((name, endpoint)) => (name.trim().to_string(), normalize_endpoint(endpoint)),
+            None => {
+                let endpoint = normalize_endpoint(entry);
+                let port = endpoint.rsplit(':').next().unwrap_or(&endpoint).to_string();
+                (port, endpoint)
+            }
+        };
+        check_name(&name)?;
+        if targets.iter().any(|t| t.name.eq_ignore_ascii_case(&name)) {
+            return Err(anyhow!("Target '{}' is listed twice", name));
+        }
+        targets.push(GameTarget { name, endpoint });
+    }
+    Ok(targets)
+}
+
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.chars().any(char::is_whitespace) {
+        return Err(anyhow!("Target names must be non-empty and without spaces"));
+    }
+    if name.eq_ignore_ascii_case(BROADCAST) {
+        return Err(anyhow!("'{}' is reserved for broadcasting", BROADCAST));
+    }
+    Ok(())
+}
+
+pub fn list() -> Vec<GameTarget> {
+    get_targets()
+        .lock()
+        .map(|targets| targets.clone())
+        .unwrap_or_default()
+}
+
+/// Target by name (case-insensitive) or by endpoint.
+pub fn find(query: &str) -> Option<GameTarget> {
+    let endpoint = normalize_endpoint(query);
+    list()
+        .into_iter()
+        .find(|t| t.name.eq_ignore_ascii_case(query.trim()) || t.endpoint == endpoint)
+}
+
+/// Adds a target, or moves an existing one to another endpoint.
+pub fn add(name: &str, endpoint: &str) -> Result<GameTarget> {
+    let name = name.trim();
+    check_name(name)?;
+    let target = GameTarget {
+        name: name.to_string(),
+        endpoint: normalize_endpoint(endpoint),
+    };
+    let mut targets = get_targets()
+        .lock()
+        .map_err(|_| anyhow!("Target list poisoned"))?;
+    match targets
+        .iter_mut()
+        .find(|t| t.name.eq_ignore_ascii_case(name))
+    {
+        Some(existing) => *existing = target.clone(),
+        None => targets.push(target.clone()),
+    }
+    drop(targets);
+    // Re-point the BRP calls if the selected target moved.
+    reselect();
+    Ok(target)
+}
+
+pub fn remove(name: &str) -> Result<GameTarget> {
+    let mut targets = get_targets()
+        .lock()
+        .map_err(|_| anyhow!("Target list poisoned"))?;
+    let index = targets
+        .iter()
+        .position(|t| t.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| anyhow!("No game target named '{}'", name))?;
+    if targets.len() == 1 {
+        return Err(anyhow!("Cannot remove the last game target"));
+    }
+    let removed = targets.remove(index);
+    drop(targets);
+    if let Ok(mut selected) = get_selected().lock() {
+        if selected.eq_ignore_ascii_case(&removed.name) {
+            selected.clear();
+        }
+    }
+    reselect();
+    Ok(removed)
+}
+
+/// Aims the BRP calls at `name`, `BROADCAST`, or the first target when empty.
+pub fn select(name: &str) -> Result<()> {
+    let name = name.trim();
+    let resolved = if name.is_empty() || name.eq_ignore_ascii_case(BROADCAST) {
+        name.to_lowercase()
+    } else {
+        find(name)
+            .ok_or_else(|| anyhow!("No game target named '{}'", name))?
+            .name
+    };
+    if let Ok(mut selected) = get_selected().lock() {
+        *selected = resolved;
+    }
+    reselect();
+    Ok(())
+}
+
+fn reselect() {
+    brp::set_endpoint(Some(selected_endpoint()));
+}
+
+/// Name of the selected target, or `BROADCAST`.
+pub fn selected() -> String {
+    let selected = get_selected().lock().map(|s| s.clone()).unwrap_or_default();
+    if selected == BROADCAST {
+        return selected;
+    }
+    find(&selected)
+        .or_else(|| list().into_iter().next())
+        .map(|t| t.name)
+        .unwrap_or_default()
+}
+
+/// Endpoint of the selected target. When broadcasting, the first target stands in for calls
+/// that are not broadcast, such as the scene map in the prompt.
+pub fn selected_endpoint() -> String {
+    let selected = selected();
+    find(&selected)
+        .or_else(|| list().into_iter().next())
+        .map(|t| t.endpoint)
+        .unwrap_or_else(brp::configured_endpoint)
+}
+
+/// The targets a tool call with `target` runs on: one, or all of them for `BROADCAST`.
+pub fn resolve(target: &str) -> Result<Vec<GameTarget>> {
+    if target.trim().eq_ignore_ascii_case(BROADCAST) {
+        return Ok(list());
+    }
+    find(target).map(|t| vec![t]).ok_or_else(|| {
+        let names: Vec<String> = list().into_iter().map(|t| t.name).collect();
+        anyhow!(
+            "No game target named '{}'. Known targets: {} (or '{}' for every one)",
+            target,
+            names.join(", "),
+            BROADCAST
+        )
+    })
+}
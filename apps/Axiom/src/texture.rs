@@ -0,0 +1,280 @@
+use anyhow::{anyhow, Result};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::imageops::FilterType as ResizeFilter;
+use image::{DynamicImage, GenericImageView, ImageEncoder};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+/// Longest texture side when `AXIOM_TEXTURE_MAX_SIZE` is not set.
+pub const DEFAULT_MAX_SIZE: u32 = 1024;
+pub const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureOptions {
+    /// Textures with a side longer than this are scaled down, keeping the aspect ratio.
+    pub max_size: u32,
+    pub jpeg_quality: u8,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            max_size: std::env::var("AXIOM_TEXTURE_MAX_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_SIZE),
+            jpeg_quality: DEFAULT_JPEG_QUALITY,
+        }
+    }
+}
+
+/// Result of [`optimize`] and [`optimize_file`].
+pub struct OptimizedTexture {
+    pub bytes: Vec<u8>,
+    pub original_bytes: usize,
+    pub original_size: (u32, u32),
+    pub size: (u32, u32),
+    /// Served from the on-disk cache instead of being processed again.
+    pub cached: bool,
+}
+
+/// Image formats the pipeline can re-encode. Anything else is uploaded untouched.
+pub fn is_optimizable(path: &Path) -> bool {
+    matches!(
+        extension(path).as_deref(),
+        Some("png") | Some("jpg") | Some("jpeg")
+    )
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+}
+
+/// 64-bit FNV-1a. Stable across builds, unlike `DefaultHasher`, so cache keys survive upgrades.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Width and height from the image header, without decoding the pixels.
+fn dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    image::ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+pub fn cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("axiom")
+        .join("textures")
+}
+
+/// Downscales and re-encodes an image in its own format (the glTF refers to it by file name, so
+/// a PNG stays a PNG). Returns the original bytes when re-encoding does not make it smaller.
+pub fn optimize(bytes: &[u8], ext: &str, options: TextureOptions) -> Result<OptimizedTexture> {
+    let img =
+        image::load_from_memory(bytes).map_err(|e| anyhow!("Failed to decode image: {}", e))?;
+    let original_size = img.dimensions();
+    let (w, h) = original_size;
+
+    let max_size = options.max_size.max(1);
+    let img = if w.max(h) > max_size {
+        let scale = max_size as f32 / w.max(h) as f32;
+        let (nw, nh) = (
+            ((w as f32 * scale).round() as u32).max(1),
+            ((h as f32 * scale).round() as u32).max(1),
+        );
+        img.resize_exact(nw, nh, ResizeFilter::Lanczos3)
+    } else {
+        img
+    };
+    let size = img.dimensions();
+
+    let mut out = Vec::new();
+    match ext {
+        "jpg" | "jpeg" => {
+            // JPEG has no alpha channel.
+            let rgb = DynamicImage::ImageRgb8(img.to_rgb8());
+            JpegEncoder::new_with_quality(&mut out, options.jpeg_quality).write_image(
+                rgb.as_bytes(),
+                size.0,
+                size.1,
+                rgb.color().into(),
+            )?;
+        }
+        "png" => {
+            PngEncoder::new_with_quality(&mut out, CompressionType::Best, FilterType::Adaptive)
+                .write_image(img.as_bytes(), size.0, size.1, img.color().into())?;
+        }
+        other => return Err(anyhow!("Unsupported texture format '{}'", other)),
+    }
+
+    let (bytes_out, size) = if size == original_size && out.len() >= bytes.len() {
+        (bytes.to_vec(), original_size)
+    } else {
+        (out, size)
+    };
+    Ok(OptimizedTexture {
+        bytes: bytes_out,
+        original_bytes: bytes.len(),
+        original_size,
+        size,
+        cached: false,
+    })
+}
+
+/// [`optimize_bytes`] on the file at `path`, cached in [`cache_dir`].
+pub fn optimize_file(path: &Path, options: TextureOptions) -> Result<OptimizedTexture> {
+    let bytes = std::fs::read(path).map_err(|e| anyhow!("Failed to read {:?}: {}", path, e))?;
+    optimize_bytes(path, &bytes, options, &cache_dir())
+}
+
+/// [`optimize`] on the already read content of `path`, with a disk cache in `cache` keyed by the
+/// source hash and the options, so the same texture is only processed once.
+pub fn optimize_bytes(
+    path: &Path,
+    bytes: &[u8],
+    options: TextureOptions,
+    cache: &Path,
+) -> Result<OptimizedTexture> {
+    let ext = extension(path).ok_or_else(|| anyhow!("{:?} has no file extension", path))?;
+
+    let key = format!(
+        "{:016x}-{}-q{}.{}",
+        fnv1a(bytes),
+        options.max_size,
+        options.jpeg_quality,
+        ext
+    );
+    let cached_path = cache.join(&key);
+    if let Ok(cached) = std::fs::read(&cached_path) {
+        if let Some(size) = dimensions(&cached) {
+            return Ok(OptimizedTexture {
+                original_bytes: bytes.len(),
+                original_size: dimensions(bytes).unwrap_or((0, 0)),
+                size,
+                bytes: cached,
+                cached: true,
+            });
+        }
+    }
+
+    let optimized = optimize(bytes, &ext, options)?;
+    // The cache is an optimisation only; a read-only cache dir must not break uploads.
+    if std::fs::create_dir_all(cache).is_ok() {
+        let _ = std::fs::write(&cached_path, &optimized.bytes);
+    }
+    Ok(optimized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, RgbaImage};
+
+    /// A `w`x`h` image with gradients and some noise, so encoders have real work to do.
+    fn picture(w: u32, h: u32, alpha: bool) -> RgbaImage {
+        RgbaImage::from_fn(w, h, |x, y| {
+            let noise = ((x * 7919 + y * 104_729) % 31) as u8;
+            let a = if alpha {
+                ((x * 255) / w.max(1)) as u8
+            } else {
+                255
+            };
+            image::Rgba([(x % 256) as u8, (y % 256) as u8, noise * 8, a])
+        })
+    }
+
+    fn encode(img: &RgbaImage, format: ImageFormat) -> Vec<u8> {
+        let mut out = Cursor::new(Vec::new());
+        match format {
+            ImageFormat::Jpeg => DynamicImage::ImageRgba8(img.clone())
+                .to_rgb8()
+                .write_to(&mut out, format)
+                .unwrap(),
+            _ => img.write_to(&mut out, format).unwrap(),
+        }
+        out.into_inner()
+    }
+
+    fn options(max_size: u32) -> TextureOptions {
+        TextureOptions {
+            max_size,
+            jpeg_quality: DEFAULT_JPEG_QUALITY,
+        }
+    }
+
+    #[test]
+    fn downscales_keeping_the_aspect_ratio() {
+        let png = encode(&picture(400, 200, false), ImageFormat::Png);
+        let optimized = optimize(&png, "png", options(100)).unwrap();
+        assert_eq!(optimized.original_size, (400, 200));
+        assert_eq!(optimized.size, (100, 50));
+        assert_eq!(dimensions(&optimized.bytes), Some((100, 50)));
+        assert!(optimized.bytes.len() < png.len());
+    }
+
+    #[test]
+    fn png_stays_png_with_alpha() {
+        let png = encode(&picture(64, 64, true), ImageFormat::Png);
+        let optimized = optimize(&png, "png", options(32)).unwrap();
+        assert_eq!(
+            image::guess_format(&optimized.bytes).unwrap(),
+            ImageFormat::Png
+        );
+        let img = image::load_from_memory(&optimized.bytes).unwrap();
+        assert!(img.color().has_alpha());
+        assert!(img.get_pixel(0, 10)[3] < 32);
+        assert!(img.get_pixel(31, 10)[3] > 200);
+    }
+
+    #[test]
+    fn keeps_the_original_when_re_encoding_grows_it() {
+        let rgb = DynamicImage::ImageRgba8(picture(64, 64, false)).to_rgb8();
+        let mut jpeg = Vec::new();
+        JpegEncoder::new_with_quality(&mut jpeg, 20)
+            .write_image(rgb.as_raw(), 64, 64, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        let high_quality = TextureOptions {
+            max_size: 1024,
+            jpeg_quality: 100,
+        };
+        let optimized = optimize(&jpeg, "jpg", high_quality).unwrap();
+        assert_eq!(optimized.bytes, jpeg);
+        assert_eq!(optimized.size, (64, 64));
+    }
+
+    #[test]
+    fn cache_hits_are_keyed_by_content_and_options() {
+        let cache = std::env::temp_dir().join(format!("axiom_texture_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&cache);
+        let path = Path::new("Textures/colormap.png");
+        let png = encode(&picture(128, 64, false), ImageFormat::Png);
+
+        let first = optimize_bytes(path, &png, options(64), &cache).unwrap();
+        let again = optimize_bytes(path, &png, options(64), &cache).unwrap();
+        assert!(!first.cached);
+        assert!(again.cached);
+        assert_eq!(again.bytes, first.bytes);
+        assert_eq!(again.size, (64, 32));
+        assert_eq!(again.original_size, (128, 64));
+
+        let other_options = optimize_bytes(path, &png, options(32), &cache).unwrap();
+        assert!(!other_options.cached);
+        let other_image = encode(&picture(128, 64, true), ImageFormat::Png);
+        assert!(
+            !optimize_bytes(path, &other_image, options(64), &cache)
+                .unwrap()
+                .cached
+        );
+        assert_eq!(std::fs::read_dir(&cache).unwrap().count(), 3);
+        let _ = std::fs::remove_dir_all(&cache);
+    }
+}
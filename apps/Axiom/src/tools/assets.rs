@@ -0,0 +1,95 @@
+use crate::catalog;
+use crate::tools::Tool;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+/// Tool to look up models in the asset catalog by what they are rather than by file name.
+pub struct SearchAssetsTool;
+
+impl Tool for SearchAssetsTool {
+    fn name(&self) -> String {
+        "search_assets".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Search the indexed model library by description, tags, size and grid footprint."
+            .to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "search_assets",
+                "description": "Search the local model library (apps/axiom/resources/models or AXIOM_ASSET_ROOTS) with free text, e.g. 'road end with barrier', 'street light', 'ramp'. Returns file names usable as bevy_upload_asset local_path, with tags, size and footprint in grid cells. An empty query lists everything.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "query": {
+                            "type": "string",
+                            "description": "What you are looking for."
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum number of results (default 10)."
+                        },
+                        "refresh": {
+                            "type": "boolean",
+                            "description": "Rescan the asset folders first, e.g. after new files were added (default false)."
+                        }
+                    },
+                    "required": []
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let query = args.get("query").and_then(|v| v.as_str()).unwrap_or("");
+        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+        let refresh = args
+            .get("refresh")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let entries = catalog::entries(refresh);
+        if entries.is_empty() {
+            return Err(anyhow!(
+                "No models found under {:?}. Set AXIOM_ASSET_ROOTS to the folders holding your .glb files.",
+                catalog::asset_roots()
+            ));
+        }
+
+        let results: Vec<&catalog::CatalogEntry> = if query.trim().is_empty() {
+            entries.iter().take(limit).collect()
+        } else {
+            catalog::search(&entries, query)
+                .into_iter()
+                .take(limit)
+                .map(|(_, entry)| entry)
+                .collect()
+        };
+        if results.is_empty() {
+            return Ok(format!(
+                "No match for '{}' among {} models. Try simpler words (e.g. 'end', 'barrier', 'light').",
+                query,
+                entries.len()
+            ));
+        }
+
+        let mut out = format!(
+            "{} of {} models (best match first):\n",
+            results.len(),
+            entries.len()
+        );
+        for entry in results {
+            out.push_str(&format!(
+                "- {}: {} [tags: {}]\n",
+                entry.name,
+                entry.description,
+                entry.tags.join(", ")
+            ));
+        }
+        Ok(out)
+    }
+}
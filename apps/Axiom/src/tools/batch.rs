@@ -7,12 +7,18 @@ use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
 
 pub struct BatchTool {
+    channel_id: String,
+    target: String,
     tx: Sender<AsyncMessage>,
 }
 
 impl BatchTool {
-    pub fn new(tx: Sender<AsyncMessage>) -> Self {
-        Self { tx }
+    pub fn new(channel_id: &str, target: &str, tx: Sender<AsyncMessage>) -> Self {
+        Self {
+            channel_id: channel_id.to_string(),
+            target: target.to_string(),
+            tx,
+        }
     }
 }
 
@@ -63,7 +69,11 @@ impl Tool for BatchTool {
         let tx = self.tx.clone();
 
         // Wrap tools in Arc for sharing across threads
-        let available_tools = Arc::new(crate::tools::get_all_tools(tx.clone()));
+        let available_tools = Arc::new(crate::tools::get_all_tools(
+            &self.channel_id,
+            &self.target,
+            tx.clone(),
+        ));
 
         // Use Arc<Mutex<Vec<_>>> to collect results thread-safely
         let results = Arc::new(Mutex::new(Vec::new()));
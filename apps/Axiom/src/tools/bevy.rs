@@ -1,13 +1,18 @@
+use crate::brp;
+use crate::connection;
+use crate::scene::{self, PlacedAsset};
+use crate::schema;
+use crate::texture::{self, TextureOptions};
 use crate::tools::Tool;
 use anyhow::{anyhow, Result};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use glam::{Quat, Vec3};
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::Read;
-use std::path::Path;
-
-const BEVY_RPC_URL: &str = "http://127.0.0.1:15721";
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 
 /// Tool to upload a local file to Bevy via BRP and spawn it
 pub struct BevyUploadAssetTool;
@@ -21,6 +26,10 @@ impl Tool for BevyUploadAssetTool {
         "Upload a local asset file (e.g., .glb) to Bevy and spawn it. Encodes file as Base64 and sends via 'AxiomRemoteAsset'.".to_string()
     }
 
+    fn requires(&self) -> &'static [&'static str] {
+        &["upload"]
+    }
+
     fn schema(&self) -> Value {
         json!({
             "type": "function",
@@ -36,7 +45,7 @@ impl Tool for BevyUploadAssetTool {
                         },
                         "relative_path": {
                             "type": "string",
-                            "description": "Optional relative subdirectory in the game's asset cache (e.g. 'Textures')."
+                            "description": "Optional relative subdirectory in the game's asset cache (e.g. 'Textures'). Buffers and textures a .gltf/.glb references are uploaded automatically next to it."
                         },
                         "translation": {
                             "type": "array",
@@ -51,6 +60,14 @@ impl Tool for BevyUploadAssetTool {
                             "minItems": 3,
                             "maxItems": 3,
                             "description": "[x, y, z] rotation in Euler angles (Degrees). e.g. [0, 90, 0] to rotate 90 deg around Y axis."
+                        },
+                        "label": {
+                            "type": "string",
+                            "description": "Optional name to select this entity later with the editing tools."
+                        },
+                        "group": {
+                            "type": "string",
+                            "description": "Optional group to select it together with others later, e.g. 'street-lights'."
                         }
                     },
                     "required": ["local_path", "translation"]
@@ -75,129 +92,360 @@ impl Tool for BevyUploadAssetTool {
             .and_then(|v| v.as_array())
             .ok_or(anyhow!("Missing translation"))?;
 
-        let tx = t.get(0).and_then(|v| v.as_f64()).unwrap_or(0.0);
-        let ty = t.get(1).and_then(|v| v.as_f64()).unwrap_or(0.0);
-        let tz = t.get(2).and_then(|v| v.as_f64()).unwrap_or(0.0);
+        let tx = t.get(0).and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let ty = t.get(1).and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let tz = t.get(2).and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
 
         // Handle Rotation
-        let rotation_quat = if let Some(rot_arr) = args.get("rotation").and_then(|v| v.as_array()) {
+        let rotation_deg = if let Some(rot_arr) = args.get("rotation").and_then(|v| v.as_array()) {
             let rx = rot_arr.get(0).and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
             let ry = rot_arr.get(1).and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
             let rz = rot_arr.get(2).and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-
-            // Convert Degrees to Radians and create Quat
-            Quat::from_euler(
-                glam::EulerRot::XYZ,
-                rx.to_radians(),
-                ry.to_radians(),
-                rz.to_radians(),
-            )
+            [rx, ry, rz]
         } else {
-            Quat::IDENTITY
+            [0.0, 0.0, 0.0]
         };
 
-        // 1. Read file
-        let path = Path::new(local_path);
-
-        // Smart Path Resolution Strategy
-        // 1. Try absolute path or raw path provided by user
-        let mut abs_path = if path.is_absolute() {
-            path.to_path_buf()
-        } else {
-            std::env::current_dir()?.join(path)
+        let spawned = upload_asset(local_path, relative_path, [tx, ty, tz], rotation_deg)?;
+        let mut out = format!(
+            "Uploaded and Spawned {}. Entity: {}.",
+            spawned.filename, spawned.entity
+        );
+        let text = |key: &str| {
+            args.get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
         };
+        let (label, group) = (text("label"), text("group"));
+        if label.is_some() || group.is_some() {
+            scene::update(spawned.entity, |asset| {
+                asset.label = label;
+                asset.group = group;
+            });
+        }
+        if !spawned.dependencies.is_empty() {
+            out.push_str(&format!(
+                " (with dependencies: {})",
+                spawned.dependencies.join(", ")
+            ));
+        }
+        Ok(out)
+    }
+}
 
-        // 2. If not found, try fallback: apps/axiom/resources/models/{filename}
-        if !abs_path.exists() {
-            if let Some(name) = path.file_name() {
-                let fallback_models = std::env::current_dir()?
-                    .join("apps")
-                    .join("axiom")
-                    .join("resources")
-                    .join("models")
-                    .join(name);
-
-                if fallback_models.exists() {
-                    println!(
-                        "[BevyTool] Path not found, falling back to: {:?}",
-                        fallback_models
-                    );
-                    abs_path = fallback_models;
-                } else {
-                    // 3. If not found, try fallback: apps/axiom/resources/{filename}
-                    let fallback_resources = std::env::current_dir()?
-                        .join("apps")
-                        .join("axiom")
-                        .join("resources")
-                        .join(name);
-
-                    if fallback_resources.exists() {
-                        println!(
-                            "[BevyTool] Path not found, falling back to: {:?}",
-                            fallback_resources
-                        );
-                        abs_path = fallback_resources;
-                    }
-                }
+/// Smart path resolution shared by the asset tools: the path as given (absolute or relative to
+/// the working directory), then `apps/axiom/resources/models/{filename}`, then
+/// `apps/axiom/resources/{filename}`. Falls back to the first candidate when none exists.
+pub fn resolve_asset_path(local_path: &str) -> Result<PathBuf> {
+    let path = Path::new(local_path);
+
+    // 1. Try absolute path or raw path provided by user
+    let abs_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    if abs_path.exists() {
+        return Ok(abs_path);
+    }
+
+    // 2. If not found, try fallbacks: apps/axiom/resources/models/{filename}, then apps/axiom/resources/{filename}
+    if let Some(name) = path.file_name() {
+        let resources = std::env::current_dir()?
+            .join("apps")
+            .join("axiom")
+            .join("resources");
+        for fallback in [resources.join("models").join(name), resources.join(name)] {
+            if fallback.exists() {
+                println!("[BevyTool] Path not found, falling back to: {:?}", fallback);
+                return Ok(fallback);
             }
         }
+    }
+    Ok(abs_path)
+}
 
-        let filename = path
-            .file_name()
-            .ok_or(anyhow!("Invalid filename"))?
-            .to_string_lossy()
-            .to_string();
+/// An asset spawned through `upload_asset`.
+pub struct SpawnedAsset {
+    pub filename: String,
+    pub entity: u64,
+    /// External files of the model (buffers, textures), relative to the model's folder.
+    pub dependencies: Vec<String>,
+}
 
-        let mut file = File::open(&abs_path)
-            .map_err(|e| anyhow!("Failed to open file at {:?}: {}", abs_path, e))?;
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
+/// Game endpoint, local file, target subdir and a hash of the file's contents.
+type DependencyKey = (String, PathBuf, String, u64);
 
-        // 2. Encode to Base64
-        let b64_data = BASE64.encode(&buffer);
+// Dependencies already sent this session. Road tiles share one texture, so it only needs to go
+// over the wire once; a file edited on disk hashes differently and is sent again.
+static UPLOADED_DEPENDENCIES: OnceLock<Mutex<HashSet<DependencyKey>>> = OnceLock::new();
 
-        println!(
-            "[BevyTool] Uploading {} ({} bytes) ...",
-            filename,
-            buffer.len()
+fn get_uploaded_dependencies() -> &'static Mutex<HashSet<DependencyKey>> {
+    UPLOADED_DEPENDENCIES.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Forgets what was uploaded to `endpoint`, e.g. once that game restarted and its asset
+/// cache may no longer hold the files.
+pub fn forget_uploads(endpoint: &str) {
+    if let Ok(mut uploaded) = get_uploaded_dependencies().lock() {
+        uploaded.retain(|key| key.0 != endpoint);
+    }
+}
+
+/// Relative file URIs (buffers and images) referenced by a `.gltf` / `.glb`. Data URIs are
+/// embedded and skipped; paths escaping the model's folder are rejected.
+pub fn gltf_dependencies(model_path: &Path) -> Result<Vec<String>> {
+    let doc = crate::glb::load(model_path)?;
+    let mut uris = Vec::new();
+    for key in ["buffers", "images"] {
+        let items = doc.json.get(key).and_then(|v| v.as_array());
+        for item in items.into_iter().flatten() {
+            let Some(uri) = item.get("uri").and_then(|u| u.as_str()) else {
+                continue;
+            };
+            if uri.starts_with("data:") {
+                continue;
+            }
+            let uri = crate::glb::percent_decode(uri).replace('\\', "/");
+            let uri = uri.trim_start_matches("./").to_string();
+            let relative = Path::new(&uri);
+            if relative.is_absolute()
+                || relative
+                    .components()
+                    .any(|c| !matches!(c, std::path::Component::Normal(_)))
+            {
+                return Err(anyhow!(
+                    "{} references '{}', which is outside the model's folder",
+                    model_path.display(),
+                    uri
+                ));
+            }
+            if !uris.contains(&uri) {
+                uris.push(uri);
+            }
+        }
+    }
+    Ok(uris)
+}
+
+/// Uploads every external file of a model into the matching subdirectory of the game's cache,
+/// next to where the model itself goes (`subdir`). Returns the dependency list for the plugin.
+fn upload_dependencies(model_path: &Path, subdir: Option<&str>) -> Result<Vec<String>> {
+    let dependencies = gltf_dependencies(model_path)?;
+    let model_dir = model_path.parent().unwrap_or(Path::new(""));
+
+    for dep in &dependencies {
+        let local = model_dir.join(dep);
+        let (dep_dir, dep_name) = match dep.rsplit_once('/') {
+            Some((dir, name)) => (Some(dir), name),
+            None => (None, dep.as_str()),
+        };
+        let target_dir = [subdir.filter(|s| !s.is_empty()), dep_dir]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let data = std::fs::read(&local).map_err(|e| {
+            anyhow!(
+                "{} needs '{}' but it could not be read at {:?}: {}",
+                model_path.display(),
+                dep,
+                local,
+                e
+            )
+        })?;
+        let key = (
+            brp::endpoint(),
+            local.clone(),
+            target_dir.clone(),
+            texture::fnv1a(&data),
         );
+        if get_uploaded_dependencies()
+            .lock()
+            .map(|uploaded| uploaded.contains(&key))
+            .unwrap_or(false)
+        {
+            continue;
+        }
 
-        // 3. Send Payload
-        let payload = json!({
-            "jsonrpc": "2.0",
-            "method": "world.spawn_entity",
-            "id": 1,
-            "params": {
-                "components": {
-                    "bevy_ai_remote::AxiomRemoteAsset": {
-                        "filename": filename,
-                        "data_base64": b64_data,
-                        "subdir": relative_path
-                    },
-                    "bevy_transform::components::transform::Transform": {
-                        "translation": [tx, ty, tz],
-                        "rotation": [rotation_quat.x, rotation_quat.y, rotation_quat.z, rotation_quat.w],
-                        "scale": [1.0, 1.0, 1.0]
-                    }
+        // Textures go through the optimisation pipeline; on failure the original is sent.
+        let data = if texture::is_optimizable(&local) {
+            match texture::optimize_bytes(
+                &local,
+                &data,
+                TextureOptions::default(),
+                &texture::cache_dir(),
+            ) {
+                Ok(optimized) => optimized.bytes,
+                Err(e) => {
+                    println!("[BevyTool] Could not optimise {}: {}", dep, e);
+                    data
                 }
             }
-        });
-
-        // Use a longer timeout for large files
-        let agent = ureq::AgentBuilder::new()
-            .timeout_read(std::time::Duration::from_secs(10))
-            .timeout_write(std::time::Duration::from_secs(10))
-            .build();
-
-        match agent.post(BEVY_RPC_URL).send_json(payload) {
-            Ok(res) => Ok(format!(
-                "Uploaded and Spawned {}. Response: {}",
-                filename,
-                res.status()
-            )),
-            Err(e) => Err(anyhow!("Failed to upload asset: {}", e)),
+        } else {
+            data
+        };
+        println!(
+            "[BevyTool] Uploading dependency {} ({} bytes) ...",
+            dep,
+            data.len()
+        );
+        let components = Map::from_iter([remote_asset(json!({
+            "filename": dep_name,
+            "data_base64": BASE64.encode(&data),
+            "subdir": (!target_dir.is_empty()).then_some(target_dir),
+            "dependencies": []
+        }))]);
+        let client = brp::client().with_timeout(brp::UPLOAD_TIMEOUT);
+        brp::block_on(client.spawn_entity(components))
+            .map_err(|e| anyhow!("Failed to upload dependency {}: {}", dep, e))?;
+
+        if let Ok(mut uploaded) = get_uploaded_dependencies().lock() {
+            uploaded.insert(key);
+        }
+    }
+    Ok(dependencies)
+}
+
+/// `AxiomRemoteAsset` component for the current game: under the type path its plugin reports,
+/// and without `dependencies` for plugins that do not know the field.
+fn remote_asset(mut value: Value) -> (String, Value) {
+    let endpoint = brp::endpoint();
+    if !connection::supports(&endpoint, "upload_dependencies") {
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("dependencies");
         }
     }
+    (connection::type_path(&endpoint, brp::REMOTE_ASSET), value)
+}
+
+/// Reads `local_path` (with the models/resources fallbacks), uploads it as an `AxiomRemoteAsset`
+/// and spawns it with the given transform. Models are recorded in the scene mirror.
+pub fn upload_asset(
+    local_path: &str,
+    relative_path: Option<String>,
+    translation: [f32; 3],
+    rotation_deg: [f32; 3],
+) -> Result<SpawnedAsset> {
+    // Convert Degrees to Radians and create Quat
+    let rotation_quat = Quat::from_euler(
+        glam::EulerRot::XYZ,
+        rotation_deg[0].to_radians(),
+        rotation_deg[1].to_radians(),
+        rotation_deg[2].to_radians(),
+    );
+
+    // 1. Read file
+    let path = Path::new(local_path);
+    let abs_path = resolve_asset_path(local_path)?;
+
+    let filename = path
+        .file_name()
+        .ok_or(anyhow!("Invalid filename"))?
+        .to_string_lossy()
+        .to_string();
+
+    let mut file = File::open(&abs_path)
+        .map_err(|e| anyhow!("Failed to open file at {:?}: {}", abs_path, e))?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+
+    connection::require(&brp::endpoint(), "upload")?;
+
+    // 2. Models: send the buffers/textures they reference first, the game waits for them
+    let is_model = filename.ends_with(".glb") || filename.ends_with(".gltf");
+    let dependencies = if is_model {
+        upload_dependencies(&abs_path, relative_path.as_deref())?
+    } else {
+        Vec::new()
+    };
+
+    // 3. Encode to Base64
+    let b64_data = BASE64.encode(&buffer);
+
+    println!(
+        "[BevyTool] Uploading {} ({} bytes) ...",
+        filename,
+        buffer.len()
+    );
+
+    // 4. Spawn it, with a longer timeout for large files
+    let components = Map::from_iter([
+        remote_asset(json!({
+            "filename": filename,
+            "data_base64": b64_data,
+            "subdir": relative_path,
+            "dependencies": dependencies
+        })),
+        (
+            brp::TRANSFORM.to_string(),
+            brp::transform(translation, rotation_quat.to_array(), [1.0; 3]),
+        ),
+    ]);
+    let client = brp::client().with_timeout(brp::UPLOAD_TIMEOUT);
+    let entity = brp::block_on(client.spawn_entity(components))
+        .map_err(|e| anyhow!("Failed to upload asset: {}", e))?;
+
+    // Only models become visible entities; textures/bins are just cached by the game.
+    if is_model {
+        scene::record(PlacedAsset::new(
+            Some(entity),
+            filename.clone(),
+            abs_path.clone(),
+            translation,
+            rotation_deg,
+        ));
+    }
+
+    Ok(SpawnedAsset {
+        filename,
+        entity,
+        dependencies,
+    })
+}
+
+/// Despawns one entity (and its scene children) and drops it from the scene mirror. An entity
+/// the game no longer has counts as despawned.
+pub fn despawn_entity(entity: u64) -> Result<()> {
+    match brp::block_on(brp::client().despawn_entity(entity)) {
+        // Already gone in the game, e.g. despawned through bevy_rpc.
+        Err(e) if e.code() != Some(brp::error_codes::ENTITY_NOT_FOUND) => {
+            return Err(anyhow!("Failed to despawn entity {}: {}", entity, e));
+        }
+        _ => {}
+    }
+    scene::forget(entity);
+    Ok(())
+}
+
+/// Replaces the `Transform` of a spawned entity and mirrors the change in the scene record.
+pub fn set_transform(
+    entity: u64,
+    translation: [f32; 3],
+    rotation_deg: [f32; 3],
+    scale: [f32; 3],
+) -> Result<()> {
+    let rotation = Quat::from_euler(
+        glam::EulerRot::XYZ,
+        rotation_deg[0].to_radians(),
+        rotation_deg[1].to_radians(),
+        rotation_deg[2].to_radians(),
+    );
+    let components = Map::from_iter([(
+        brp::TRANSFORM.to_string(),
+        brp::transform(translation, rotation.to_array(), scale),
+    )]);
+    brp::block_on(brp::client().insert_components(entity, components))
+        .map_err(|e| anyhow!("Failed to move entity {}: {}", entity, e))?;
+
+    scene::update(entity, |asset| {
+        asset.translation = translation;
+        asset.rotation = rotation_deg;
+        asset.scale = scale;
+    });
+    Ok(())
 }
 
 /// Generic JSON-RPC Tool for Bevy Remote
@@ -209,7 +457,7 @@ impl Tool for BevyRpcTool {
     }
 
     fn description(&self) -> String {
-        "Send a raw JSON-RPC request to the running Bevy engine (bevy_remote). Methods: bevy/spawn, bevy/get, bevy/list, etc.".to_string()
+        "Send a raw JSON-RPC request to the running Bevy engine (bevy_remote). Methods: world.spawn_entity, world.query, world.get_components, world.insert_components, world.remove_components, world.despawn_entity, world.list_components, registry.schema, etc.".to_string()
     }
 
     fn schema(&self) -> Value {
@@ -217,17 +465,21 @@ impl Tool for BevyRpcTool {
             "type": "function",
             "function": {
                 "name": "bevy_rpc",
-                "description": "Send a raw JSON-RPC request to Bevy.",
+                "description": "Send a raw JSON-RPC request to Bevy. Component and resource payloads are checked against the game's type registry before sending; look up type paths and fields with bevy_schema.",
                 "parameters": {
                     "type": "object",
                     "properties": {
                         "method": {
                             "type": "string",
-                            "description": "The RPC method (e.g., 'bevy/spawn', 'bevy/query', 'bevy/list')."
+                            "description": "The RPC method, e.g. 'world.spawn_entity', 'world.query', 'world.get_components', 'world.insert_components', 'world.remove_components', 'world.despawn_entity', 'world.list_components' or 'registry.schema'. Bevy 0.18 rejects the old 'bevy/*' names."
                         },
                         "params": {
                             "type": "object",
                             "description": "The parameters for the RPC method."
+                        },
+                        "skip_validation": {
+                            "type": "boolean",
+                            "description": "Send without checking the payload against the registry."
                         }
                     },
                     "required": ["method", "params"]
@@ -244,50 +496,51 @@ impl Tool for BevyRpcTool {
 
         let params = args.get("params").unwrap_or(&json!({})).clone();
 
-        let payload = if method == "world.query" {
-            // Bevy 0.18 BRP world.query expects: { "data": { "components": [...] }, "filter": ... }
-            // If the user provided { "components": [...] } directly in params, we need to wrap it.
-            if params.get("data").is_none() && params.get("components").is_some() {
-                json!({
-                    "jsonrpc": "2.0",
-                    "method": method,
-                    "id": 1,
-                    "params": {
-                        "data": params
-                    }
-                })
-            } else {
-                json!({
-                    "jsonrpc": "2.0",
-                    "method": method,
-                    "id": 1,
-                    "params": params
-                })
-            }
+        // Bevy 0.18 BRP world.query expects: { "data": { "components": [...] }, "filter": ... }
+        // If the user provided { "components": [...] } directly in params, we need to wrap it.
+        let params = if method == "world.query"
+            && params.get("data").is_none()
+            && params.get("components").is_some()
+        {
+            json!({ "data": params })
         } else {
-            json!({
-                "jsonrpc": "2.0",
-                "method": method,
-                "id": 1,
-                "params": params
-            })
+            params
         };
 
-        match ureq::post(BEVY_RPC_URL).send_json(payload) {
-            Ok(res) => {
-                let body: Value = res.into_json()?;
-                if let Some(error) = body.get("error") {
-                    Err(anyhow!("Bevy RPC Error: {}", error))
-                } else if let Some(result) = body.get("result") {
-                    Ok(serde_json::to_string_pretty(result)?)
-                } else {
-                    Ok("Success (No result)".to_string())
-                }
+        // A game whose registry cannot be read is sent the payload unchecked.
+        let skip = args
+            .get("skip_validation")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let report = if skip || !schema::CHECKED_METHODS.contains(&method) {
+            None
+        } else {
+            schema::registry(false)
+                .ok()
+                .and_then(|registry| schema::validate_call(&registry, method, &params))
+        };
+        if let Some(report) = &report {
+            if !report.errors.is_empty() {
+                return Err(anyhow!(
+                    "Payload does not match the game's type registry, nothing was sent:\n- {}\nLook up paths and fields with bevy_schema.",
+                    report.errors.join("\n- ")
+                ));
             }
-            Err(e) => Err(anyhow!(
-                "Failed to connect to Bevy (is bevy_remote feature enabled and app running?): {}",
+        }
+        let hints = report
+            .filter(|r| !r.warnings.is_empty())
+            .map(|r| format!(" Possible cause: {}.", r.warnings.join("; ")))
+            .unwrap_or_default();
+
+        match brp::block_on(brp::client().call::<Value>(method, Some(params))) {
+            Ok(Value::Null) => Ok("Success (No result)".to_string()),
+            Ok(result) => Ok(serde_json::to_string_pretty(&result)?),
+            Err(e) if e.code() == Some(brp::error_codes::METHOD_NOT_FOUND) => Err(anyhow!(
+                "Bevy RPC Error: {}. Bevy 0.18 uses 'world.*' and 'registry.*' method names (e.g. world.query, world.despawn_entity), not 'bevy/*'.",
                 e
             )),
+            Err(e @ brp::BrpError::Rpc { .. }) => Err(anyhow!("Bevy RPC Error: {}{}", e, hints)),
+            Err(e) => Err(anyhow!("{}", e)),
         }
     }
 }
@@ -372,34 +625,25 @@ impl Tool for BevySpawnSceneTool {
         // For now, we spawn an empty entity with a Transform to verify the control link works.
         // The user will see a "Ghost" entity in the scene hierarchy (if they had an inspector), but nothing visible.
         // This confirms command parsing -> network -> bevy execution is 100% working.
-        let payload = json!({
-            "jsonrpc": "2.0",
-            "method": "world.spawn_entity",
-            "id": 1,
-            "params": {
-                "components": {
-                    // Temporarily disabled SceneRoot until we figure out the correct JSON format for Handle<Scene>
-                    /*
-                    "bevy_scene::components::SceneRoot": {
-                        "Handle<bevy_scene::scene::Scene>": {
-                            "path": asset_path
-                        }
-                    },
-                    */
-                    "bevy_transform::components::transform::Transform": {
-                        "translation": [tx, ty, tz],
-                        "rotation": [0.0, 0.0, 0.0, 1.0],
-                        "scale": [1.0, 1.0, 1.0]
-                    }
-                }
-            }
-        });
-
-        match ureq::post(BEVY_RPC_URL).send_json(payload) {
-            Ok(res) => {
-                let body: Value = res.into_json()?;
-                Ok(serde_json::to_string_pretty(&body)?)
+        // Temporarily disabled SceneRoot until we figure out the correct JSON format for Handle<Scene>
+        /*
+        "bevy_scene::components::SceneRoot": {
+            "Handle<bevy_scene::scene::Scene>": {
+                "path": asset_path
             }
+        },
+        */
+        let components = Map::from_iter([(
+            brp::TRANSFORM.to_string(),
+            json!({
+                "translation": [tx, ty, tz],
+                "rotation": [0.0, 0.0, 0.0, 1.0],
+                "scale": [1.0, 1.0, 1.0]
+            }),
+        )]);
+
+        match brp::block_on(brp::client().spawn_entity(components)) {
+            Ok(entity) => Ok(format!("Spawned entity {}", entity)),
             Err(e) => Err(anyhow!("Failed to spawn scene via bevy_remote: {}", e)),
         }
     }
@@ -433,53 +677,145 @@ impl Tool for BevyClearSceneTool {
     }
 
     fn execute(&self, _args: Value) -> Result<String> {
-        // 1. List all entities
-        let list_payload = json!({
-            "jsonrpc": "2.0",
-            "method": "bevy/list",
-            "id": 1,
-            "params": {}
-        });
-
-        let resp = ureq::post(BEVY_RPC_URL).send_json(list_payload)?;
-        let body: Value = resp.into_json()?;
+        // Only entities with a SceneRoot: our GLB models, sparing Camera, Lights and system entities.
+        let query = brp::QueryParams {
+            filter: brp::QueryFilter {
+                with: vec![brp::SCENE_ROOT.to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let rows = brp::block_on(brp::client().query(&query))
+            .map_err(|e| anyhow!("Failed to list entities: {}", e))?;
 
         let mut count = 0;
-        if let Some(result) = body.get("result").and_then(|r| r.as_array()) {
-            for entity_info in result {
-                if let Some(entity_id) = entity_info.get("entity").and_then(|e| e.as_u64()) {
-                    let components = entity_info.get("components").and_then(|c| c.as_array());
-
-                    // Filter: Only despawn entities that have "SceneRoot" component
-                    // This targets our GLB models while sparing Camera, Lights, and System entities.
-                    let should_despawn = components
-                        .map(|c| {
-                            c.iter().any(|v| {
-                                v.as_str().map(|s| s.contains("SceneRoot")).unwrap_or(false)
-                            })
-                        })
-                        .unwrap_or(false);
-
-                    if should_despawn {
-                        println!("[BevyTool] Despawning entity: {}", entity_id);
-
-                        let despawn_payload = json!({
-                            "jsonrpc": "2.0",
-                            "method": "bevy/despawn",
-                            "id": 1,
-                            "params": {
-                                "entity": entity_id
-                            }
-                        });
-
-                        let _ = ureq::post(BEVY_RPC_URL).send_json(despawn_payload);
-                        count += 1;
-                    }
+        let mut failures = Vec::new();
+        for row in rows {
+            println!("[BevyTool] Despawning entity: {}", row.entity);
+            match brp::block_on(brp::client().despawn_entity(row.entity)) {
+                Ok(()) => count += 1,
+                Err(e) => failures.push(format!("{}: {}", row.entity, e)),
+            }
+        }
+
+        scene::clear();
+
+        if failures.is_empty() {
+            Ok(format!("Cleared {} entities.", count))
+        } else {
+            Ok(format!(
+                "Cleared {} entities, {} failed:\n{}",
+                count,
+                failures.len(),
+                failures.join("\n")
+            ))
+        }
+    }
+}
+
+/// Tool to list, read and remove the components of one entity.
+pub struct BevyComponentsTool;
+
+impl Tool for BevyComponentsTool {
+    fn name(&self) -> String {
+        "bevy_components".to_string()
+    }
+
+    fn description(&self) -> String {
+        "List, read or remove the components of an entity in the running game.".to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "bevy_components",
+                "description": "Inspect or strip an entity's components. With only 'entity' it lists every component with its value; 'get' reads just those, 'remove' drops them. Without 'entity' it lists the components the game can serialise. Components are full type paths, e.g. 'bevy_transform::components::transform::Transform'; look them up with bevy_schema.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "entity": {
+                            "type": "integer",
+                            "description": "Entity id, e.g. from query_scene."
+                        },
+                        "get": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Components to read."
+                        },
+                        "remove": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Components to remove from the entity."
+                        }
+                    },
+                    "required": []
                 }
             }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let names = |key: &str| -> Vec<String> {
+            args.get(key)
+                .and_then(|v| v.as_array())
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|n| n.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let (get, remove) = (names("get"), names("remove"));
+        let client = brp::client();
+
+        let Some(entity) = args.get("entity").and_then(|v| v.as_u64()) else {
+            if !get.is_empty() || !remove.is_empty() {
+                return Err(anyhow!("'get' and 'remove' need an 'entity'"));
+            }
+            let mut registered = brp::block_on(client.list_components(None))
+                .map_err(|e| anyhow!("Failed to list components: {}", e))?;
+            registered.sort();
+            return Ok(format!(
+                "{} registered components:\n{}",
+                registered.len(),
+                registered.join("\n")
+            ));
+        };
+
+        if !remove.is_empty() {
+            let remove: Vec<&str> = remove.iter().map(String::as_str).collect();
+            brp::block_on(client.remove_components(entity, &remove))
+                .map_err(|e| anyhow!("Failed to remove components from {}: {}", entity, e))?;
+            if get.is_empty() {
+                return Ok(format!(
+                    "Removed {} from entity {}.",
+                    remove.join(", "),
+                    entity
+                ));
+            }
         }
 
-        Ok(format!("Cleared {} entities.", count))
+        let mut wanted = get;
+        if wanted.is_empty() {
+            wanted = brp::block_on(client.list_components(Some(entity)))
+                .map_err(|e| anyhow!("Failed to list the components of {}: {}", entity, e))?;
+            wanted.sort();
+        }
+        let wanted: Vec<&str> = wanted.iter().map(String::as_str).collect();
+        let answer = brp::block_on(client.get_components(entity, &wanted))
+            .map_err(|e| anyhow!("Failed to read the components of {}: {}", entity, e))?;
+
+        let mut out = format!("Entity {}:\n", entity);
+        for name in &wanted {
+            if let Some(value) = answer.components.get(*name) {
+                out.push_str(&format!("- {} = {}\n", name, value));
+            } else if let Some(error) = answer.errors.get(*name) {
+                out.push_str(&format!("- {}: {}\n", name, error));
+            }
+        }
+        Ok(out)
     }
 }
 
@@ -495,6 +831,10 @@ impl Tool for BevySpawnPrimitiveTool {
         "Spawn a primitive 3D object (currently just a cube) at a specific location via Bevy Remote using a pre-existing glTF asset 'cube.glb'.".to_string()
     }
 
+    fn requires(&self) -> &'static [&'static str] {
+        &["primitives"]
+    }
+
     fn schema(&self) -> Value {
         json!({
             "type": "function",
@@ -538,53 +878,246 @@ impl Tool for BevySpawnPrimitiveTool {
 
         // Use the custom AxiomPrimitive component we added to bevy_ai_remote
         // This triggers the spawn_primitives system on the game side to attach Mesh and Material.
-        let payload = json!({
-            "jsonrpc": "2.0",
-            "method": "world.spawn_entity",
-            "id": 1,
-            "params": {
-                "components": {
-                    "bevy_ai_remote::AxiomPrimitive": {
-                        "primitive_type": "cube"
-                    },
-                    "bevy_transform::components::transform::Transform": {
-                        "translation": [tx, ty, tz],
-                        "rotation": [0.0, 0.0, 0.0, 1.0],
-                        "scale": [1.0, 1.0, 1.0]
-                    }
-                }
+        let components = Map::from_iter([
+            (
+                connection::type_path(&brp::endpoint(), brp::PRIMITIVE),
+                json!({ "primitive_type": "cube" }),
+            ),
+            (
+                brp::TRANSFORM.to_string(),
+                json!({
+                    "translation": [tx, ty, tz],
+                    "rotation": [0.0, 0.0, 0.0, 1.0],
+                    "scale": [1.0, 1.0, 1.0]
+                }),
+            ),
+        ]);
+
+        // Short timeout to prevent hanging
+        let client = brp::client().with_timeout(std::time::Duration::from_secs(2));
+        println!("[BevyTool] Spawning cube via {}", client.endpoint());
+
+        match brp::block_on(client.spawn_entity(components)) {
+            Ok(entity) => Ok(format!("Spawned Cube (Scene). Entity: {}", entity)),
+            Err(e) => {
+                println!("[BevyTool] ERROR: {}", e);
+                Err(anyhow!("Failed to spawn primitive via bevy_remote: {}", e))
             }
-        });
+        }
+    }
+}
 
-        // Create an agent with a timeout to prevent hanging
-        let agent = ureq::AgentBuilder::new()
-            .timeout_read(std::time::Duration::from_secs(2))
-            .timeout_write(std::time::Duration::from_secs(2))
-            .build();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brp::mock::{model, MockBrp};
+
+    #[test]
+    fn upload_sends_dependencies_then_spawns_the_model() {
+        let game = MockBrp::start();
+        let out = BevyUploadAssetTool
+            .execute(json!({
+                "local_path": model("road-straight.glb"),
+                "translation": [1.0, 0.0, 2.0],
+                "rotation": [0.0, 90.0, 0.0],
+                "label": "entry",
+                "group": "roads"
+            }))
+            .unwrap();
+
+        let requests = game.requests();
+        let spawns: Vec<&Value> = requests
+            .iter()
+            .filter(|r| r["method"] == "world.spawn_entity")
+            .collect();
+        let model_spawn = spawns.last().unwrap();
+        let asset = &model_spawn["params"]["components"][brp::REMOTE_ASSET];
+        assert_eq!(asset["filename"], "road-straight.glb");
+        for dep in asset["dependencies"].as_array().unwrap() {
+            let name = dep.as_str().unwrap().rsplit('/').next().unwrap();
+            assert!(
+                spawns
+                    .iter()
+                    .any(|r| r["params"]["components"][brp::REMOTE_ASSET]["filename"] == name),
+                "dependency {} was not uploaded before the model",
+                name
+            );
+        }
+        let transform = &model_spawn["params"]["components"][brp::TRANSFORM];
+        assert_eq!(transform["translation"], json!([1.0, 0.0, 2.0]));
+        let y = transform["rotation"][1].as_f64().unwrap();
+        assert!((y - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-4);
+
+        let placed = scene::snapshot();
+        assert_eq!(placed.len(), 1);
+        let entity = placed[0].entity.unwrap();
+        assert!(out.contains(&format!("Entity: {}.", entity)), "{}", out);
+        assert_eq!(placed[0].label.as_deref(), Some("entry"));
+        assert_eq!(placed[0].group.as_deref(), Some("roads"));
+        assert!(game.entity(entity).unwrap().contains_key(brp::SCENE_ROOT));
+    }
 
-        println!(
-            "[BevyTool] Sending Payload to {}: {}",
-            BEVY_RPC_URL, payload
-        );
+    #[test]
+    fn dependencies_are_sent_again_when_edited_or_forgotten() {
+        let game = MockBrp::start();
+        let dir = std::env::temp_dir().join(format!("axiom_deps_test_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("Textures")).unwrap();
+        std::fs::copy(model("road-end.glb"), dir.join("road-end.glb")).unwrap();
+        let texture = dir.join("Textures/colormap.png");
+        std::fs::copy(model("Textures/colormap.png"), &texture).unwrap();
+        let uploads = || {
+            game.requests()
+                .iter()
+                .filter(|r| {
+                    r["params"]["components"][brp::REMOTE_ASSET]["filename"] == "colormap.png"
+                })
+                .count()
+        };
+        let path = dir.join("road-end.glb").to_string_lossy().to_string();
+        let upload = || {
+            upload_asset(&path, None, [0.0; 3], [0.0; 3]).unwrap();
+        };
 
-        match agent.post(BEVY_RPC_URL).send_json(payload) {
-            Ok(res) => {
-                let status = res.status();
-                let body_str = res.into_string()?; // Get raw string first for debugging
-                println!("[BevyTool] Response ({}): {}", status, body_str);
+        upload();
+        upload();
+        assert_eq!(uploads(), 1);
 
-                // Parse back to JSON to be safe
-                let body: Value = serde_json::from_str(&body_str)
-                    .unwrap_or(json!({"error": "Invalid JSON response"}));
-                Ok(format!("Spawned Cube (Scene). Bevy Response: {}", body))
-            }
-            Err(e) => {
-                println!("[BevyTool] ERROR: {}", e);
-                Err(anyhow!(
-                    "Failed to spawn primitive via bevy_remote: {}. Is Bevy running on port 15721?",
-                    e
-                ))
-            }
+        let mut bytes = std::fs::read(&texture).unwrap();
+        bytes.push(0);
+        std::fs::write(&texture, bytes).unwrap();
+        upload();
+        assert_eq!(uploads(), 2);
+
+        forget_uploads(game.url());
+        upload();
+        assert_eq!(uploads(), 3);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn clear_scene_despawns_models_only() {
+        let game = MockBrp::start();
+        let camera = game.insert_entity(json!({ (brp::TRANSFORM): {} }));
+        for x in [0.0, 1.0] {
+            upload_asset(&model("road-bend.glb"), None, [x, 0.0, 0.0], [0.0; 3]).unwrap();
         }
+        assert_eq!(scene::snapshot().len(), 2);
+
+        let out = BevyClearSceneTool.execute(json!({})).unwrap();
+        assert_eq!(out, "Cleared 2 entities.");
+        assert!(game
+            .entity_ids()
+            .iter()
+            .all(|e| *e == camera || !game.entity(*e).unwrap().contains_key(brp::SCENE_ROOT)));
+        assert!(game.entity(camera).is_some());
+        assert!(scene::snapshot().is_empty());
+    }
+
+    #[test]
+    fn rpc_tool_wraps_bare_query_components() {
+        let game = MockBrp::start();
+        let entity = game.insert_entity(json!({ (brp::TRANSFORM): { "translation": [0, 1, 0] } }));
+        let out = BevyRpcTool
+            .execute(json!({
+                "method": "world.query",
+                "params": { "components": [brp::TRANSFORM] }
+            }))
+            .unwrap();
+
+        let request = &game.requests()[0];
+        assert_eq!(
+            request["params"]["data"]["components"],
+            json!([brp::TRANSFORM])
+        );
+        assert!(out.contains(&entity.to_string()), "{}", out);
+    }
+
+    #[test]
+    fn rpc_tool_reports_errors_and_old_method_names() {
+        let _game = MockBrp::start();
+        let err = BevyRpcTool
+            .execute(json!({ "method": "bevy/list", "params": {} }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("world.*"), "{}", err);
+
+        let err = BevyRpcTool
+            .execute(json!({ "method": "world.despawn_entity", "params": { "entity": 42 } }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("-23401"), "{}", err);
+    }
+
+    #[test]
+    fn components_tool_lists_reads_and_removes() {
+        let game = MockBrp::start();
+        let entity = game.insert_entity(json!({
+            (brp::TRANSFORM): { "translation": [0, 1, 0] },
+            (brp::NAME): "Lamp"
+        }));
+
+        let out = BevyComponentsTool
+            .execute(json!({ "entity": entity }))
+            .unwrap();
+        assert!(
+            out.contains(&format!("- {} = \"Lamp\"", brp::NAME)),
+            "{}",
+            out
+        );
+        assert!(out.contains(brp::TRANSFORM), "{}", out);
+
+        let out = BevyComponentsTool
+            .execute(json!({ "entity": entity, "get": [brp::NAME, brp::SCENE_ROOT] }))
+            .unwrap();
+        assert!(out.contains(&format!("- {}: ", brp::SCENE_ROOT)), "{}", out);
+        assert!(!out.contains(brp::TRANSFORM), "{}", out);
+
+        BevyComponentsTool
+            .execute(json!({ "entity": entity, "remove": [brp::NAME] }))
+            .unwrap();
+        let stored = game.entity(entity).unwrap();
+        assert!(!stored.contains_key(brp::NAME));
+        assert!(stored.contains_key(brp::TRANSFORM));
+        assert_eq!(
+            game.methods(),
+            [
+                "world.list_components",
+                "world.get_components",
+                "world.get_components",
+                "world.remove_components"
+            ]
+        );
+
+        let err = BevyComponentsTool
+            .execute(json!({ "get": [brp::NAME] }))
+            .unwrap_err();
+        assert!(err.to_string().contains("entity"), "{}", err);
+    }
+
+    #[test]
+    fn set_transform_and_despawn_update_game_and_mirror() {
+        let game = MockBrp::start();
+        let spawned = upload_asset(&model("road-end.glb"), None, [0.0; 3], [0.0; 3]).unwrap();
+        set_transform(
+            spawned.entity,
+            [3.0, 0.0, -1.0],
+            [0.0, 180.0, 0.0],
+            [2.0; 3],
+        )
+        .unwrap();
+
+        let stored = game.entity(spawned.entity).unwrap();
+        assert_eq!(
+            stored[brp::TRANSFORM]["translation"],
+            json!([3.0, 0.0, -1.0])
+        );
+        assert_eq!(stored[brp::TRANSFORM]["scale"], json!([2.0, 2.0, 2.0]));
+        assert_eq!(scene::snapshot()[0].translation, [3.0, 0.0, -1.0]);
+
+        despawn_entity(spawned.entity).unwrap();
+        assert!(game.entity(spawned.entity).is_none());
+        assert!(scene::snapshot().is_empty());
+        // Despawning again is not an error: the entity is already gone.
+        despawn_entity(spawned.entity).unwrap();
     }
 }
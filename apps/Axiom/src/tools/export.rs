@@ -0,0 +1,87 @@
+use crate::level_export::{self, ExportOptions};
+use crate::scene;
+use crate::tools::{query, Tool};
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Tool that writes everything Axiom has placed into one `.glb` for Blender and friends.
+pub struct ExportLevelTool;
+
+impl Tool for ExportLevelTool {
+    fn name(&self) -> String {
+        "export_level_glb".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Export the placed level as a single merged .glb file.".to_string()
+    }
+
+    fn schema(&self) -> Value {
+        query::with_selectors(json!({
+            "type": "function",
+            "function": {
+                "name": "export_level_glb",
+                "description": "Merge every asset placed this session (with its position and rotation) into one self-contained .glb that opens in Blender or any glTF viewer. Meshes, materials and textures shared between pieces are stored once. Only assets spawned through Axiom are included. Give ids, labels, groups or a 'where' query to export just part of the level.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "output_path": {
+                            "type": "string",
+                            "description": "Where to write the file, e.g. 'exports/level.glb'."
+                        },
+                        "group_by_model": {
+                            "type": "boolean",
+                            "description": "Put placements of the same model under one parent node (default false)."
+                        }
+                    },
+                    "required": ["output_path"]
+                }
+            }
+        }))
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let output = args
+            .get("output_path")
+            .and_then(|v| v.as_str())
+            .ok_or(anyhow!("Missing output_path"))?;
+        let options = ExportOptions {
+            group_by_model: args
+                .get("group_by_model")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        };
+
+        let placements = if query::has_selector(&args) {
+            // Entities the game has but Axiom did not spawn have no source file to merge.
+            query::select(&args)?
+                .into_iter()
+                .filter(|a| !a.local_path.as_os_str().is_empty())
+                .collect()
+        } else {
+            scene::snapshot()
+        };
+        if placements.is_empty() {
+            return Err(anyhow!("The scene is empty; nothing has been placed yet"));
+        }
+        let path = Path::new(output);
+        let summary = level_export::export(&placements, path, options)?;
+        let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+
+        let mut out = format!(
+            "Exported {} placements of {} models to {} ({:.1} KB): {} meshes, {} materials, {} images.",
+            summary.placements,
+            summary.models,
+            output,
+            size as f64 / 1024.0,
+            summary.meshes,
+            summary.materials,
+            summary.images
+        );
+        if !summary.skipped.is_empty() {
+            out.push_str(&format!("\nSkipped:\n- {}", summary.skipped.join("\n- ")));
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,386 @@
+use crate::game::{self, LogStream, ProcessStatus};
+use crate::targets::{self, GameTarget};
+use crate::tools::Tool;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::Duration;
+
+// The process tools pick their target themselves instead of going through `Targeted`: waiting
+// for a build must not hold up Bevy calls to the other games. `default` is the channel's target
+// when the turn started.
+fn target_of(args: &Value, default: &str) -> Result<GameTarget> {
+    let target = args
+        .get("target")
+        .and_then(|v| v.as_str())
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or(default);
+    if target.eq_ignore_ascii_case(targets::BROADCAST) {
+        return Err(anyhow!(
+            "Game processes are managed one target at a time, pass a target name"
+        ));
+    }
+    Ok(targets::resolve(target)?.remove(0))
+}
+
+fn target_property() -> Value {
+    json!({
+        "type": "string",
+        "description": "Game target whose process to manage (see game_targets). Default: the channel's target."
+    })
+}
+
+fn strings(value: Option<&Value>) -> Option<Vec<String>> {
+    value.and_then(|v| v.as_array()).map(|items| {
+        items
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect()
+    })
+}
+
+fn ready_timeout(args: &Value) -> Duration {
+    args.get("timeout_secs")
+        .and_then(|v| v.as_u64())
+        .map(Duration::from_secs)
+        .or_else(|| {
+            std::env::var("AXIOM_GAME_READY_SECS")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .map(Duration::from_secs)
+        })
+        .unwrap_or(game::DEFAULT_READY_TIMEOUT)
+}
+
+/// Launch report, waiting for BRP unless `wait` is false.
+fn launched(target: &GameTarget, status: ProcessStatus, args: &Value) -> Result<String> {
+    let mut out = format!(
+        "Started `{}` in {} (pid {}) for target '{}'.",
+        status.command,
+        status.dir.display(),
+        status.pid.unwrap_or(0),
+        target.name
+    );
+    if !args.get("wait").and_then(|v| v.as_bool()).unwrap_or(true) {
+        out.push_str(" Not waiting for it; check game_logs or the connection status.");
+        return Ok(out);
+    }
+    let took = game::wait_ready(target, ready_timeout(args))?;
+    out.push_str(&format!(
+        " BRP is ready at {} after {:.1}s.",
+        target.endpoint,
+        took.as_secs_f32()
+    ));
+    Ok(out)
+}
+
+/// Tool that builds and launches the game with `cargo run`.
+pub struct GameStartTool {
+    target: String,
+}
+
+impl GameStartTool {
+    pub fn new(target: &str) -> Self {
+        Self {
+            target: target.to_string(),
+        }
+    }
+}
+
+impl Tool for GameStartTool {
+    fn name(&self) -> String {
+        "game_start".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Build and launch the game with cargo run, then wait until it answers over BRP.".to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "game_start",
+                "description": "Build and launch the game with `cargo run` and wait until it answers over BRP. Compile errors and crashes are returned with the end of the output, so call this after editing game code to verify it. Launch settings are remembered for game_restart.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "target": target_property(),
+                        "dir": { "type": "string", "description": "Folder with the game's Cargo.toml. Default: AXIOM_GAME_DIR or examples/simple_game." },
+                        "package": { "type": "string", "description": "Cargo package to run (-p), for a workspace." },
+                        "cargo_args": { "type": "array", "items": { "type": "string" }, "description": "Extra cargo arguments, e.g. [\"--release\"]." },
+                        "args": { "type": "array", "items": { "type": "string" }, "description": "Arguments for the game, passed after --." },
+                        "restart_on_edit": { "type": "boolean", "description": "Restart automatically when the game's sources change." },
+                        "wait": { "type": "boolean", "description": "Wait for BRP to answer (default true)." },
+                        "timeout_secs": { "type": "integer", "description": "How long to wait for BRP, default 300 (first builds are slow)." }
+                    },
+                    "required": []
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let target = target_of(&args, &self.target)?;
+        let mut config = game::config(&target.endpoint);
+        if let Some(dir) = args.get("dir").and_then(|v| v.as_str()) {
+            config.dir = PathBuf::from(dir);
+        }
+        if let Some(package) = args.get("package").and_then(|v| v.as_str()) {
+            config.package = Some(package.to_string()).filter(|p| !p.is_empty());
+        }
+        if let Some(cargo_args) = strings(args.get("cargo_args")) {
+            config.cargo_args = cargo_args;
+        }
+        if let Some(game_args) = strings(args.get("args")) {
+            config.args = game_args;
+        }
+        if let Some(restart) = args.get("restart_on_edit").and_then(|v| v.as_bool()) {
+            config.restart_on_edit = restart;
+        }
+
+        let status = game::start(&target.endpoint, Some(config))?;
+        launched(&target, status, &args)
+    }
+}
+
+/// Tool that stops the game Axiom launched.
+pub struct GameStopTool {
+    target: String,
+}
+
+impl GameStopTool {
+    pub fn new(target: &str) -> Self {
+        Self {
+            target: target.to_string(),
+        }
+    }
+}
+
+impl Tool for GameStopTool {
+    fn name(&self) -> String {
+        "game_stop".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Stop the game process Axiom launched.".to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "game_stop",
+                "description": "Stop the game process started with game_start (cargo and the game itself).",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "target": target_property()
+                    },
+                    "required": []
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let target = target_of(&args, &self.target)?;
+        if game::stop(&target.endpoint) {
+            Ok(format!("Stopped the game for target '{}'.", target.name))
+        } else {
+            Ok(format!(
+                "No game launched by Axiom is running for target '{}'.",
+                target.name
+            ))
+        }
+    }
+}
+
+/// Tool that restarts the game with its last launch settings.
+pub struct GameRestartTool {
+    target: String,
+}
+
+impl GameRestartTool {
+    pub fn new(target: &str) -> Self {
+        Self {
+            target: target.to_string(),
+        }
+    }
+}
+
+impl Tool for GameRestartTool {
+    fn name(&self) -> String {
+        "game_restart".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Rebuild and restart the game, then wait until it answers over BRP.".to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "game_restart",
+                "description": "Stop the game if it runs, rebuild and launch it again with the settings of the last game_start, and wait until it answers over BRP. Use after editing game code; compile errors are returned. Entities spawned before are gone afterwards.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "target": target_property(),
+                        "wait": { "type": "boolean", "description": "Wait for BRP to answer (default true)." },
+                        "timeout_secs": { "type": "integer", "description": "How long to wait for BRP, default 300." }
+                    },
+                    "required": []
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let target = target_of(&args, &self.target)?;
+        let status = game::restart(&target.endpoint)?;
+        launched(&target, status, &args)
+    }
+}
+
+/// Tool that reads the captured output of the game.
+pub struct GameLogsTool {
+    target: String,
+}
+
+impl GameLogsTool {
+    pub fn new(target: &str) -> Self {
+        Self {
+            target: target.to_string(),
+        }
+    }
+}
+
+impl Tool for GameLogsTool {
+    fn name(&self) -> String {
+        "game_logs".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Read the captured stdout/stderr of the game Axiom launched.".to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "game_logs",
+                "description": "Read the captured output (cargo build messages, game stdout/stderr, panics) of the game started with game_start, newest last.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "target": target_property(),
+                        "lines": { "type": "integer", "description": "How many lines, default 80." },
+                        "filter": { "type": "string", "description": "Only lines containing this text (case-insensitive), e.g. 'error' or 'panicked'." },
+                        "stream": { "type": "string", "enum": ["all", "stdout", "stderr"], "description": "Default 'all'." },
+                        "all_runs": { "type": "boolean", "description": "Include earlier launches, not only the current one." }
+                    },
+                    "required": []
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let target = target_of(&args, &self.target)?;
+        let count = args.get("lines").and_then(|v| v.as_u64()).unwrap_or(80) as usize;
+        let filter = args
+            .get("filter")
+            .and_then(|v| v.as_str())
+            .map(str::to_lowercase)
+            .filter(|f| !f.is_empty());
+        let stream = match args.get("stream").and_then(|v| v.as_str()).unwrap_or("all") {
+            "all" => None,
+            "stdout" => Some(LogStream::Stdout),
+            "stderr" => Some(LogStream::Stderr),
+            other => return Err(anyhow!("Unknown stream '{}'", other)),
+        };
+        let all_runs = args
+            .get("all_runs")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let status = game::status(&target.endpoint);
+        let lines: Vec<String> = game::logs(&target.endpoint)
+            .into_iter()
+            .filter(|l| all_runs || l.run == status.run)
+            .filter(|l| stream.is_none_or(|s| l.stream == s || l.stream == LogStream::Axiom))
+            .filter(|l| {
+                filter
+                    .as_ref()
+                    .is_none_or(|f| l.text.to_lowercase().contains(f))
+            })
+            .map(|l| l.text)
+            .collect();
+
+        let state = match (status.pid, &status.exit) {
+            (Some(pid), _) => format!("running (pid {})", pid),
+            (None, Some(exit)) => exit.clone(),
+            (None, None) => "not started".to_string(),
+        };
+        let shown = &lines[lines.len().saturating_sub(count)..];
+        let mut out = format!(
+            "Game for target '{}': {}. {} of {} matching lines:\n",
+            target.name,
+            state,
+            shown.len(),
+            lines.len()
+        );
+        out.push_str(&shown.join("\n"));
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn start_reports_a_game_that_dies_before_brp_is_up() {
+        let dir = std::env::temp_dir().join(format!("axiom_game_test_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"crashing_game\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("src/main.rs"),
+            "fn main() {\n    println!(\"booting\");\n    panic!(\"no window\");\n}\n",
+        )
+        .unwrap();
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        targets::add("process-test", &port.to_string()).unwrap();
+
+        let err = GameStartTool::new("process-test")
+            .execute(json!({ "target": "process-test", "dir": dir, "cargo_args": ["--offline"], "timeout_secs": 120 }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("exited with code"), "{}", err);
+        assert!(err.contains("no window"), "{}", err);
+
+        let logs = GameLogsTool::new("process-test")
+            .execute(json!({ "target": "process-test", "stream": "stdout" }))
+            .unwrap();
+        assert!(logs.contains("booting"), "{}", logs);
+        assert!(!logs.contains("no window"), "{}", logs);
+        let stop = GameStopTool::new("process-test")
+            .execute(json!({ "target": "process-test" }))
+            .unwrap();
+        assert!(stop.contains("No game"), "{}", stop);
+
+        targets::remove("process-test").unwrap();
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::glb::{self, ModelInfo, NodeInfo};
+use crate::tools::{bevy, Tool};
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+fn fmt_vec(v: [f32; 3]) -> String {
+    format!("[{:.3}, {:.3}, {:.3}]", v[0], v[1], v[2])
+}
+
+fn push_nodes(out: &mut String, nodes: &[NodeInfo], depth: usize) {
+    for node in nodes {
+        out.push_str(&format!("{}- {}", "  ".repeat(depth + 1), node.name));
+        if let Some(mesh) = &node.mesh {
+            out.push_str(&format!(" (mesh: {})", mesh));
+        }
+        out.push('\n');
+        push_nodes(out, &node.children, depth + 1);
+    }
+}
+
+fn list(items: &[String]) -> String {
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+/// Human-readable report of a model, as returned by `gltf_inspect`.
+pub fn format_report(name: &str, file_bytes: u64, info: &ModelInfo) -> String {
+    let mut out = format!("Model: {} ({:.1} KB", name, file_bytes as f64 / 1024.0);
+    if let Some(generator) = &info.generator {
+        out.push_str(&format!(", generator: {}", generator));
+    }
+    out.push_str(")\n");
+
+    match (info.bounds, info.size()) {
+        (Some([min, max]), Some(size)) => out.push_str(&format!(
+            "Bounds: min {} max {} size {}\n",
+            fmt_vec(min),
+            fmt_vec(max),
+            fmt_vec(size)
+        )),
+        _ => out.push_str("Bounds: unknown (no POSITION min/max)\n"),
+    }
+
+    out.push_str(&format!(
+        "Meshes: {} ({} vertices, {} triangles)\n",
+        info.meshes.len(),
+        info.vertex_count(),
+        info.triangle_count()
+    ));
+    for mesh in &info.meshes {
+        out.push_str(&format!(
+            "  - {}: {} primitive(s), {} vertices, {} triangles, materials: {}\n",
+            mesh.name,
+            mesh.primitives,
+            mesh.vertices,
+            mesh.triangles,
+            list(&mesh.materials)
+        ));
+    }
+
+    out.push_str("Node hierarchy:\n");
+    push_nodes(&mut out, &info.nodes, 0);
+
+    out.push_str(&format!("Materials: {}\n", list(&info.materials)));
+    let textures: Vec<String> = info
+        .textures
+        .iter()
+        .map(|t| match (&t.uri, &t.mime_type) {
+            (Some(uri), _) => uri.clone(),
+            (None, Some(mime)) => format!("embedded {}", mime),
+            (None, None) => "embedded".to_string(),
+        })
+        .collect();
+    out.push_str(&format!("Textures: {}\n", list(&textures)));
+    out.push_str(&format!("Animations: {}\n", list(&info.animations)));
+    if !info.external_files.is_empty() {
+        out.push_str(&format!(
+            "External files (must be uploaded alongside): {}\n",
+            info.external_files.join(", ")
+        ));
+    }
+    out
+}
+
+/// Tool that reads a .glb / .gltf file and reports what is inside before it gets spawned.
+pub struct GltfInspectTool;
+
+impl Tool for GltfInspectTool {
+    fn name(&self) -> String {
+        "gltf_inspect".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Inspect a .glb/.gltf model: node hierarchy, meshes, vertex counts, materials, textures, animations and bounding box.".to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "gltf_inspect",
+                "description": "Inspect a local .glb or .gltf model before uploading it: node hierarchy, mesh names with vertex/triangle counts, materials, referenced textures, animations and the bounding box (use it to pick scale and placement). Paths resolve like bevy_upload_asset, so a bare filename such as 'road-bend.glb' works.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "local_path": {
+                            "type": "string",
+                            "description": "Path to the model, or just its filename if it lives in the resources folder."
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["text", "json"],
+                            "description": "Readable summary (default) or the full report as JSON."
+                        }
+                    },
+                    "required": ["local_path"]
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let local_path = args
+            .get("local_path")
+            .and_then(|v| v.as_str())
+            .ok_or(anyhow!("Missing local_path"))?;
+        let path = bevy::resolve_asset_path(local_path)?;
+        let info = glb::inspect(&glb::load(&path)?);
+
+        if args.get("format").and_then(|v| v.as_str()) == Some("json") {
+            return Ok(serde_json::to_string_pretty(&info)?);
+        }
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| local_path.to_string());
+        let file_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Ok(format_report(&name, file_bytes, &info))
+    }
+}
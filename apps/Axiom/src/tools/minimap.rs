@@ -0,0 +1,46 @@
+use crate::tools::Tool;
+use crate::{minimap, scene};
+use anyhow::Result;
+use serde_json::{json, Value};
+
+/// Tool that renders the current layout top-down so the model can look at what it built.
+pub struct SceneMinimapTool;
+
+impl Tool for SceneMinimapTool {
+    fn name(&self) -> String {
+        "scene_minimap".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Render a top-down minimap of everything placed so far and attach it to the next turn."
+            .to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "scene_minimap",
+                "description": "Render a top-down PNG minimap of the roads, pillars and models spawned so far, with grid coordinates. The image is attached to your next turn, so call this after building to check the layout visually (gaps, wrong rotations, overlaps).",
+                "parameters": {
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }
+            }
+        })
+    }
+
+    fn execute(&self, _args: Value) -> Result<String> {
+        let assets = scene::snapshot();
+        let png = minimap::render_scene_png()?;
+        let size = png.len();
+        minimap::set_pending(png);
+        Ok(format!(
+            "Minimap of {} placed assets rendered ({} KB), it is attached to your next turn.\n{}",
+            assets.len(),
+            size / 1024,
+            minimap::legend(&assets)
+        ))
+    }
+}
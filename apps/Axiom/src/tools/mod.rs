@@ -1,12 +1,25 @@
+pub mod assets;
 pub mod ast_grep;
 pub mod batch;
 pub mod bevy;
+pub mod export;
+pub mod game;
+pub mod gltf;
 pub mod locks;
 pub mod lsp;
+pub mod minimap;
 pub mod multiedit;
+pub mod query;
+pub mod road;
+pub mod scatter;
+pub mod schema;
 pub mod search;
 pub mod shell;
+pub mod sketch;
+pub mod target;
+pub mod texture;
 pub mod todo;
+pub mod transform;
 
 use crate::types::AsyncMessage;
 use anyhow::{anyhow, Result};
@@ -17,6 +30,7 @@ use bevy::{
 use serde_json::{json, Value};
 use std::fs;
 use std::sync::mpsc::Sender;
+use target::Targeted;
 
 pub trait Tool: Send + Sync {
     fn name(&self) -> String;
@@ -24,6 +38,16 @@ pub trait Tool: Send + Sync {
     fn description(&self) -> String;
     fn schema(&self) -> Value;
     fn execute(&self, args: Value) -> Result<String>;
+    /// `bevy_ai_remote` features (see `axiom/hello`) the game must offer. The tool is hidden
+    /// from the agent when the channel's game is known to lack one.
+    fn requires(&self) -> &'static [&'static str] {
+        &[]
+    }
+    /// Whether the tool acts on the running game. The prompt then tells the agent about the
+    /// game's state.
+    fn uses_game(&self) -> bool {
+        false
+    }
 }
 
 // ... (Other standard tools: ReadFileTool, WriteFileTool, etc.)
@@ -154,7 +178,14 @@ impl Tool for EditFileTool {
     }
 }
 
-pub fn get_tools_for_profile(profile_name: &str, tx: Sender<AsyncMessage>) -> Vec<Box<dyn Tool>> {
+/// `target` is the game target of the channel when the turn started; the game tools use it when
+/// a call names none.
+pub fn get_tools_for_profile(
+    profile_name: &str,
+    channel_id: &str,
+    target: &str,
+    tx: Sender<AsyncMessage>,
+) -> Vec<Box<dyn Tool>> {
     let mut tools: Vec<Box<dyn Tool>> = vec![
         Box::new(ReadFileTool),
         Box::new(WriteFileTool),
@@ -163,23 +194,46 @@ pub fn get_tools_for_profile(profile_name: &str, tx: Sender<AsyncMessage>) -> Ve
         Box::new(todo::TodoReadTool),
         Box::new(todo::TodoWriteTool),
         Box::new(ast_grep::AstGrepTool),
-        Box::new(batch::BatchTool::new(tx.clone())),
+        Box::new(batch::BatchTool::new(channel_id, target, tx.clone())),
         Box::new(multiedit::MultiEditTool),
         Box::new(lsp::LspTool),
         Box::new(shell::ShellTool),
-        Box::new(bevy::BevyUploadAssetTool), // Now available to all agents
-        Box::new(bevy::BevyClearSceneTool),  // New: Clear Scene
+        Box::new(Targeted::new(bevy::BevyUploadAssetTool, target)), // Now available to all agents
+        Box::new(Targeted::new(bevy::BevyClearSceneTool, target)),  // New: Clear Scene
+        Box::new(gltf::GltfInspectTool),
+        Box::new(assets::SearchAssetsTool),
+        Box::new(texture::OptimizeTextureTool),
+        Box::new(Targeted::new(road::RoadNavTool, target)),
+        Box::new(Targeted::new(road::RoadTurtleTool::new(channel_id), target)),
+        Box::new(Targeted::new(road::RoadSplineTool, target)),
+        Box::new(Targeted::new(sketch::RoadSketchTool, target)),
+        Box::new(Targeted::new(scatter::ScatterTool, target)),
+        Box::new(Targeted::single(minimap::SceneMinimapTool, target)),
+        Box::new(Targeted::single(export::ExportLevelTool, target)),
+        Box::new(Targeted::new(transform::EditTransformsTool, target)),
+        Box::new(Targeted::new(query::QuerySceneTool, target)),
+        Box::new(Targeted::single(schema::BevySchemaTool, target)),
+        Box::new(Targeted::single(bevy::BevyComponentsTool, target)),
+        Box::new(target::GameTargetsTool),
+        Box::new(game::GameStartTool::new(target)),
+        Box::new(game::GameStopTool::new(target)),
+        Box::new(game::GameRestartTool::new(target)),
+        Box::new(game::GameLogsTool::new(target)),
                                              // Box::new(bevy::BevySpawnPrimitiveTool), // Temporarily disabled to force asset upload workflow
     ];
 
     if profile_name == "Bevy Editor Companion" {
-        tools.push(Box::new(bevy::BevyRpcTool));
-        tools.push(Box::new(bevy::BevySpawnSceneTool));
+        tools.push(Box::new(Targeted::new(bevy::BevyRpcTool, target)));
+        tools.push(Box::new(Targeted::new(bevy::BevySpawnSceneTool, target)));
     }
 
     tools
 }
 
-pub fn get_all_tools(tx: Sender<AsyncMessage>) -> Vec<Box<dyn Tool>> {
-    get_tools_for_profile("General", tx)
+pub fn get_all_tools(
+    channel_id: &str,
+    target: &str,
+    tx: Sender<AsyncMessage>,
+) -> Vec<Box<dyn Tool>> {
+    get_tools_for_profile("General", channel_id, target, tx)
 }
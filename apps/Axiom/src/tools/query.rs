@@ -0,0 +1,318 @@
+use crate::arrange::degrees_from_quat;
+use crate::brp;
+use crate::query;
+use crate::scene::{self, PlacedAsset};
+use crate::tools::Tool;
+use anyhow::{anyhow, Result};
+use glam::Quat;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+fn array3(value: Option<&Value>) -> Option<[f32; 3]> {
+    let a = value?.as_array()?;
+    let at = |i: usize| a.get(i).and_then(|v| v.as_f64()).map(|v| v as f32);
+    Some([at(0)?, at(1)?, at(2)?])
+}
+
+fn round3(v: [f32; 3]) -> [f32; 3] {
+    v.map(|x| (x * 1000.0).round() / 1000.0 + 0.0)
+}
+
+/// Model entities currently in the game, read with `world.query`. Entities Axiom spawned keep
+/// their asset, label and group from the scene mirror, which is refreshed with the live
+/// transforms. Mirrored entities the query did not return (still waiting for their textures,
+/// so no `SceneRoot` yet) are kept as recorded.
+pub fn live_scene() -> Result<Vec<PlacedAsset>> {
+    let params = brp::QueryParams {
+        data: brp::QueryData {
+            components: vec![brp::TRANSFORM.to_string()],
+            option: vec![brp::NAME.to_string()],
+            ..Default::default()
+        },
+        filter: brp::QueryFilter {
+            with: vec![brp::SCENE_ROOT.to_string()],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let rows = brp::block_on(brp::client().query(&params))
+        .map_err(|e| anyhow!("Failed to query the game: {}", e))?;
+
+    let mirror = scene::snapshot();
+    let mut live = Vec::new();
+    let mut seen = HashSet::new();
+    for row in rows {
+        let entity = row.entity;
+        let transform = row.components.get(brp::TRANSFORM);
+        let translation =
+            round3(array3(transform.and_then(|t| t.get("translation"))).unwrap_or_default());
+        let scale = round3(array3(transform.and_then(|t| t.get("scale"))).unwrap_or([1.0; 3]));
+        let rotation = transform
+            .and_then(|t| t.get("rotation"))
+            .and_then(|r| r.as_array())
+            .and_then(|r| {
+                let at = |i: usize| r.get(i).and_then(|v| v.as_f64()).map(|v| v as f32);
+                Some(Quat::from_xyzw(at(0)?, at(1)?, at(2)?, at(3)?))
+            })
+            .map_or([0.0; 3], degrees_from_quat);
+
+        seen.insert(entity);
+        let asset = match mirror.iter().find(|a| a.entity == Some(entity)) {
+            Some(known) => {
+                let mut known = known.clone();
+                known.translation = translation;
+                known.rotation = rotation;
+                known.scale = scale;
+                scene::update(entity, |a| *a = known.clone());
+                known
+            }
+            None => {
+                // Not spawned by Axiom: use the entity's `Name` as its asset, if it has one.
+                let name = row
+                    .components
+                    .get(brp::NAME)
+                    .and_then(|n| n.as_str().or_else(|| n.get("name")?.as_str()))
+                    .unwrap_or("?");
+                let mut asset = PlacedAsset::new(
+                    Some(entity),
+                    name.to_string(),
+                    PathBuf::new(),
+                    translation,
+                    rotation,
+                );
+                asset.scale = scale;
+                asset
+            }
+        };
+        live.push(asset);
+    }
+
+    live.extend(
+        mirror
+            .into_iter()
+            .filter(|a| a.entity.is_some_and(|e| !seen.contains(&e))),
+    );
+    live.sort_by_key(|a| a.entity);
+    Ok(live)
+}
+
+fn strings(args: &Value, key: &str) -> Vec<String> {
+    match args.get(key) {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn model_stem(filename: &str) -> &str {
+    filename.rsplit_once('.').map_or(filename, |(stem, _)| stem)
+}
+
+/// Adds the selector parameters (`ids`, `labels`, `groups`, `where`) to a tool schema.
+pub fn with_selectors(mut schema: Value) -> Value {
+    let selectors = json!({
+        "ids": {
+            "type": "array",
+            "items": { "type": "integer" },
+            "description": "Entity ids."
+        },
+        "labels": {
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "Labels given at upload. Unlabelled entities also answer to their model name, e.g. 'light-square'."
+        },
+        "groups": {
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "Groups given at upload."
+        },
+        "where": {
+            "type": "string",
+            "description": "Scene query as in query_scene, e.g. 'asset ~ \"road-bend*\" within 3 of [2,0,2]'. Combined with ids/labels/groups it narrows them down."
+        }
+    });
+    if let (Some(Value::Object(properties)), Value::Object(selectors)) = (
+        schema.pointer_mut("/function/parameters/properties"),
+        selectors,
+    ) {
+        properties.extend(selectors);
+    }
+    schema
+}
+
+/// True if `args` contains any selector parameter.
+pub fn has_selector(args: &Value) -> bool {
+    ["ids", "labels", "groups", "where"]
+        .iter()
+        .any(|k| args.get(*k).is_some_and(|v| !v.is_null()))
+}
+
+/// Entities picked by id, label, group and/or a `where` query. Ids, labels and groups add up;
+/// `where` then filters them (or the whole scene when it is the only selector). Uses the live
+/// game state when the game answers, the scene mirror otherwise.
+pub fn select(args: &Value) -> Result<Vec<PlacedAsset>> {
+    let ids: Vec<u64> = match args.get("ids") {
+        Some(Value::Array(items)) => items.iter().filter_map(|v| v.as_u64()).collect(),
+        Some(v) => v.as_u64().into_iter().collect(),
+        None => Vec::new(),
+    };
+    let labels = strings(args, "labels");
+    let groups = strings(args, "groups");
+    let filter = args
+        .get("where")
+        .and_then(|v| v.as_str())
+        .map(query::parse)
+        .transpose()?;
+    let listed = !(ids.is_empty() && labels.is_empty() && groups.is_empty());
+    if !listed && filter.is_none() {
+        return Err(anyhow!(
+            "Select entities with 'ids', 'labels', 'groups' or 'where'"
+        ));
+    }
+
+    let candidates = live_scene().unwrap_or_else(|_| scene::snapshot());
+    let selected: Vec<PlacedAsset> = candidates
+        .into_iter()
+        .filter(|a| {
+            !listed
+                || a.entity.is_some_and(|e| ids.contains(&e))
+                || labels.iter().any(|l| {
+                    a.label.as_deref() == Some(l.as_str())
+                        || (a.label.is_none() && model_stem(&a.filename) == model_stem(l))
+                })
+                || a.group.as_ref().is_some_and(|g| groups.contains(g))
+        })
+        .filter(|a| filter.as_ref().is_none_or(|f| f.matches(a)))
+        .collect();
+    if selected.is_empty() {
+        return Err(anyhow!(
+            "No placed entity matches ids {:?}, labels {:?}, groups {:?} or where {:?}",
+            ids,
+            labels,
+            groups,
+            args.get("where").and_then(|v| v.as_str()).unwrap_or("")
+        ));
+    }
+    Ok(selected)
+}
+
+/// Tool that answers scene questions with a compact table instead of raw `world.query` dumps.
+pub struct QuerySceneTool;
+
+impl Tool for QuerySceneTool {
+    fn name(&self) -> String {
+        "query_scene".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Find spawned entities with a small query language and list them as a compact table."
+            .to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "query_scene",
+                "description": "Find entities in the running game and list them as 'id | label | asset | cell | rot' rows. Prefer this over a raw world.query. Fields: id, asset, label, group, x, y, z, yaw, cell. Operators: = != < <= > >= and ~ / !~ for globs. Also 'within R of [x,z]' (or [x,y,z]), 'and', 'or', 'not' and parentheses; conditions side by side must all hold. Examples: 'asset ~ \"road-bend*\" within 3 of [2,0,2]', 'group = roundabout', 'y > 0', 'cell = [4,-2]'. The same text works as 'where' in edit_transforms and export_level_glb.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "query": {
+                            "type": "string",
+                            "description": "The query. Empty or '*' lists everything."
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum rows to print (default 50)."
+                        }
+                    },
+                    "required": ["query"]
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let text = args.get("query").and_then(|v| v.as_str()).unwrap_or("");
+        let expr = query::parse(text).map_err(|e| anyhow!("Invalid query '{}': {}", text, e))?;
+        let limit = args
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(50, |n| n as usize);
+
+        let (entities, source) = match live_scene() {
+            Ok(entities) => (entities, "game"),
+            Err(e) => {
+                eprintln!("[QueryScene] {}, using the scene mirror", e);
+                (scene::snapshot(), "scene mirror (game not reachable)")
+            }
+        };
+        let matches = query::filter(&entities, &expr);
+        if matches.is_empty() {
+            return Ok(format!(
+                "No entity matches '{}' ({} entities searched in the {}).",
+                text,
+                entities.len(),
+                source
+            ));
+        }
+        Ok(format!(
+            "{} of {} entities match (from the {}):\n{}",
+            matches.len(),
+            entities.len(),
+            source,
+            query::table(&matches, limit)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brp::mock::{model, MockBrp};
+    use crate::tools::bevy;
+
+    #[test]
+    fn lists_matches_from_the_game_with_mirror_metadata() {
+        let game = MockBrp::start();
+        let bend = bevy::upload_asset(&model("road-bend.glb"), None, [2.0, 0.0, 3.0], [0.0; 3])
+            .unwrap()
+            .entity;
+        bevy::upload_asset(&model("road-end.glb"), None, [9.0, 0.0, 9.0], [0.0; 3]).unwrap();
+        let stranger = game.insert_entity(json!({
+            (brp::TRANSFORM): { "translation": [2.0, 0.0, 2.0], "rotation": [0.0, 0.0, 0.0, 1.0], "scale": [1.0, 1.0, 1.0] },
+            (brp::SCENE_ROOT): {},
+            (brp::NAME): "Tree"
+        }));
+        scene::update(bend, |a| a.label = Some("corner".to_string()));
+
+        let out = QuerySceneTool
+            .execute(json!({ "query": "within 2 of [2,2]" }))
+            .unwrap();
+        assert!(
+            out.starts_with("2 of 3 entities match (from the game)"),
+            "{}",
+            out
+        );
+        assert!(
+            out.contains(&format!("{} | corner | road-bend | 2,3 | 0", bend)),
+            "{}",
+            out
+        );
+        assert!(
+            out.contains(&format!("{} | - | Tree | 2,2 | 0", stranger)),
+            "{}",
+            out
+        );
+
+        let selected = select(&json!({ "where": "asset ~ road-*", "labels": ["corner"] })).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].entity, Some(bend));
+    }
+}
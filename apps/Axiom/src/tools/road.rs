@@ -0,0 +1,520 @@
+use crate::tools::{bevy, Tool};
+use crate::{brp, scene};
+use anyhow::{anyhow, Result};
+use axiom_roads::{
+    find_collisions, plan_pillars, spline_to_tiles, Cell, CurveKind, NavGraph, SplineOptions,
+    TilePlacement, Turtle, TurtleCommand,
+};
+use rayon::prelude::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+fn parse_point(args: &Value, key: &str) -> Result<[f32; 3]> {
+    let arr = args
+        .get(key)
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow!("Missing '{}'", key))?;
+    let x = arr.first().and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+    let y = arr.get(1).and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+    let z = arr.get(2).and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+    Ok([x, y, z])
+}
+
+fn fmt_point(p: [f32; 3]) -> String {
+    format!("[{:.2}, {:.2}, {:.2}]", p[0], p[1], p[2])
+}
+
+pub fn fmt_placement(p: &TilePlacement) -> String {
+    format!(
+        "{} at {} rotation [0, {}, 0]",
+        p.asset,
+        fmt_point(p.translation),
+        p.yaw_degrees
+    )
+}
+
+/// Spawns all placements in parallel, like `batch_run` would. Returns one line per failure.
+pub fn spawn_placements(placements: &[TilePlacement]) -> Vec<String> {
+    placements
+        .par_iter()
+        .filter_map(|p| {
+            bevy::upload_asset(&p.asset, None, p.translation, [0.0, p.yaw_degrees, 0.0])
+                .err()
+                .map(|e| format!("{}: {}", fmt_placement(p), e))
+        })
+        .collect()
+}
+
+/// Lists `placements` in the result and spawns them in one batch unless `spawn` is off.
+pub fn place_tiles(placements: &[TilePlacement], spawn: bool, out: &mut String) {
+    out.push_str(&format!("{} road tiles:\n", placements.len()));
+    for p in placements {
+        out.push_str(&format!("- {}\n", fmt_placement(p)));
+    }
+
+    if !spawn {
+        out.push_str("Preview only, nothing was spawned.\n");
+        return;
+    }
+    let failures = spawn_placements(placements);
+    if failures.is_empty() {
+        out.push_str("All tiles spawned.\n");
+    } else {
+        out.push_str(&format!("{} tiles failed to spawn:\n", failures.len()));
+        for f in failures {
+            out.push_str(&format!("- {}\n", f));
+        }
+    }
+}
+
+/// Puts pillars under the elevated tiles among `roads` and reports collisions in the result.
+/// With `spawn` off nothing is placed and the check runs against scene + `roads`.
+pub fn support_and_validate(roads: &[TilePlacement], spacing: i32, spawn: bool, out: &mut String) {
+    let world = scene::tile_placements();
+    let plan = plan_pillars(roads, &world, spacing);
+
+    if !plan.pillars.is_empty() {
+        out.push_str(&format!("{} bridge pillars", plan.pillars.len()));
+        if spawn {
+            let failures = spawn_placements(&plan.pillars);
+            out.push_str(&format!(" placed ({} failed)", failures.len()));
+        } else {
+            out.push_str(" needed");
+        }
+        out.push_str(".\n");
+    }
+    for (x, z) in &plan.blocked {
+        out.push_str(&format!(
+            "- No pillar at cell [{}, {}]: a road runs underneath.\n",
+            x, z
+        ));
+    }
+
+    let problems = if spawn {
+        find_collisions(&scene::tile_placements())
+    } else {
+        let mut all = world;
+        all.extend_from_slice(roads);
+        all.extend(plan.pillars);
+        find_collisions(&all)
+    };
+    for problem in problems {
+        out.push_str(&format!("- Collision: {}\n", problem));
+    }
+}
+
+pub fn pillar_spacing(args: &Value) -> i32 {
+    args.get("pillar_spacing")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(2) as i32
+}
+
+/// Tool to inspect the drivable lane graph of the roads placed so far and query paths on it.
+pub struct RoadNavTool;
+
+impl Tool for RoadNavTool {
+    fn name(&self) -> String {
+        "road_nav".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Compute the navigation graph of the road tiles placed via bevy_upload_asset. Reports nodes, edges and open road ends, or finds an A* path between two world points.".to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "road_nav",
+                "description": "Inspect the lane graph of placed roads or find a drivable path. Use 'summary' to check that a network is connected and has no unintended dead ends.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "action": {
+                            "type": "string",
+                            "enum": ["summary", "path"],
+                            "description": "'summary' lists graph stats and open ends; 'path' runs A* from 'start' to 'goal'."
+                        },
+                        "start": {
+                            "type": "array",
+                            "items": { "type": "number" },
+                            "minItems": 3,
+                            "maxItems": 3,
+                            "description": "[x, y, z] start point (required for 'path')"
+                        },
+                        "goal": {
+                            "type": "array",
+                            "items": { "type": "number" },
+                            "minItems": 3,
+                            "maxItems": 3,
+                            "description": "[x, y, z] goal point (required for 'path')"
+                        }
+                    },
+                    "required": ["action"]
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let action = args
+            .get("action")
+            .and_then(|v| v.as_str())
+            .unwrap_or("summary");
+
+        let placements = scene::tile_placements();
+        let graph = NavGraph::build(&placements);
+        if graph.is_empty() {
+            return Ok(
+                "No road tiles have been placed in this session. Spawn roads with bevy_upload_asset first."
+                    .to_string(),
+            );
+        }
+
+        match action {
+            "summary" => {
+                let open_ends = graph.open_ends();
+                let mut out = format!(
+                    "Road nav graph: {} tiles, {} nodes, {} edges, {} open ends.\n",
+                    placements.len() - graph.skipped.len(),
+                    graph.nodes.len(),
+                    graph.edges.len(),
+                    open_ends.len()
+                );
+                for id in open_ends {
+                    out.push_str(&format!(
+                        "- open end at {}\n",
+                        fmt_point(graph.nodes[id].position)
+                    ));
+                }
+                if !graph.skipped.is_empty() {
+                    out.push_str(&format!(
+                        "Ignored non-road assets: {}\n",
+                        graph.skipped.join(", ")
+                    ));
+                }
+                Ok(out)
+            }
+            "path" => {
+                let start = parse_point(&args, "start")?;
+                let goal = parse_point(&args, "goal")?;
+                let path = graph.find_path(start, goal).ok_or_else(|| {
+                    anyhow!(
+                        "No drivable path between {} and {}",
+                        fmt_point(start),
+                        fmt_point(goal)
+                    )
+                })?;
+
+                let waypoints = path
+                    .waypoints
+                    .iter()
+                    .map(|p| fmt_point(*p))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                Ok(format!(
+                    "Path length {:.2} through {} nodes:\n{}",
+                    path.length,
+                    path.waypoints.len(),
+                    waypoints
+                ))
+            }
+            other => Err(anyhow!("Unknown action '{}'", other)),
+        }
+    }
+}
+
+/// Turtle plus the entities it spawned, so tiles can be swapped when the road crosses itself.
+#[derive(Default)]
+struct TurtleSession {
+    turtle: Turtle,
+    entities: HashMap<(Cell, i32), u64>,
+}
+
+// One turtle per chat channel and game, so parallel conversations do not move each other's
+// cursor and every game instance keeps its own road.
+static TURTLES: OnceLock<Mutex<HashMap<(String, String), TurtleSession>>> = OnceLock::new();
+
+fn get_turtles() -> &'static Mutex<HashMap<(String, String), TurtleSession>> {
+    TURTLES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Tool that draws roads with a stateful cursor: the agent describes intent
+/// ("forward 3", "turn_right") and the tile types and rotations are derived here.
+pub struct RoadTurtleTool {
+    channel: String,
+}
+
+impl RoadTurtleTool {
+    pub fn new(channel: &str) -> Self {
+        Self {
+            channel: channel.to_string(),
+        }
+    }
+}
+
+impl Tool for RoadTurtleTool {
+    fn name(&self) -> String {
+        "road_turtle".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Draw roads turtle-style. Keeps a cursor and heading per channel and places correctly rotated road tiles for each command.".to_string()
+    }
+
+    fn requires(&self) -> &'static [&'static str] {
+        &["upload"]
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "road_turtle",
+                "description": "Draw roads with a cursor instead of computing tile rotations by hand. The cursor persists between calls. Each command consumes the cell under the cursor: 'forward N' lays N straights, 'turn_left'/'turn_right' lay a bend and change heading, 'branch left|right|cross' lays a junction and queues the side roads, 'branch T' ends the road in a T and continues right, 'climb N'/'descend N' lay slopes one level (0.5 units) per cell, 'end' caps the road (or joins the road already there) and resumes at the next queued branch. Crossing your own road upgrades the tile automatically; elevated tiles become bridges and get pillars.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "commands": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Commands in order, e.g. [\"start at [0, 0] heading East\", \"forward 3\", \"turn_right\", \"forward 2\", \"end\"]. Add 'level N' to start to begin elevated. 'reset' forgets the cursor and drawn road."
+                        },
+                        "spawn": {
+                            "type": "boolean",
+                            "description": "Spawn the tiles in the running game (default true). Set false to preview the placements without moving the cursor."
+                        },
+                        "pillar_spacing": {
+                            "type": "integer",
+                            "description": "Put a pillar under every Nth elevated cell (default 2)."
+                        }
+                    },
+                    "required": ["commands"]
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let commands = args
+            .get("commands")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow!("Missing 'commands'"))?;
+        let spawn = args.get("spawn").and_then(|v| v.as_bool()).unwrap_or(true);
+
+        let key = (self.channel.clone(), brp::endpoint());
+        // A preview runs on a copy so the stored cursor only moves when tiles are really placed.
+        let (mut turtle, mut entities) = {
+            let mut turtles = get_turtles()
+                .lock()
+                .map_err(|_| anyhow!("Turtle state poisoned"))?;
+            let session = turtles.entry(key.clone()).or_default();
+            (session.turtle.clone(), session.entities.clone())
+        };
+
+        // Tiles of the commands that went through, `None` for a reset.
+        let mut steps = Vec::new();
+        let mut failure = None;
+        for command in commands {
+            let text = command.as_str().unwrap_or_default();
+            match TurtleCommand::parse(text).and_then(|cmd| turtle.apply(&cmd).map(|c| (cmd, c))) {
+                Ok((TurtleCommand::Reset, _)) => steps.push((text, None)),
+                Ok((_, changes)) => steps.extend(changes.into_iter().map(|c| (text, Some(c)))),
+                Err(e) => {
+                    failure = Some(format!(
+                        "- '{}' failed: {}. Remaining commands skipped.\n",
+                        text, e
+                    ));
+                    break;
+                }
+            }
+        }
+
+        // The turtle is stored before spawning, so the lock is not held during the uploads.
+        if spawn {
+            let mut turtles = get_turtles()
+                .lock()
+                .map_err(|_| anyhow!("Turtle state poisoned"))?;
+            turtles.entry(key.clone()).or_default().turtle = turtle.clone();
+        }
+
+        let mut out = String::new();
+        let mut laid = Vec::new();
+        for (text, change) in steps {
+            let Some(change) = change else {
+                if spawn {
+                    entities.clear();
+                }
+                out.push_str("- reset\n");
+                continue;
+            };
+            let p = &change.placement;
+            let mut line = format!("- {}: {}", text, fmt_placement(p));
+            if change.replaces_existing {
+                line.push_str(" (replaces the tile there)");
+            }
+
+            if spawn {
+                if let Some(old) = entities.remove(&(change.cell, change.level)) {
+                    if let Err(e) = bevy::despawn_entity(old) {
+                        line.push_str(&format!(" [despawn failed: {}]", e));
+                    }
+                }
+                match bevy::upload_asset(&p.asset, None, p.translation, [0.0, p.yaw_degrees, 0.0]) {
+                    Ok(spawned) => {
+                        entities.insert((change.cell, change.level), spawned.entity);
+                    }
+                    Err(e) => line.push_str(&format!(" [spawn failed: {}]", e)),
+                }
+            }
+            out.push_str(&line);
+            out.push('\n');
+            laid.push(change.placement);
+        }
+        if let Some(failure) = failure {
+            out.push_str(&failure);
+        }
+        support_and_validate(&laid, pillar_spacing(&args), spawn, &mut out);
+
+        match turtle.cursor {
+            Some((x, z)) => out.push_str(&format!(
+                "Cursor at [{}, {}] heading {:?} on level {}.",
+                x, z, turtle.heading, turtle.level
+            )),
+            None => out.push_str("Road finished, cursor is idle."),
+        }
+        if !turtle.branches.is_empty() {
+            out.push_str(&format!(" {} branch(es) queued.", turtle.branches.len()));
+        }
+        if spawn {
+            let mut turtles = get_turtles()
+                .lock()
+                .map_err(|_| anyhow!("Turtle state poisoned"))?;
+            turtles.entry(key).or_default().entities = entities;
+        } else {
+            out.push_str(" (preview only, nothing was placed)");
+        }
+        Ok(out)
+    }
+}
+
+/// Tool that rasterises a smooth curve through control points into grid road pieces.
+pub struct RoadSplineTool;
+
+impl Tool for RoadSplineTool {
+    fn name(&self) -> String {
+        "road_spline".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Turn a Catmull-Rom or Bezier curve through control points into straight, bend and road-curve tiles on the 1.0 grid.".to_string()
+    }
+
+    fn requires(&self) -> &'static [&'static str] {
+        &["upload"]
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "road_spline",
+                "description": "Build a curvy road (S-curves, arcs, loops) from a few control points. The curve is rasterised onto the grid and each cell gets the correctly rotated straight/bend piece; 90-degree turns spread over 2x2 cells use road-curve. Returns the placements and spawns them in one batch unless spawn is false.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "points": {
+                            "type": "array",
+                            "items": {
+                                "type": "array",
+                                "items": { "type": "number" },
+                                "minItems": 2,
+                                "maxItems": 2
+                            },
+                            "minItems": 2,
+                            "description": "Control points as [x, z] in world units, e.g. [[0, 0], [4, 0], [4, 4], [8, 4]]."
+                        },
+                        "kind": {
+                            "type": "string",
+                            "enum": ["catmull_rom", "bezier"],
+                            "description": "'catmull_rom' (default) passes through every point; 'bezier' treats the inner points as pull handles."
+                        },
+                        "closed": {
+                            "type": "boolean",
+                            "description": "Connect the last point back to the first (default false)."
+                        },
+                        "wide_curves": {
+                            "type": "boolean",
+                            "description": "Use road-curve pieces for wide 90-degree turns (default true)."
+                        },
+                        "end_caps": {
+                            "type": "boolean",
+                            "description": "Finish both ends with road-end pieces (default false, ends stay open to connect other roads)."
+                        },
+                        "level": {
+                            "type": "integer",
+                            "description": "Road level for an elevated road (0.5 units per level, default 0). Elevated tiles get bridge pillars."
+                        },
+                        "ramps": {
+                            "type": "boolean",
+                            "description": "With level > 0, turn the first and last 'level' cells into slopes down to the ground (default true). They must be straight."
+                        },
+                        "pillar_spacing": {
+                            "type": "integer",
+                            "description": "Put a pillar under every Nth elevated cell (default 2)."
+                        },
+                        "spawn": {
+                            "type": "boolean",
+                            "description": "Spawn the tiles in the running game (default true). Set false to only get the placements."
+                        }
+                    },
+                    "required": ["points"]
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let points: Vec<[f32; 2]> = args
+            .get("points")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow!("Missing 'points'"))?
+            .iter()
+            .filter_map(|p| {
+                let p = p.as_array()?;
+                Some([p.first()?.as_f64()? as f32, p.get(1)?.as_f64()? as f32])
+            })
+            .collect();
+        if points.len() < 2 {
+            return Err(anyhow!("'points' needs at least two [x, z] pairs"));
+        }
+
+        let kind = match args.get("kind").and_then(|v| v.as_str()) {
+            Some("bezier") => CurveKind::Bezier,
+            Some("catmull_rom") | None => CurveKind::CatmullRom,
+            Some(other) => return Err(anyhow!("Unknown curve kind '{}'", other)),
+        };
+        let flag =
+            |key: &str, default: bool| args.get(key).and_then(|v| v.as_bool()).unwrap_or(default);
+        let options = SplineOptions {
+            kind,
+            closed: flag("closed", false),
+            wide_curves: flag("wide_curves", true),
+            end_caps: flag("end_caps", false),
+            level: args.get("level").and_then(|v| v.as_i64()).unwrap_or(0) as i32,
+            ramps: flag("ramps", true),
+        };
+
+        let placements = spline_to_tiles(&points, &options).map_err(|e| anyhow!(e))?;
+        if placements.is_empty() {
+            return Err(anyhow!("The curve does not leave its starting cell"));
+        }
+
+        let spawn = flag("spawn", true);
+        let mut out = String::new();
+        place_tiles(&placements, spawn, &mut out);
+        support_and_validate(&placements, pillar_spacing(&args), spawn, &mut out);
+        Ok(out)
+    }
+}
@@ -0,0 +1,251 @@
+use crate::scene;
+use crate::tools::{road, Tool};
+use anyhow::{anyhow, Result};
+use axiom_roads::{occupied_cells, scatter, NavGraph, ScatterArea, ScatterOptions, Side};
+use serde_json::{json, Value};
+
+fn parse_xz(value: &Value) -> Option<[f32; 2]> {
+    let p = value.as_array()?;
+    Some([p.first()?.as_f64()? as f32, p.get(1)?.as_f64()? as f32])
+}
+
+fn parse_xz_list(args: &Value, key: &str) -> Option<Vec<[f32; 2]>> {
+    args.get(key)
+        .and_then(|v| v.as_array())
+        .map(|points| points.iter().filter_map(parse_xz).collect())
+}
+
+/// `light-square` and `light-square.glb` both name the same model.
+fn asset_file(name: &str) -> String {
+    let name = name.trim();
+    if name.ends_with(".glb") || name.ends_with(".gltf") {
+        name.to_string()
+    } else {
+        format!("{}.glb", name)
+    }
+}
+
+fn parse_area(args: &Value) -> Result<ScatterArea> {
+    if let Some(points) = parse_xz_list(args, "path") {
+        return Ok(ScatterArea::Path(points));
+    }
+    if let Some(road) = args.get("along_road") {
+        let point = |key: &str| -> Result<[f32; 3]> {
+            let p = road
+                .get(key)
+                .and_then(parse_xz)
+                .ok_or_else(|| anyhow!("along_road needs '{}' as [x, z]", key))?;
+            Ok([p[0], 0.0, p[1]])
+        };
+        let (from, to) = (point("from")?, point("to")?);
+        let graph = NavGraph::build(&scene::tile_placements());
+        let path = graph
+            .find_path(from, to)
+            .ok_or_else(|| anyhow!("No road connects {:?} and {:?}", from, to))?;
+        return Ok(ScatterArea::Path(
+            path.waypoints.iter().map(|p| [p[0], p[2]]).collect(),
+        ));
+    }
+    if let Some(points) = parse_xz_list(args, "polygon") {
+        return Ok(ScatterArea::Polygon(points));
+    }
+    if let Some(rect) = args.get("rect") {
+        let corner = |key: &str| {
+            rect.get(key)
+                .and_then(parse_xz)
+                .ok_or_else(|| anyhow!("rect needs '{}' as [x, z]", key))
+        };
+        return Ok(ScatterArea::Rect {
+            min: corner("min")?,
+            max: corner("max")?,
+        });
+    }
+    Err(anyhow!(
+        "Give one of 'path', 'along_road', 'polygon' or 'rect'"
+    ))
+}
+
+/// Tool that places many props at once along a road or over an area.
+pub struct ScatterTool;
+
+impl Tool for ScatterTool {
+    fn name(&self) -> String {
+        "scatter".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Scatter props along a road path or inside a polygon/rectangle with spacing, jitter and a seeded RNG, avoiding occupied cells.".to_string()
+    }
+
+    fn requires(&self) -> &'static [&'static str] {
+        &["upload"]
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "scatter",
+                "description": "Place many props in one call instead of one bevy_upload_asset per item, e.g. street lights every 3 units along a road or cones around a construction site. Give exactly one of 'path', 'along_road', 'polygon' or 'rect'. Cells already holding a road, pillar or prop are skipped. The same seed always gives the same layout.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "assets": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Models to pick from at random, e.g. [\"construction-cone\", \"construction-barrier\"]. '.glb' is optional."
+                        },
+                        "path": {
+                            "type": "array",
+                            "items": { "type": "array", "items": { "type": "number" }, "minItems": 2, "maxItems": 2 },
+                            "description": "Polyline of [x, z] points to place items along."
+                        },
+                        "along_road": {
+                            "type": "object",
+                            "properties": {
+                                "from": { "type": "array", "items": { "type": "number" } },
+                                "to": { "type": "array", "items": { "type": "number" } }
+                            },
+                            "description": "Follow the placed roads from one [x, z] point to another (shortest drivable path)."
+                        },
+                        "polygon": {
+                            "type": "array",
+                            "items": { "type": "array", "items": { "type": "number" }, "minItems": 2, "maxItems": 2 },
+                            "description": "Area outline as [x, z] points."
+                        },
+                        "rect": {
+                            "type": "object",
+                            "properties": {
+                                "min": { "type": "array", "items": { "type": "number" } },
+                                "max": { "type": "array", "items": { "type": "number" } }
+                            },
+                            "description": "Area as opposite [x, z] corners, edges included."
+                        },
+                        "spacing": {
+                            "type": "number",
+                            "description": "Distance between items along the path, or grid spacing in an area (default 3 along paths, 1 in areas)."
+                        },
+                        "jitter": {
+                            "type": "number",
+                            "description": "Random displacement of up to this many units on X and Z (default 0)."
+                        },
+                        "side": {
+                            "type": "string",
+                            "enum": ["left", "right", "both", "alternate", "center"],
+                            "description": "Path mode: which side of the path, looking along it (default both)."
+                        },
+                        "side_offset": {
+                            "type": "number",
+                            "description": "Path mode: distance from the path centre line (default 0.6, just off a road tile)."
+                        },
+                        "seed": {
+                            "type": "integer",
+                            "description": "RNG seed (default 0). Change it for a different random layout."
+                        },
+                        "random_rotation": {
+                            "type": "boolean",
+                            "description": "Random yaw per item (default false). Otherwise path items turn towards the path."
+                        },
+                        "yaw_offset": {
+                            "type": "number",
+                            "description": "Degrees added to every yaw, for models that should face another way."
+                        },
+                        "max_count": {
+                            "type": "integer",
+                            "description": "Upper limit on placed items (default 200)."
+                        },
+                        "spawn": {
+                            "type": "boolean",
+                            "description": "Spawn the props (default true). Set false to preview the placements."
+                        }
+                    },
+                    "required": ["assets"]
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let assets: Vec<String> = args
+            .get("assets")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow!("Missing 'assets'"))?
+            .iter()
+            .filter_map(|a| a.as_str())
+            .filter(|a| !a.trim().is_empty())
+            .map(asset_file)
+            .collect();
+        let area = parse_area(&args)?;
+
+        let number = |key: &str| args.get(key).and_then(|v| v.as_f64()).map(|v| v as f32);
+        let defaults = ScatterOptions::default();
+        let side = match args.get("side").and_then(|v| v.as_str()) {
+            Some(s) => Side::parse(s).ok_or_else(|| anyhow!("Unknown side '{}'", s))?,
+            None => defaults.side,
+        };
+        let options = ScatterOptions {
+            assets,
+            spacing: number("spacing").unwrap_or(match area {
+                ScatterArea::Path(_) => defaults.spacing,
+                _ => 1.0,
+            }),
+            jitter: number("jitter").unwrap_or(defaults.jitter).max(0.0),
+            side,
+            side_offset: number("side_offset").unwrap_or(defaults.side_offset),
+            seed: args.get("seed").and_then(|v| v.as_u64()).unwrap_or(0),
+            random_yaw: args
+                .get("random_rotation")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            yaw_offset: number("yaw_offset").unwrap_or(0.0),
+            max_count: args
+                .get("max_count")
+                .and_then(|v| v.as_u64())
+                .map_or(defaults.max_count, |n| n as usize),
+            ..defaults
+        };
+
+        let occupied = occupied_cells(&scene::tile_placements());
+        let result = scatter(&area, &options, &occupied).map_err(|e| anyhow!(e))?;
+        if result.placements.is_empty() {
+            return Err(anyhow!(
+                "Nothing could be placed: all {} candidate spots are occupied or outside the area",
+                result.blocked
+            ));
+        }
+
+        let spawn = args.get("spawn").and_then(|v| v.as_bool()).unwrap_or(true);
+        let mut out = format!(
+            "{} props (seed {}):\n",
+            result.placements.len(),
+            options.seed
+        );
+        for p in &result.placements {
+            out.push_str(&format!("- {}\n", road::fmt_placement(p)));
+        }
+        if result.blocked > 0 {
+            out.push_str(&format!(
+                "{} spots skipped because the cell was occupied.\n",
+                result.blocked
+            ));
+        }
+        if result.truncated {
+            out.push_str(&format!("Stopped at max_count {}.\n", options.max_count));
+        }
+
+        if !spawn {
+            out.push_str("Preview only, nothing was spawned.\n");
+            return Ok(out);
+        }
+        let failures = road::spawn_placements(&result.placements);
+        if failures.is_empty() {
+            out.push_str("All props spawned.\n");
+        } else {
+            out.push_str(&format!("{} props failed to spawn:\n", failures.len()));
+            for f in failures {
+                out.push_str(&format!("- {}\n", f));
+            }
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,111 @@
+use crate::schema;
+use crate::tools::Tool;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+/// Most types listed for a search.
+const MAX_SEARCH_RESULTS: usize = 40;
+
+/// Tool that answers "what fields does this component have" from the game's type registry.
+pub struct BevySchemaTool;
+
+impl Tool for BevySchemaTool {
+    fn name(&self) -> String {
+        "bevy_schema".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Look up components and resources in the game's type registry: full type paths, fields and JSON shapes.".to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "bevy_schema",
+                "description": "Look up the exact type path, fields and JSON shape of components/resources in the running game's type registry, e.g. Transform, PointLight or the game's own components. Use this before bevy_rpc instead of guessing paths or field names. Vectors and quaternions are sent as arrays ([x, y, z]), enums as \"Variant\" or {\"Variant\": ..}.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "types": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Short names ('PointLight') or full type paths to describe."
+                        },
+                        "search": {
+                            "type": "string",
+                            "description": "List components/resources whose path contains this text, e.g. 'light' or 'my_game::'."
+                        },
+                        "kind": {
+                            "type": "string",
+                            "enum": ["component", "resource", "any"],
+                            "description": "What search lists. Default 'any'."
+                        },
+                        "refresh": {
+                            "type": "boolean",
+                            "description": "Read the registry again, e.g. after the game was rebuilt with new components."
+                        }
+                    },
+                    "required": []
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let refresh = args
+            .get("refresh")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let registry = schema::registry(refresh)?;
+        let mut out = String::new();
+
+        if let Some(query) = args.get("search").and_then(|v| v.as_str()) {
+            let reflect = match args.get("kind").and_then(|v| v.as_str()).unwrap_or("any") {
+                "component" => Some("Component"),
+                "resource" => Some("Resource"),
+                "any" => None,
+                other => return Err(anyhow!("Unknown kind '{}'", other)),
+            };
+            let found = registry.search(query, reflect);
+            out.push_str(&format!("{} types match '{}':\n", found.len(), query));
+            for t in found.iter().take(MAX_SEARCH_RESULTS) {
+                let kind = if t.reflects("Component") {
+                    "component"
+                } else {
+                    "resource"
+                };
+                out.push_str(&format!("- {} ({}, {})\n", t.short_path, t.type_path, kind));
+            }
+            if found.len() > MAX_SEARCH_RESULTS {
+                out.push_str("... narrow the search to see the rest.\n");
+            }
+        }
+
+        let names: Vec<&str> = args
+            .get("types")
+            .and_then(|v| v.as_array())
+            .map(|names| names.iter().filter_map(|n| n.as_str()).collect())
+            .unwrap_or_default();
+        for name in names {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            match registry.resolve(name) {
+                Ok(t) => {
+                    out.push_str(&registry.describe(t));
+                    out.push_str(&format!("Example: {}\n", registry.example(&t.type_path, 0)));
+                }
+                Err(e) => out.push_str(&format!("{}\n", e)),
+            }
+        }
+
+        if out.is_empty() {
+            out = format!(
+                "The game's registry has {} types. Pass 'types' to describe some or 'search' to find them.",
+                registry.len()
+            );
+        }
+        Ok(out)
+    }
+}
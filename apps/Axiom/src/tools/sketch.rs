@@ -0,0 +1,200 @@
+use crate::tools::road::{place_tiles, support_and_validate};
+use crate::tools::Tool;
+use anyhow::{anyhow, Result};
+use axiom_roads::{layout_from_mask, CellMask};
+use image::RgbaImage;
+use serde_json::{json, Value};
+use std::sync::{Mutex, OnceLock};
+
+// PNG of the last image pasted into the chat, so the agent can refer to it without a path.
+static LAST_PASTED_IMAGE: OnceLock<Mutex<Option<Vec<u8>>>> = OnceLock::new();
+
+fn get_last_pasted() -> &'static Mutex<Option<Vec<u8>>> {
+    LAST_PASTED_IMAGE.get_or_init(|| Mutex::new(None))
+}
+
+pub fn remember_pasted_image(png: Vec<u8>) {
+    if let Ok(mut last) = get_last_pasted().lock() {
+        *last = Some(png);
+    }
+}
+
+/// Marks a grid cell as road when at least `coverage` of its pixels are road-coloured.
+fn threshold_to_mask(
+    img: &RgbaImage,
+    columns: u32,
+    threshold: f32,
+    dark_roads: bool,
+    coverage: f32,
+) -> CellMask {
+    let (width, height) = img.dimensions();
+    let columns = columns.clamp(1, width.max(1));
+    let cell = width as f32 / columns as f32;
+    let rows = ((height as f32 / cell).round() as u32).max(1);
+    let mut mask = CellMask::new(columns as usize, rows as usize);
+
+    for row in 0..rows {
+        for col in 0..columns {
+            let x0 = (col as f32 * cell) as u32;
+            let x1 = (((col + 1) as f32 * cell) as u32).min(width).max(x0 + 1);
+            let y0 = (row as f32 * cell) as u32;
+            let y1 = (((row + 1) as f32 * cell) as u32).min(height).max(y0 + 1);
+
+            let mut road = 0u32;
+            let mut total = 0u32;
+            for y in y0..y1.min(height) {
+                for x in x0..x1.min(width) {
+                    let [r, g, b, a] = img.get_pixel(x, y).0;
+                    total += 1;
+                    // Transparent pixels are background, whatever their colour.
+                    if a < 128 {
+                        continue;
+                    }
+                    let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+                    if (luma < threshold) == dark_roads {
+                        road += 1;
+                    }
+                }
+            }
+            if total > 0 && road as f32 / total as f32 >= coverage {
+                mask.set(col as usize, row as usize, true);
+            }
+        }
+    }
+    mask
+}
+
+/// Tool that converts a top-down road sketch or screenshot into a tile layout.
+pub struct RoadSketchTool;
+
+impl Tool for RoadSketchTool {
+    fn name(&self) -> String {
+        "road_from_sketch".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Threshold a top-down road image onto the grid and build the matching road tiles, so the layout does not depend on reading coordinates off the picture.".to_string()
+    }
+
+    fn requires(&self) -> &'static [&'static str] {
+        &["upload"]
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "road_from_sketch",
+                "description": "Build roads from a top-down sketch, map or screenshot. The image is split into 'columns' grid cells across (top of the image is North / -Z), cells covered by road strokes are detected and every road cell gets the straight/bend/T/crossroad piece that matches its neighbours. Preview with spawn=false first and check the detected grid.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "image_path": {
+                            "type": "string",
+                            "description": "Path to the image. Omit to use the image last pasted into the chat."
+                        },
+                        "columns": {
+                            "type": "integer",
+                            "description": "Number of grid cells across the image width (default 24). Rows follow from the aspect ratio."
+                        },
+                        "origin": {
+                            "type": "array",
+                            "items": { "type": "integer" },
+                            "minItems": 2,
+                            "maxItems": 2,
+                            "description": "[x, z] world cell of the image's top-left cell (default [0, 0])."
+                        },
+                        "roads_are": {
+                            "type": "string",
+                            "enum": ["dark", "light"],
+                            "description": "Whether roads are drawn darker (default, pen on paper) or lighter than the background."
+                        },
+                        "threshold": {
+                            "type": "number",
+                            "description": "Brightness cut-off 0-255 between road and background (default 128)."
+                        },
+                        "coverage": {
+                            "type": "number",
+                            "description": "Fraction of a cell's pixels that must be road for the cell to count (default 0.3)."
+                        },
+                        "thin": {
+                            "type": "boolean",
+                            "description": "Thin thick strokes to one-cell-wide roads (default true)."
+                        },
+                        "spawn": {
+                            "type": "boolean",
+                            "description": "Spawn the tiles in the running game (default true)."
+                        }
+                    },
+                    "required": []
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let img = match args.get("image_path").and_then(|v| v.as_str()) {
+            Some(path) => {
+                image::open(path).map_err(|e| anyhow!("Failed to open image {}: {}", path, e))?
+            }
+            None => {
+                let png = get_last_pasted()
+                    .lock()
+                    .ok()
+                    .and_then(|last| last.clone())
+                    .ok_or_else(|| {
+                        anyhow!("No image_path given and no image has been pasted into the chat")
+                    })?;
+                image::load_from_memory(&png)?
+            }
+        }
+        .to_rgba8();
+
+        let columns = args.get("columns").and_then(|v| v.as_u64()).unwrap_or(24) as u32;
+        let threshold = args
+            .get("threshold")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(128.0) as f32;
+        let coverage = args.get("coverage").and_then(|v| v.as_f64()).unwrap_or(0.3) as f32;
+        let dark_roads = args.get("roads_are").and_then(|v| v.as_str()) != Some("light");
+        let thin = args.get("thin").and_then(|v| v.as_bool()).unwrap_or(true);
+        let spawn = args.get("spawn").and_then(|v| v.as_bool()).unwrap_or(true);
+        let origin = args
+            .get("origin")
+            .and_then(|v| v.as_array())
+            .map(|o| {
+                let at = |i: usize| o.get(i).and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+                (at(0), at(1))
+            })
+            .unwrap_or((0, 0));
+
+        let mut mask = threshold_to_mask(&img, columns, threshold, dark_roads, coverage);
+        if thin {
+            mask = mask.thinned();
+        }
+        let mask = mask.four_connected();
+
+        let placements = layout_from_mask(&mask, origin);
+        if placements.is_empty() {
+            return Err(anyhow!(
+                "No connected road cells found ({} of {}x{} cells matched). Try another threshold, 'roads_are' or fewer columns.",
+                mask.count(),
+                mask.width,
+                mask.height
+            ));
+        }
+
+        let mut out = format!(
+            "Detected road grid ({}x{} cells, top-left at [{}, {}]):\n{}",
+            mask.width,
+            mask.height,
+            origin.0,
+            origin.1,
+            mask.to_ascii()
+        );
+        place_tiles(&placements, spawn, &mut out);
+        // Sketched roads are all on the ground, so this only checks for collisions.
+        support_and_validate(&placements, 2, spawn, &mut out);
+        Ok(out)
+    }
+}
@@ -0,0 +1,311 @@
+use crate::brp;
+use crate::connection::{self, LinkState};
+use crate::targets::{self, GameTarget};
+use crate::tools::Tool;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+/// Wraps a Bevy tool so it takes an optional `target`: one game instance, or `all` to run the
+/// same call on every connected instance and collect the results.
+pub struct Targeted<T: Tool> {
+    tool: T,
+    broadcast: bool,
+    /// The channel's target when the turn started, for calls that name none. Switching
+    /// channels mid-turn does not redirect the rest of the turn.
+    target: String,
+}
+
+impl<T: Tool> Targeted<T> {
+    pub fn new(tool: T, target: &str) -> Self {
+        Self {
+            tool,
+            broadcast: true,
+            target: target.to_string(),
+        }
+    }
+
+    /// For tools whose result only makes sense for one game at a time.
+    pub fn single(tool: T, target: &str) -> Self {
+        Self {
+            tool,
+            broadcast: false,
+            target: target.to_string(),
+        }
+    }
+
+    fn run(&self, target: &GameTarget, args: Value) -> Result<String> {
+        let missing = connection::missing(&target.endpoint, self.tool.requires());
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "{} is not supported by game '{}': its bevy_ai_remote lacks {}",
+                self.tool.name(),
+                target.name,
+                missing.join(", ")
+            ));
+        }
+        brp::with_endpoint(&target.endpoint, || self.tool.execute(args))
+    }
+}
+
+impl<T: Tool> Tool for Targeted<T> {
+    fn name(&self) -> String {
+        self.tool.name()
+    }
+
+    fn description(&self) -> String {
+        self.tool.description()
+    }
+
+    fn requires(&self) -> &'static [&'static str] {
+        self.tool.requires()
+    }
+
+    fn uses_game(&self) -> bool {
+        true
+    }
+
+    fn schema(&self) -> Value {
+        let mut schema = self.tool.schema();
+        let description = if self.broadcast {
+            format!("Game instance to run this on: a name from game_targets, or '{}' to run it on every connected instance. Default: the channel's target.", targets::BROADCAST)
+        } else {
+            "Game instance to run this on, a name from game_targets. Default: the channel's target."
+                .to_string()
+        };
+        if let Some(properties) = schema
+            .pointer_mut("/function/parameters/properties")
+            .and_then(|p| p.as_object_mut())
+        {
+            properties.insert(
+                "target".to_string(),
+                json!({ "type": "string", "description": description }),
+            );
+        }
+        schema
+    }
+
+    fn execute(&self, mut args: Value) -> Result<String> {
+        let requested = args
+            .as_object_mut()
+            .and_then(|args| args.remove("target"))
+            .and_then(|t| t.as_str().map(str::to_string))
+            .filter(|t| !t.trim().is_empty());
+        let target = requested.unwrap_or_else(|| self.target.clone());
+        let resolved = targets::resolve(&target)?;
+
+        if !target.eq_ignore_ascii_case(targets::BROADCAST) {
+            return self.run(&resolved[0], args);
+        }
+        if !self.broadcast {
+            return Err(anyhow!(
+                "{} works on one game at a time, pass a target name ({})",
+                self.tool.name(),
+                resolved
+                    .iter()
+                    .map(|t| t.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        let (reachable, offline): (Vec<GameTarget>, Vec<GameTarget>) = resolved
+            .into_iter()
+            .partition(|t| connection::status_of(&t.endpoint).state != LinkState::Offline);
+        if reachable.is_empty() {
+            return Err(anyhow!("No game target is connected, nothing was run"));
+        }
+
+        // One game after the other: the calls share the scene mirror and the BRP endpoint.
+        let mut sections = Vec::new();
+        let mut failures = 0;
+        for target in &reachable {
+            let result = match self.run(target, args.clone()) {
+                Ok(output) => output,
+                Err(e) => {
+                    failures += 1;
+                    format!("Error: {}", e)
+                }
+            };
+            sections.push(format!(
+                "### {} ({})\n{}",
+                target.name, target.endpoint, result
+            ));
+        }
+
+        let mut out = format!(
+            "Broadcast to {} game target(s), {} failed.",
+            reachable.len(),
+            failures
+        );
+        if !offline.is_empty() {
+            let names: Vec<&str> = offline.iter().map(|t| t.name.as_str()).collect();
+            out.push_str(&format!(" Skipped offline: {}.", names.join(", ")));
+        }
+        out.push_str("\n\n");
+        out.push_str(&sections.join("\n\n"));
+        if failures == reachable.len() {
+            return Err(anyhow!(out));
+        }
+        Ok(out)
+    }
+}
+
+/// Tool that lists, adds and removes the game instances the Bevy tools can target.
+pub struct GameTargetsTool;
+
+impl Tool for GameTargetsTool {
+    fn name(&self) -> String {
+        "game_targets".to_string()
+    }
+
+    fn description(&self) -> String {
+        "List, add or remove the named game instances Bevy tools can target.".to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "game_targets",
+                "description": "List the named game instances (BRP endpoints) with their connection status, or add/remove one. Every Bevy tool takes an optional 'target' naming one of them, or 'all' to run the same call on every connected instance.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "action": {
+                            "type": "string",
+                            "enum": ["list", "add", "remove"],
+                            "description": "Default 'list'."
+                        },
+                        "name": { "type": "string", "description": "Target name, for add and remove." },
+                        "endpoint": { "type": "string", "description": "For add: 'http://127.0.0.1:15722', '127.0.0.1:15722' or just the port." }
+                    },
+                    "required": []
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let action = args
+            .get("action")
+            .and_then(|v| v.as_str())
+            .unwrap_or("list");
+        let name = args.get("name").and_then(|v| v.as_str());
+
+        let mut out = String::new();
+        match action {
+            "list" => {}
+            "add" => {
+                let name = name.ok_or_else(|| anyhow!("Missing 'name'"))?;
+                let endpoint = args
+                    .get("endpoint")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing 'endpoint'"))?;
+                let target = targets::add(name, endpoint)?;
+                brp::block_on(connection::check_target(&target));
+                out.push_str(&format!(
+                    "Added '{}' at {}.\n",
+                    target.name, target.endpoint
+                ));
+            }
+            "remove" => {
+                let name = name.ok_or_else(|| anyhow!("Missing 'name'"))?;
+                let target = targets::remove(name)?;
+                out.push_str(&format!("Removed '{}'.\n", target.name));
+            }
+            other => return Err(anyhow!("Unknown action '{}'", other)),
+        }
+
+        let selected = targets::selected();
+        out.push_str(&format!("Channel target: {}\n", selected));
+        for (target, status) in connection::all() {
+            let state = match status.state {
+                LinkState::Unknown => "not checked yet".to_string(),
+                LinkState::Online => match (&status.game, status.latency) {
+                    (Some(game), Some(latency)) => {
+                        format!("online, {} ms, {}", latency.as_millis(), game.protocol)
+                    }
+                    _ => "online".to_string(),
+                },
+                LinkState::Offline => format!(
+                    "offline: {}",
+                    status.last_error.as_deref().unwrap_or("no answer")
+                ),
+            };
+            out.push_str(&format!(
+                "- {} {} ({})\n",
+                target.name, target.endpoint, state
+            ));
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brp::mock::MockBrp;
+    use crate::scene;
+    use crate::tools::bevy::BevyUploadAssetTool;
+    use std::net::TcpListener;
+
+    #[test]
+    fn broadcast_runs_on_every_connected_game_and_skips_offline_ones() {
+        let first = MockBrp::start();
+        let second = first.another();
+        let closed_port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        targets::add("mock-first", first.url()).unwrap();
+        targets::add("mock-second", second.url()).unwrap();
+        targets::add("mock-down", &closed_port.to_string()).unwrap();
+        brp::block_on(connection::check());
+
+        let model = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/models/road-straight.glb");
+        let tool = Targeted::new(BevyUploadAssetTool, "mock-first");
+        let args = json!({ "local_path": model, "translation": [0.0, 0.0, 0.0] });
+
+        let mut all = args.clone();
+        all["target"] = json!("all");
+        let out = tool.execute(all).unwrap();
+        assert!(out.contains("### mock-first"), "{}", out);
+        assert!(out.contains("### mock-second"), "{}", out);
+        assert!(out.contains("Skipped offline:"), "{}", out);
+        assert!(out.contains("mock-down"), "{}", out);
+        for game in [&first, &second] {
+            let mirror = brp::with_endpoint(game.url(), scene::snapshot);
+            assert_eq!(mirror.len(), 1, "each game keeps its own scene mirror");
+            assert!(game.methods().contains(&"world.spawn_entity".to_string()));
+        }
+
+        // A named target only reaches that game.
+        let spawns = |game: &MockBrp| {
+            game.methods()
+                .iter()
+                .filter(|m| *m == "world.spawn_entity")
+                .count()
+        };
+        let before = (spawns(&first), spawns(&second));
+        let mut one = args.clone();
+        one["target"] = json!("mock-second");
+        tool.execute(one).unwrap();
+        assert_eq!(spawns(&first), before.0);
+        assert!(spawns(&second) > before.1);
+
+        // Without a target the call stays on the one captured when the turn started, even
+        // after another channel selected a different game.
+        targets::select("mock-second").unwrap();
+        let before = (spawns(&first), spawns(&second));
+        tool.execute(args.clone()).unwrap();
+        assert!(spawns(&first) > before.0);
+        assert_eq!(spawns(&second), before.1);
+        targets::select("").unwrap();
+
+        for name in ["mock-first", "mock-second", "mock-down"] {
+            targets::remove(name).unwrap();
+        }
+    }
+}
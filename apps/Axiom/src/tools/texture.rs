@@ -0,0 +1,89 @@
+use crate::texture::{self, TextureOptions};
+use crate::tools::{bevy, Tool};
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+/// Tool to shrink and re-encode a texture the way model uploads do.
+pub struct OptimizeTextureTool;
+
+impl Tool for OptimizeTextureTool {
+    fn name(&self) -> String {
+        "optimize_texture".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Downscale and re-encode a PNG/JPEG texture, with a cache of processed results.".to_string()
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "optimize_texture",
+                "description": "Downscale a PNG/JPEG texture to a maximum resolution and re-encode it (same format). Textures referenced by uploaded models already go through this automatically with the defaults; use this tool to check the savings, try other settings or write an optimised copy.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "local_path": {
+                            "type": "string",
+                            "description": "Texture to process. Resolves like bevy_upload_asset."
+                        },
+                        "max_size": {
+                            "type": "integer",
+                            "description": "Longest side in pixels (default AXIOM_TEXTURE_MAX_SIZE or 1024)."
+                        },
+                        "jpeg_quality": {
+                            "type": "integer",
+                            "description": "JPEG quality 1-100 (default 85). Ignored for PNG."
+                        },
+                        "output_path": {
+                            "type": "string",
+                            "description": "Optional file to write the optimised texture to."
+                        }
+                    },
+                    "required": ["local_path"]
+                }
+            }
+        })
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let local_path = args
+            .get("local_path")
+            .and_then(|v| v.as_str())
+            .ok_or(anyhow!("Missing local_path"))?;
+        let path = bevy::resolve_asset_path(local_path)?;
+        if !texture::is_optimizable(&path) {
+            return Err(anyhow!(
+                "Only .png, .jpg and .jpeg textures can be optimised"
+            ));
+        }
+
+        let mut options = TextureOptions::default();
+        if let Some(max_size) = args.get("max_size").and_then(|v| v.as_u64()) {
+            options.max_size = max_size as u32;
+        }
+        if let Some(quality) = args.get("jpeg_quality").and_then(|v| v.as_u64()) {
+            options.jpeg_quality = quality.clamp(1, 100) as u8;
+        }
+
+        let optimized = texture::optimize_file(&path, options)?;
+        let mut out = format!(
+            "{}: {}x{} ({:.1} KB) -> {}x{} ({:.1} KB){}",
+            path.display(),
+            optimized.original_size.0,
+            optimized.original_size.1,
+            optimized.original_bytes as f64 / 1024.0,
+            optimized.size.0,
+            optimized.size.1,
+            optimized.bytes.len() as f64 / 1024.0,
+            if optimized.cached { ", from cache" } else { "" }
+        );
+        if let Some(output) = args.get("output_path").and_then(|v| v.as_str()) {
+            std::fs::write(output, &optimized.bytes)
+                .map_err(|e| anyhow!("Failed to write {}: {}", output, e))?;
+            out.push_str(&format!("\nWritten to {}", output));
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,382 @@
+use crate::arrange::{self, AlignTarget, Axis};
+use crate::scene::{self, PlacedAsset};
+use crate::tools::{bevy, query, Tool};
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use serde_json::{json, Value};
+
+fn vec3(args: &Value, key: &str) -> Option<[f32; 3]> {
+    let v = args.get(key)?;
+    if let Some(n) = v.as_f64() {
+        return Some([n as f32; 3]);
+    }
+    let a = v.as_array()?;
+    let at = |i: usize| a.get(i).and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+    Some([at(0), at(1), at(2)])
+}
+
+fn number(args: &Value, key: &str) -> Option<f32> {
+    args.get(key).and_then(|v| v.as_f64()).map(|v| v as f32)
+}
+
+fn fmt_asset(a: &PlacedAsset) -> String {
+    let mut out = match a.entity {
+        Some(e) => format!("{} {}", e, a.filename),
+        None => a.filename.clone(),
+    };
+    if let Some(label) = &a.label {
+        out.push_str(&format!(" '{}'", label));
+    }
+    out.push_str(&format!(
+        " at {:?} rotation {:?}",
+        a.translation, a.rotation
+    ));
+    if a.scale != [1.0; 3] {
+        out.push_str(&format!(" scale {:?}", a.scale));
+    }
+    out
+}
+
+/// Sends the new transforms of the items that changed. Returns a line per item.
+fn apply(before: &[PlacedAsset], after: &[PlacedAsset]) -> Vec<String> {
+    before
+        .par_iter()
+        .zip(after.par_iter())
+        .filter(|(old, new)| {
+            old.translation != new.translation
+                || old.rotation != new.rotation
+                || old.scale != new.scale
+        })
+        .map(|(_, new)| {
+            let result = match new.entity {
+                Some(entity) => {
+                    bevy::set_transform(entity, new.translation, new.rotation, new.scale)
+                }
+                None => Err(anyhow!("the game never reported its entity id")),
+            };
+            match result {
+                Ok(()) => format!("- {}", fmt_asset(new)),
+                Err(e) => format!("- {} FAILED: {}", fmt_asset(new), e),
+            }
+        })
+        .collect()
+}
+
+/// Spawns copies made by the array and mirror operations, keeping label, group and scale.
+fn spawn_copies(copies: &[PlacedAsset]) -> Vec<String> {
+    copies
+        .par_iter()
+        .map(|copy| {
+            let spawned = bevy::upload_asset(
+                &copy.local_path.to_string_lossy(),
+                None,
+                copy.translation,
+                copy.rotation,
+            );
+            let entity = match spawned {
+                Ok(spawned) => spawned.entity,
+                Err(e) => return format!("- {} FAILED: {}", fmt_asset(copy), e),
+            };
+            let mut placed = copy.clone();
+            placed.entity = Some(entity);
+            if copy.scale != [1.0; 3] {
+                if let Err(e) =
+                    bevy::set_transform(entity, copy.translation, copy.rotation, copy.scale)
+                {
+                    return format!("- {} (scale not applied: {})", fmt_asset(&placed), e);
+                }
+            }
+            scene::update(entity, |asset| {
+                asset.label = copy.label.clone();
+                asset.group = copy.group.clone();
+            });
+            format!("- {}", fmt_asset(&placed))
+        })
+        .collect()
+}
+
+/// Tool for editing the transforms of entities that are already in the scene.
+pub struct EditTransformsTool;
+
+impl Tool for EditTransformsTool {
+    fn name(&self) -> String {
+        "edit_transforms".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Move, rotate, scale, snap, align, distribute, mirror or array spawned entities selected by id, label, group or scene query.".to_string()
+    }
+
+    fn requires(&self) -> &'static [&'static str] {
+        &["upload"]
+    }
+
+    fn schema(&self) -> Value {
+        query::with_selectors(json!({
+            "type": "function",
+            "function": {
+                "name": "edit_transforms",
+                "description": "Edit entities that were already spawned, instead of despawning and re-uploading them or crafting bevy_rpc payloads. Select with 'ids', 'labels' (or model names like 'light-square') and/or 'groups' (set via bevy_upload_asset), or with a 'where' query as in query_scene. Rotations are Euler degrees like bevy_upload_asset. 'mirror' with copy=true and the array operations spawn new copies.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "operation": {
+                            "type": "string",
+                            "enum": ["move", "rotate", "scale", "snap", "align", "distribute", "mirror", "array_linear", "array_radial"],
+                            "description": "move: by 'offset' or centre to 'to'. rotate: add 'rotation'. scale: multiply by 'scale'. snap: round positions to 'grid' (and yaw to 'angle'). align: same 'axis' coordinate ('align_to'). distribute: even spacing on 'axis'. mirror: reflect across the plane 'axis' = 'at'. array_linear: 'count' copies stepping by 'offset'. array_radial: 'count' copies around 'center' over 'angle' degrees."
+                        },
+                        "offset": {
+                            "type": "array",
+                            "items": { "type": "number" },
+                            "description": "[x, y, z] for move and array_linear."
+                        },
+                        "to": {
+                            "type": "array",
+                            "items": { "type": "number" },
+                            "description": "move: put the selection's centre here instead of using 'offset'."
+                        },
+                        "rotation": {
+                            "type": "array",
+                            "items": { "type": "number" },
+                            "description": "rotate: [x, y, z] degrees to add."
+                        },
+                        "scale": {
+                            "description": "scale: factor, a number or [x, y, z]."
+                        },
+                        "absolute": {
+                            "type": "boolean",
+                            "description": "rotate/scale: set the value instead of adding to it (default false)."
+                        },
+                        "pivot": {
+                            "description": "rotate/scale: 'each' (default, in place), 'center' (around the selection centre) or [x, y, z]."
+                        },
+                        "grid": {
+                            "type": "number",
+                            "description": "snap: grid size (default 1)."
+                        },
+                        "angle": {
+                            "type": "number",
+                            "description": "snap: yaw step in degrees. array_radial: total angle (default 360)."
+                        },
+                        "axis": {
+                            "type": "string",
+                            "enum": ["x", "y", "z"],
+                            "description": "align, distribute and mirror."
+                        },
+                        "align_to": {
+                            "description": "align: 'min', 'max', 'center' (default) or a coordinate."
+                        },
+                        "spacing": {
+                            "type": "number",
+                            "description": "distribute: fixed spacing from the first item instead of spreading between the ends."
+                        },
+                        "at": {
+                            "type": "number",
+                            "description": "mirror: plane coordinate (default the selection centre)."
+                        },
+                        "copy": {
+                            "type": "boolean",
+                            "description": "mirror: spawn mirrored copies and keep the originals (default false)."
+                        },
+                        "count": {
+                            "type": "integer",
+                            "description": "Arrays: number of new copies."
+                        },
+                        "rotation_step": {
+                            "type": "number",
+                            "description": "array_linear: extra yaw per copy in degrees."
+                        },
+                        "center": {
+                            "type": "array",
+                            "items": { "type": "number" },
+                            "description": "array_radial: [x, y, z] point to revolve around (required)."
+                        },
+                        "turn_items": {
+                            "type": "boolean",
+                            "description": "array_radial: rotate each copy with the circle (default true)."
+                        }
+                    },
+                    "required": ["operation"]
+                }
+            }
+        }))
+    }
+
+    fn execute(&self, args: Value) -> Result<String> {
+        let operation = args
+            .get("operation")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("Missing 'operation'"))?;
+        let before = query::select(&args)?;
+        let mut after = before.clone();
+        let axis = || {
+            let name = args.get("axis").and_then(|v| v.as_str()).unwrap_or("");
+            Axis::parse(name).ok_or_else(|| anyhow!("'{}' needs 'axis' x, y or z", operation))
+        };
+        let pivot = match args.get("pivot") {
+            Some(Value::String(s)) if s == "center" => Some(arrange::center(&before)),
+            Some(Value::Array(_)) => vec3(&args, "pivot"),
+            _ => None,
+        };
+        let absolute = args
+            .get("absolute")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let count = || match args.get("count").and_then(|v| v.as_u64()) {
+            Some(n) if (1..=500).contains(&n) => Ok(n as usize),
+            _ => Err(anyhow!("'{}' needs 'count' between 1 and 500", operation)),
+        };
+
+        let mut copies = Vec::new();
+        match operation {
+            "move" => {
+                let offset = match (vec3(&args, "to"), vec3(&args, "offset")) {
+                    (Some(to), _) => {
+                        let c = arrange::center(&before);
+                        [to[0] - c[0], to[1] - c[1], to[2] - c[2]]
+                    }
+                    (None, Some(offset)) => offset,
+                    (None, None) => return Err(anyhow!("'move' needs 'offset' or 'to'")),
+                };
+                arrange::translate(&mut after, offset);
+            }
+            "rotate" => {
+                let rotation =
+                    vec3(&args, "rotation").ok_or_else(|| anyhow!("'rotate' needs 'rotation'"))?;
+                if absolute {
+                    after.iter_mut().for_each(|a| a.rotation = rotation);
+                } else {
+                    arrange::rotate(&mut after, rotation, pivot);
+                }
+            }
+            "scale" => {
+                let factor =
+                    vec3(&args, "scale").ok_or_else(|| anyhow!("'scale' needs 'scale'"))?;
+                if factor.iter().any(|f| *f <= 0.0) {
+                    return Err(anyhow!("Scale factors must be positive"));
+                }
+                if absolute {
+                    after.iter_mut().for_each(|a| a.scale = factor);
+                } else {
+                    arrange::scale(&mut after, factor, pivot);
+                }
+            }
+            "snap" => arrange::snap(
+                &mut after,
+                number(&args, "grid").unwrap_or(1.0),
+                number(&args, "angle"),
+            ),
+            "align" => {
+                let target = match args.get("align_to") {
+                    Some(Value::Number(n)) => AlignTarget::Value(n.as_f64().unwrap_or(0.0) as f32),
+                    Some(Value::String(s)) if s == "min" => AlignTarget::Min,
+                    Some(Value::String(s)) if s == "max" => AlignTarget::Max,
+                    _ => AlignTarget::Center,
+                };
+                arrange::align(&mut after, axis()?, target);
+            }
+            "distribute" => arrange::distribute(&mut after, axis()?, number(&args, "spacing")),
+            "mirror" => {
+                let axis = axis()?;
+                let at = number(&args, "at").unwrap_or(arrange::center(&before)[axis.index()]);
+                arrange::mirror(&mut after, axis, at);
+                if args.get("copy").and_then(|v| v.as_bool()).unwrap_or(false) {
+                    copies = after
+                        .into_iter()
+                        .map(|a| PlacedAsset { entity: None, ..a })
+                        .collect();
+                    after = before.clone();
+                }
+            }
+            "array_linear" => {
+                let offset = vec3(&args, "offset")
+                    .ok_or_else(|| anyhow!("'array_linear' needs 'offset'"))?;
+                copies = arrange::linear_array(
+                    &before,
+                    count()?,
+                    offset,
+                    number(&args, "rotation_step").unwrap_or(0.0),
+                );
+            }
+            "array_radial" => {
+                let center = vec3(&args, "center")
+                    .ok_or_else(|| anyhow!("'array_radial' needs 'center'"))?;
+                copies = arrange::radial_array(
+                    &before,
+                    count()?,
+                    center,
+                    number(&args, "angle").unwrap_or(360.0),
+                    args.get("turn_items")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(true),
+                );
+            }
+            other => return Err(anyhow!("Unknown operation '{}'", other)),
+        }
+
+        let mut out = format!("{}: {} selected.\n", operation, before.len());
+        let changed = apply(&before, &after);
+        if !changed.is_empty() {
+            out.push_str(&format!(
+                "{} updated:\n{}\n",
+                changed.len(),
+                changed.join("\n")
+            ));
+        } else if copies.is_empty() {
+            out.push_str("Nothing changed.\n");
+        }
+        if !copies.is_empty() {
+            let spawned = spawn_copies(&copies);
+            out.push_str(&format!(
+                "{} copies:\n{}\n",
+                spawned.len(),
+                spawned.join("\n")
+            ));
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brp;
+    use crate::brp::mock::{model, MockBrp};
+
+    #[test]
+    fn moves_selection_and_spawns_radial_copies() {
+        let game = MockBrp::start();
+        for x in [0.0, 2.0] {
+            bevy::upload_asset(&model("light-square.glb"), None, [x, 0.0, 0.0], [0.0; 3]).unwrap();
+        }
+
+        EditTransformsTool
+            .execute(json!({
+                "operation": "move",
+                "where": "asset = light-square",
+                "offset": [0.0, 0.0, 5.0]
+            }))
+            .unwrap();
+        for asset in scene::snapshot() {
+            let stored = game.entity(asset.entity.unwrap()).unwrap();
+            assert_eq!(stored[brp::TRANSFORM]["translation"][2], json!(5.0));
+        }
+
+        EditTransformsTool
+            .execute(json!({
+                "operation": "array_radial",
+                "labels": ["light-square"],
+                "count": 3,
+                "center": [1.0, 0.0, 5.0]
+            }))
+            .unwrap();
+        assert_eq!(scene::snapshot().len(), 8);
+        assert_eq!(
+            game.methods()
+                .iter()
+                .filter(|m| *m == "world.insert_components")
+                .count(),
+            2
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::llm::MessageContent;
+use crate::llm::{MessageContent, Usage};
 
 #[derive(Clone, Debug)]
 pub struct ChannelState {
@@ -6,6 +6,7 @@ pub struct ChannelState {
     pub name: String,                           // Display Name (e.g., "🌐 Global", "🦀 Backend")
     pub history: Vec<(String, MessageContent)>, // The chat history for this channel
     pub assigned_agents: Vec<String>,           // List of Agent Names assigned to this channel
+    pub target: String, // Game target the Bevy tools act on ("all" broadcasts, empty = first)
 }
 
 impl Default for ChannelState {
@@ -15,6 +16,7 @@ impl Default for ChannelState {
             name: "🌐 Global".to_string(),
             history: Vec::new(),
             assigned_agents: Vec::new(), // Global usually implies all, or dynamic
+            target: String::new(),
         }
     }
 }
@@ -25,6 +27,7 @@ pub struct AgentProfile {
     pub description: String,
     pub avatar_path: String,   // e.g., "bevy.png"
     pub model: String,         // e.g., "gemini-pro"
+    pub provider: String,      // "openai", "gemini", "anthropic", "ollama" (see llm::PROVIDERS)
     pub research_mode: String, // "Fast", "Smart Hybrid", "Deep Research"
     pub context_mode: String,  // "General", "Bevy", "Pokemon"
     pub system_prompt: String, // The actual prompt
@@ -37,6 +40,7 @@ impl Default for AgentProfile {
             description: "Default AI Assistant".to_string(),
             avatar_path: "system.png".to_string(),
             model: "gemini-2.5-flash".to_string(),
+            provider: crate::llm::default_provider(),
             research_mode: "Smart Hybrid".to_string(),
             context_mode: "General".to_string(),
             system_prompt: "".to_string(),
@@ -51,4 +55,17 @@ pub enum AsyncMessage {
     Done,
     Log(String),
     Error(String),
+    /// PNG of a freshly rendered scene minimap, for the minimap panel.
+    Minimap(Vec<u8>),
+    /// Why the model stopped answering, as the provider reported it.
+    Finish(String),
+    /// Tokens the model's last answer used.
+    Usage(Usage),
+}
+
+/// How the model's last answer ended, shown in the top panel.
+#[derive(Clone, Debug, Default)]
+pub struct TurnStatus {
+    pub finish: Option<String>,
+    pub usage: Option<Usage>,
 }
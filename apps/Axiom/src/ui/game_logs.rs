@@ -0,0 +1,100 @@
+use crate::game::{LogLine, LogStream, ProcessStatus};
+use eframe::egui;
+use std::time::Duration;
+
+pub enum GameLogsAction {
+    Start,
+    Stop,
+    Restart,
+    Clear,
+    None,
+}
+
+fn line_color(ui: &egui::Ui, line: &LogLine) -> egui::Color32 {
+    let lower = line.text.to_lowercase();
+    if line.stream == LogStream::Axiom {
+        ui.visuals().weak_text_color()
+    } else if lower.starts_with("error") || lower.contains("panicked") || lower.contains(" error ")
+    {
+        egui::Color32::from_rgb(220, 60, 60)
+    } else if lower.starts_with("warning") || lower.contains(" warn ") {
+        egui::Color32::from_rgb(230, 180, 40)
+    } else {
+        ui.visuals().text_color()
+    }
+}
+
+pub fn render_game_logs_window(
+    ctx: &egui::Context,
+    open: &mut bool,
+    target: &str,
+    status: &ProcessStatus,
+    lines: &[LogLine],
+) -> GameLogsAction {
+    let mut action = GameLogsAction::None;
+
+    egui::Window::new("📜 Game")
+        .open(open)
+        .default_width(640.0)
+        .default_height(360.0)
+        .resizable(true)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if status.is_running() {
+                    if ui.button("🔁 Restart").clicked() {
+                        action = GameLogsAction::Restart;
+                    }
+                    if ui.button("⏹ Stop").clicked() {
+                        action = GameLogsAction::Stop;
+                    }
+                } else if ui.button("▶ Start").clicked() {
+                    action = GameLogsAction::Start;
+                }
+                if ui.button("🧹 Clear").clicked() {
+                    action = GameLogsAction::Clear;
+                }
+
+                let state = match (status.pid, status.uptime, &status.exit) {
+                    (Some(pid), Some(uptime), _) => {
+                        format!("running · pid {} · {}s", pid, uptime.as_secs())
+                    }
+                    (None, _, Some(exit)) => exit.clone(),
+                    _ => "not started".to_string(),
+                };
+                ui.label(
+                    egui::RichText::new(format!("{}: {}", target, state))
+                        .small()
+                        .weak(),
+                )
+                .on_hover_text(format!(
+                    "{}\nin {}",
+                    status.command,
+                    status.dir.display()
+                ));
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    if lines.is_empty() {
+                        ui.label("No output yet.");
+                    }
+                    for line in lines {
+                        ui.label(
+                            egui::RichText::new(&line.text)
+                                .monospace()
+                                .color(line_color(ui, line)),
+                        );
+                    }
+                });
+        });
+
+    // Output keeps coming in from the log threads.
+    if status.is_running() {
+        ctx.request_repaint_after(Duration::from_millis(300));
+    }
+
+    action
+}
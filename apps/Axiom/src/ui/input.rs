@@ -1,4 +1,5 @@
 use crate::agent::AgentProfile;
+use crate::llm::PROVIDERS;
 use eframe::egui;
 
 pub enum InputAction {
@@ -6,6 +7,8 @@ pub enum InputAction {
     StopLoading,
     RequestScreenshot,
     ClearPendingImage,
+    /// Backend the current profile should use from now on.
+    SelectProvider(String),
     None,
 }
 
@@ -81,11 +84,33 @@ pub fn render_input_panel(
         });
 
         // Identity label moved to bottom
-        ui.label(
-            egui::RichText::new(format!("🎭 Identity: {}", current_profile.name))
-                .small()
-                .weak(),
-        );
+        ui.horizontal(|ui| {
+            ui.label(
+                egui::RichText::new(format!("🎭 Identity: {}", current_profile.name))
+                    .small()
+                    .weak(),
+            );
+            egui::ComboBox::from_id_salt("llm_provider")
+                .selected_text(
+                    egui::RichText::new(format!(
+                        "{} · {}",
+                        current_profile.provider, current_profile.model
+                    ))
+                    .small(),
+                )
+                .show_ui(ui, |ui| {
+                    for provider in PROVIDERS {
+                        if ui
+                            .selectable_label(current_profile.provider == *provider, *provider)
+                            .clicked()
+                        {
+                            action = InputAction::SelectProvider(provider.to_string());
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("LLM provider and model of this profile");
+        });
     });
 
     // Add some spacing at the bottom to lift it up
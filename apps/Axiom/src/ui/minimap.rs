@@ -0,0 +1,47 @@
+use eframe::egui;
+
+pub enum MinimapAction {
+    Refresh,
+    None,
+}
+
+pub fn render_minimap_window(
+    ctx: &egui::Context,
+    open: &mut bool,
+    texture: &Option<egui::TextureHandle>,
+) -> MinimapAction {
+    let mut action = MinimapAction::None;
+
+    egui::Window::new("🗺️ Minimap")
+        .open(open)
+        .default_width(420.0)
+        .resizable(true)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("🔄 Refresh").clicked() {
+                    action = MinimapAction::Refresh;
+                }
+                ui.label(
+                    egui::RichText::new("Top-down, north up, from the editor's scene mirror")
+                        .small()
+                        .weak(),
+                );
+            });
+            ui.separator();
+
+            match texture {
+                Some(texture) => {
+                    egui::ScrollArea::both().show(ui, |ui| {
+                        let size = texture.size_vec2();
+                        let scale = (ui.available_width() / size.x).min(1.0);
+                        ui.image((texture.id(), size * scale));
+                    });
+                }
+                None => {
+                    ui.label("Nothing placed yet.");
+                }
+            }
+        });
+
+    action
+}
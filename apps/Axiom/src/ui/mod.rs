@@ -1,5 +1,7 @@
 pub mod chat;
 pub mod file_tree;
+pub mod game_logs;
 pub mod input;
+pub mod minimap;
 pub mod sidebar;
 pub mod top_panel;
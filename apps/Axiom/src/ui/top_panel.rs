@@ -1,3 +1,6 @@
+use crate::connection::ConnectionStatus;
+use crate::targets::{GameTarget, BROADCAST};
+use crate::types::TurnStatus;
 use eframe::egui;
 
 pub enum TopPanelAction {
@@ -5,15 +8,98 @@ pub enum TopPanelAction {
     ClearChat,
     // ClearScene, // Hidden per user request
     CopyLog,
+    ToggleMinimap,
+    ToggleGameLogs,
+    CheckConnection,
+    SelectTarget(String),
     None,
 }
 
-pub fn render_top_panel(ui: &mut egui::Ui, active_channel_id: &str) -> TopPanelAction {
+pub fn render_top_panel(
+    ui: &mut egui::Ui,
+    active_channel_id: &str,
+    targets: &[(GameTarget, ConnectionStatus)],
+    selected_target: &str,
+    turn: &TurnStatus,
+) -> TopPanelAction {
     let mut action = TopPanelAction::None;
 
     ui.horizontal(|ui| {
         ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
             ui.heading("Bevy AI Editor");
+
+            ui.add_space(10.0);
+
+            if targets.len() > 1 {
+                let label = if selected_target == BROADCAST {
+                    "📡 All games".to_string()
+                } else {
+                    format!("🎯 {}", selected_target)
+                };
+                egui::ComboBox::from_id_salt("game_target")
+                    .selected_text(label)
+                    .show_ui(ui, |ui| {
+                        for (target, _) in targets {
+                            if ui
+                                .selectable_label(target.name == selected_target, &target.name)
+                                .on_hover_text(&target.endpoint)
+                                .clicked()
+                            {
+                                action = TopPanelAction::SelectTarget(target.name.clone());
+                            }
+                        }
+                        ui.separator();
+                        if ui
+                            .selectable_label(selected_target == BROADCAST, "📡 All (broadcast)")
+                            .clicked()
+                        {
+                            action = TopPanelAction::SelectTarget(BROADCAST.to_string());
+                        }
+                    });
+            }
+
+            // One dot for the channel's game, or one per game when broadcasting.
+            for (target, status) in targets {
+                let text = if selected_target == BROADCAST {
+                    format!("● {}", target.name)
+                } else if target.name == selected_target {
+                    format!("● {}", status.label())
+                } else {
+                    continue;
+                };
+                let indicator = ui
+                    .add(
+                        egui::Label::new(egui::RichText::new(text).color(status.color()))
+                            .sense(egui::Sense::click()),
+                    )
+                    .on_hover_text(status.details());
+                if indicator.clicked() {
+                    action = TopPanelAction::CheckConnection;
+                }
+            }
+
+            // How the last answer ended, e.g. `stop · 1472 in / 89 out`.
+            let mut status = Vec::new();
+            if let Some(finish) = &turn.finish {
+                status.push(finish.clone());
+            }
+            if let Some(usage) = turn.usage {
+                status.push(format!(
+                    "{} in / {} out",
+                    usage.input_tokens, usage.output_tokens
+                ));
+            }
+            if !status.is_empty() {
+                ui.add_space(10.0);
+                let text = egui::RichText::new(status.join(" · "));
+                if turn.finish.as_deref().is_some_and(crate::llm::is_truncated) {
+                    ui.label(text.color(egui::Color32::from_rgb(230, 180, 40)))
+                        .on_hover_text("The answer hit the model's output token limit");
+                } else {
+                    ui.label(text.weak())
+                        .on_hover_text("Finish reason and tokens of the last answer");
+                }
+            }
         });
 
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -32,6 +118,18 @@ pub fn render_top_panel(ui: &mut egui::Ui, active_channel_id: &str) -> TopPanelA
             if ui.button("📋 Copy Log").clicked() {
                 action = TopPanelAction::CopyLog;
             }
+
+            ui.add_space(5.0);
+
+            if ui.button("🗺️ Minimap").clicked() {
+                action = TopPanelAction::ToggleMinimap;
+            }
+
+            ui.add_space(5.0);
+
+            if ui.button("📜 Game").clicked() {
+                action = TopPanelAction::ToggleGameLogs;
+            }
         });
     });
 
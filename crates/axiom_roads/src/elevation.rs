@@ -0,0 +1,238 @@
+use crate::tiles::{asset_stem, cell_center, Cell, Dir, TilePlacement};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Height of one road level. `road-slant-high` climbs exactly one level and a
+/// `bridge-pillar` is exactly one level tall.
+pub const LEVEL_HEIGHT: f32 = 0.5;
+
+const SLOPE_ASSET: &str = "road-slant-high.glb";
+const PILLAR_ASSET: &str = "bridge-pillar.glb";
+const WIDE_PILLAR_ASSET: &str = "bridge-pillar-wide.glb";
+
+pub fn level_y(level: i32) -> f32 {
+    level as f32 * LEVEL_HEIGHT
+}
+
+/// Level a world height belongs to.
+pub fn level_of(y: f32) -> i32 {
+    (y / LEVEL_HEIGHT).round() as i32
+}
+
+pub fn is_pillar(asset: &str) -> bool {
+    asset_stem(asset).starts_with("bridge-pillar")
+}
+
+/// Moves a flat road placement to `level`. Straights above ground become `road-bridge`.
+pub fn at_level(mut placement: TilePlacement, level: i32) -> TilePlacement {
+    placement.translation[1] = level_y(level);
+    if level > 0 && asset_stem(&placement.asset) == "road-straight" {
+        placement.asset = "road-bridge.glb".to_string();
+    }
+    placement
+}
+
+/// A slope in `cell` whose low end sits at `base_level` and which rises towards `uphill`.
+pub fn slope_placement(cell: Cell, uphill: Dir, base_level: i32) -> TilePlacement {
+    // Slopes rise towards +X (East) at rotation 0.
+    let quarter_turns = (0..4u8)
+        .find(|&q| Dir::East.rotate(q) == uphill)
+        .unwrap_or(0);
+    let [x, _, z] = cell_center(cell);
+    TilePlacement::new(
+        SLOPE_ASSET,
+        [x, level_y(base_level), z],
+        quarter_turns as f32 * 90.0,
+    )
+}
+
+/// Pillars to add under elevated roads, and the cells where one could not go.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PillarPlan {
+    pub pillars: Vec<TilePlacement>,
+    /// Elevated cells left without a pillar because a lower road runs underneath.
+    pub blocked: Vec<Cell>,
+}
+
+/// Plans pillar stacks from the ground up to every `spacing`-th elevated cell of `roads`
+/// (by `(x + z) % spacing`, so straight runs get evenly spaced columns).
+///
+/// `world` is everything already placed. Cells with a lower road in them are skipped and
+/// reported, and cells that already have a pillar are left alone. Columns taller than one
+/// level use `bridge-pillar-wide`.
+pub fn plan_pillars(roads: &[TilePlacement], world: &[TilePlacement], spacing: i32) -> PillarPlan {
+    let spacing = spacing.max(1);
+
+    // Lowest road level per cell, and cells that already carry a pillar.
+    let mut lowest_road: HashMap<Cell, i32> = HashMap::new();
+    let mut supported: HashSet<Cell> = HashSet::new();
+    for placement in world.iter().chain(roads) {
+        if is_pillar(&placement.asset) {
+            supported.extend(placement.cells());
+        } else if placement.spec().is_some() {
+            let level = level_of(placement.translation[1]);
+            for cell in placement.cells() {
+                let entry = lowest_road.entry(cell).or_insert(level);
+                *entry = (*entry).min(level);
+            }
+        }
+    }
+
+    let mut plan = PillarPlan::default();
+    let mut planned: HashSet<Cell> = HashSet::new();
+    for road in roads {
+        let level = level_of(road.translation[1]);
+        if level <= 0 || road.spec().is_none() {
+            continue;
+        }
+        for cell in road.cells() {
+            if (cell.0 + cell.1).rem_euclid(spacing) != 0
+                || supported.contains(&cell)
+                || !planned.insert(cell)
+            {
+                continue;
+            }
+            if lowest_road.get(&cell).is_some_and(|&lowest| lowest < level) {
+                plan.blocked.push(cell);
+                continue;
+            }
+
+            let asset = if level > 1 {
+                WIDE_PILLAR_ASSET
+            } else {
+                PILLAR_ASSET
+            };
+            let [x, _, z] = cell_center(cell);
+            for step in 0..level {
+                plan.pillars
+                    .push(TilePlacement::new(asset, [x, level_y(step), z], 0.0));
+            }
+        }
+    }
+    plan
+}
+
+/// Road tiles sharing a cell on the same level, and pillars standing in a road.
+pub fn find_collisions(placements: &[TilePlacement]) -> Vec<String> {
+    let mut occupied: HashMap<(Cell, i32), &str> = HashMap::new();
+    let mut pillars: Vec<(Cell, i32)> = Vec::new();
+    let mut problems = Vec::new();
+
+    for placement in placements {
+        let level = level_of(placement.translation[1]);
+        if is_pillar(&placement.asset) {
+            pillars.extend(placement.cells().into_iter().map(|c| (c, level)));
+            continue;
+        }
+        if placement.spec().is_none() {
+            continue;
+        }
+        for cell in placement.cells() {
+            if let Some(other) = occupied.insert((cell, level), &placement.asset) {
+                problems.push(format!(
+                    "{} and {} overlap at cell [{}, {}] on level {}",
+                    other, placement.asset, cell.0, cell.1, level
+                ));
+            }
+        }
+    }
+
+    for (cell, level) in pillars {
+        if let Some(road) = occupied.get(&(cell, level)) {
+            problems.push(format!(
+                "Pillar in the middle of {} at cell [{}, {}] on level {}",
+                road, cell.0, cell.1, level
+            ));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spline::{spline_to_tiles, SplineOptions};
+
+    fn bridge(x: i32, level: i32) -> TilePlacement {
+        at_level(
+            TilePlacement::new("road-straight.glb", cell_center((x, 0)), 0.0),
+            level,
+        )
+    }
+
+    #[test]
+    fn ramps_need_room_for_the_height_change() {
+        let ramped = |level| SplineOptions {
+            level,
+            ramps: true,
+            ..Default::default()
+        };
+        let err = spline_to_tiles(&[[0.0, 0.0], [2.0, 0.0]], &ramped(2)).unwrap_err();
+        assert_eq!(
+            err,
+            "The road is 3 cells long, too short for two 2-cell ramps"
+        );
+
+        let tiles = spline_to_tiles(&[[0.0, 0.0], [2.0, 0.0]], &ramped(1)).unwrap();
+        let summary: Vec<(&str, [f32; 3], f32)> = tiles
+            .iter()
+            .map(|t| (t.asset.as_str(), t.translation, t.yaw_degrees))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (SLOPE_ASSET, [0.0, 0.0, 0.0], 0.0),
+                (SLOPE_ASSET, [2.0, 0.0, 0.0], 180.0),
+                ("road-bridge.glb", [1.0, LEVEL_HEIGHT, 0.0], 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn pillars_follow_the_spacing() {
+        let roads: Vec<TilePlacement> = (0..7).map(|x| bridge(x, 1)).collect();
+        let plan = plan_pillars(&roads, &[], 3);
+        let xs: Vec<f32> = plan.pillars.iter().map(|p| p.translation[0]).collect();
+        assert_eq!(xs, [0.0, 3.0, 6.0]);
+        assert!(plan.pillars.iter().all(|p| p.asset == PILLAR_ASSET));
+
+        // Two levels up: wide pillars, stacked.
+        let plan = plan_pillars(&[bridge(0, 2)], &[], 3);
+        assert_eq!(plan.pillars.len(), 2);
+        assert_eq!(plan.pillars[1].translation[1], LEVEL_HEIGHT);
+        assert!(plan.pillars.iter().all(|p| p.asset == WIDE_PILLAR_ASSET));
+
+        // Existing pillars are kept.
+        let existing = [TilePlacement::new(PILLAR_ASSET, cell_center((0, 0)), 0.0)];
+        assert!(plan_pillars(&[bridge(0, 1)], &existing, 3)
+            .pillars
+            .is_empty());
+    }
+
+    #[test]
+    fn ground_road_blocks_a_pillar() {
+        let crossing = TilePlacement::new("road-straight.glb", cell_center((3, 0)), 90.0);
+        let roads: Vec<TilePlacement> = (0..4).map(|x| bridge(x, 1)).collect();
+        let plan = plan_pillars(&roads, &[crossing], 3);
+        assert_eq!(plan.blocked, [(3, 0)]);
+        assert_eq!(plan.pillars.len(), 1);
+        assert_eq!(plan.pillars[0].translation, [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn collisions_on_the_same_level() {
+        let ground = TilePlacement::new("road-straight.glb", cell_center((1, 0)), 0.0);
+        let bend = TilePlacement::new("road-bend.glb", cell_center((1, 0)), 90.0);
+        assert_eq!(
+            find_collisions(&[ground.clone(), bend]),
+            ["road-straight.glb and road-bend.glb overlap at cell [1, 0] on level 0"]
+        );
+        assert!(find_collisions(&[ground.clone(), bridge(1, 1)]).is_empty());
+
+        let pillar = TilePlacement::new(PILLAR_ASSET, cell_center((1, 0)), 0.0);
+        assert_eq!(
+            find_collisions(&[ground, pillar]),
+            ["Pillar in the middle of road-straight.glb at cell [1, 0] on level 0"]
+        );
+    }
+}
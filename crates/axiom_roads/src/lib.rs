@@ -0,0 +1,23 @@
+//! Road knowledge shared by the Axiom editor and the `bevy_ai_remote` plugin.
+//!
+//! This crate is deliberately engine-free (plain `[f32; 3]` instead of glam/Bevy types)
+//! so the editor can use it without pulling in Bevy.
+
+pub mod elevation;
+pub mod nav;
+pub mod scatter;
+pub mod sketch;
+pub mod spline;
+pub mod tiles;
+pub mod turtle;
+
+pub use elevation::{find_collisions, plan_pillars, PillarPlan, LEVEL_HEIGHT};
+pub use nav::{NavEdge, NavGraph, NavNode, NavPath};
+pub use scatter::{occupied_cells, scatter, ScatterArea, ScatterOptions, ScatterResult, Side};
+pub use sketch::{layout_from_mask, CellMask};
+pub use spline::{spline_to_tiles, CurveKind, SplineOptions};
+pub use tiles::{
+    asset_stem, cell_at, cell_center, placement_for_openings, rotate_offset, tile_for_openings,
+    tile_spec, Cell, Dir, Port, TileKind, TilePlacement, TileSpec, GRID_SIZE,
+};
+pub use turtle::{BranchKind, Turtle, TurtleCommand};
@@ -0,0 +1,301 @@
+use crate::tiles::TilePlacement;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Tile-edge midpoints are quantised to a quarter unit so that the ports of two
+/// neighbouring tiles land on the same node even with float noise in the transforms.
+type NodeKey = [i32; 3];
+
+fn node_key(p: [f32; 3]) -> NodeKey {
+    [
+        (p[0] * 4.0).round() as i32,
+        (p[1] * 4.0).round() as i32,
+        (p[2] * 4.0).round() as i32,
+    ]
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    let dx = a[0] - b[0];
+    let dy = a[1] - b[1];
+    let dz = a[2] - b[2];
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+/// A lane node sitting on a tile edge.
+#[derive(Clone, Debug, Serialize)]
+pub struct NavNode {
+    pub position: [f32; 3],
+    /// Number of placed tiles that open onto this edge. `1` means the road stops here.
+    pub tiles: u32,
+}
+
+/// A drivable connection through one tile. Edges are undirected.
+#[derive(Clone, Debug, Serialize)]
+pub struct NavEdge {
+    pub from: usize,
+    pub to: usize,
+    pub cost: f32,
+    /// Index of the placement this edge runs through.
+    pub tile: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct NavPath {
+    pub nodes: Vec<usize>,
+    pub waypoints: Vec<[f32; 3]>,
+    pub length: f32,
+}
+
+/// Navigation graph computed from placed road tiles: nodes at tile edges,
+/// edges through each tile according to its type and rotation.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct NavGraph {
+    pub nodes: Vec<NavNode>,
+    pub edges: Vec<NavEdge>,
+    /// Assets that were ignored because they are not known road pieces.
+    pub skipped: Vec<String>,
+    #[serde(skip)]
+    adjacency: Vec<Vec<usize>>,
+    #[serde(skip)]
+    lookup: HashMap<NodeKey, usize>,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+struct Candidate {
+    estimate: f32,
+    node: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so BinaryHeap pops the lowest estimate first.
+        other
+            .estimate
+            .partial_cmp(&self.estimate)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl NavGraph {
+    pub fn build(placements: &[TilePlacement]) -> Self {
+        let mut graph = NavGraph::default();
+
+        for (tile, placement) in placements.iter().enumerate() {
+            let Some(ports) = placement.world_ports() else {
+                graph.skipped.push(placement.asset.clone());
+                continue;
+            };
+
+            let ids: Vec<usize> = ports.iter().map(|p| graph.intern(p.offset)).collect();
+            for &id in &ids {
+                graph.nodes[id].tiles += 1;
+            }
+
+            for i in 0..ids.len() {
+                for j in (i + 1)..ids.len() {
+                    let (from, to) = (ids[i], ids[j]);
+                    let cost = distance(graph.nodes[from].position, graph.nodes[to].position);
+                    graph.adjacency[from].push(graph.edges.len());
+                    graph.adjacency[to].push(graph.edges.len());
+                    graph.edges.push(NavEdge {
+                        from,
+                        to,
+                        cost,
+                        tile,
+                    });
+                }
+            }
+        }
+
+        graph
+    }
+
+    fn intern(&mut self, position: [f32; 3]) -> usize {
+        let key = node_key(position);
+        if let Some(&id) = self.lookup.get(&key) {
+            return id;
+        }
+        let id = self.nodes.len();
+        self.nodes.push(NavNode { position, tiles: 0 });
+        self.adjacency.push(Vec::new());
+        self.lookup.insert(key, id);
+        id
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Nodes only one tile opens onto, i.e. places where the road network stops abruptly.
+    pub fn open_ends(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&i| self.nodes[i].tiles == 1)
+            .collect()
+    }
+
+    /// Neighbouring node ids with the cost of the connecting edge.
+    pub fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, f32)> + '_ {
+        self.adjacency[node].iter().map(move |&e| {
+            let edge = &self.edges[e];
+            let other = if edge.from == node {
+                edge.to
+            } else {
+                edge.from
+            };
+            (other, edge.cost)
+        })
+    }
+
+    pub fn nearest_node(&self, point: [f32; 3]) -> Option<usize> {
+        (0..self.nodes.len()).min_by(|&a, &b| {
+            distance(self.nodes[a].position, point)
+                .partial_cmp(&distance(self.nodes[b].position, point))
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+
+    /// A* between the nodes closest to two world points.
+    pub fn find_path(&self, start: [f32; 3], goal: [f32; 3]) -> Option<NavPath> {
+        let start = self.nearest_node(start)?;
+        let goal = self.nearest_node(goal)?;
+        self.find_path_between(start, goal)
+    }
+
+    pub fn find_path_between(&self, start: usize, goal: usize) -> Option<NavPath> {
+        let goal_pos = self.nodes.get(goal)?.position;
+        self.nodes.get(start)?;
+
+        let mut best = vec![f32::INFINITY; self.nodes.len()];
+        let mut came_from: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let mut open = BinaryHeap::new();
+
+        best[start] = 0.0;
+        open.push(Candidate {
+            estimate: distance(self.nodes[start].position, goal_pos),
+            node: start,
+        });
+
+        while let Some(Candidate { estimate, node }) = open.pop() {
+            if node == goal {
+                let mut nodes = vec![goal];
+                let mut current = goal;
+                while let Some(prev) = came_from[current] {
+                    nodes.push(prev);
+                    current = prev;
+                }
+                nodes.reverse();
+                return Some(NavPath {
+                    waypoints: nodes.iter().map(|&n| self.nodes[n].position).collect(),
+                    length: best[goal],
+                    nodes,
+                });
+            }
+
+            // Skip stale heap entries.
+            if estimate - distance(self.nodes[node].position, goal_pos) > best[node] + 1e-4 {
+                continue;
+            }
+
+            for (next, cost) in self.neighbors(node) {
+                let tentative = best[node] + cost;
+                if tentative < best[next] {
+                    best[next] = tentative;
+                    came_from[next] = Some(node);
+                    open.push(Candidate {
+                        estimate: tentative + distance(self.nodes[next].position, goal_pos),
+                        node: next,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile(asset: &str, cell: (i32, i32), yaw: f32) -> TilePlacement {
+        TilePlacement::new(asset, [cell.0 as f32, 0.0, cell.1 as f32], yaw)
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn straight_road() {
+        let graph = NavGraph::build(&[
+            tile("road-straight.glb", (0, 0), 0.0),
+            tile("road-straight.glb", (1, 0), 0.0),
+            tile("road-straight.glb", (2, 0), 0.0),
+            tile("tree.glb", (1, 1), 0.0),
+        ]);
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.skipped, ["tree.glb"]);
+        assert_eq!(graph.open_ends().len(), 2);
+
+        let path = graph.find_path([-0.5, 0.0, 0.0], [2.5, 0.0, 0.0]).unwrap();
+        assert_eq!(path.nodes.len(), 4);
+        assert!(close(path.length, 3.0));
+        assert_eq!(path.waypoints[3], [2.5, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn path_follows_an_l_bend() {
+        let graph = NavGraph::build(&[
+            tile("road-straight.glb", (0, 0), 0.0),
+            tile("road-straight.glb", (1, 0), 0.0),
+            // Joins West and South at rotation 0.
+            tile("road-bend.glb", (2, 0), 0.0),
+            tile("road-straight.glb", (2, 1), 90.0),
+        ]);
+        let path = graph.find_path([-0.5, 0.0, 0.0], [2.0, 0.0, 1.5]).unwrap();
+        assert_eq!(
+            path.waypoints,
+            [
+                [-0.5, 0.0, 0.0],
+                [0.5, 0.0, 0.0],
+                [1.5, 0.0, 0.0],
+                [2.0, 0.0, 0.5],
+                [2.0, 0.0, 1.5],
+            ]
+        );
+        assert!(close(path.length, 3.0 + 0.5f32.sqrt()));
+    }
+
+    #[test]
+    fn unreachable_target() {
+        let graph = NavGraph::build(&[
+            tile("road-straight.glb", (0, 0), 0.0),
+            tile("road-straight.glb", (5, 0), 0.0),
+        ]);
+        assert!(graph.find_path([0.0, 0.0, 0.0], [5.0, 0.0, 0.0]).is_none());
+        assert!(NavGraph::default()
+            .find_path([0.0; 3], [1.0, 0.0, 0.0])
+            .is_none());
+    }
+
+    #[test]
+    fn start_off_the_road_snaps_to_the_nearest_node() {
+        let graph = NavGraph::build(&[
+            tile("road-straight.glb", (0, 0), 0.0),
+            tile("road-straight.glb", (1, 0), 0.0),
+        ]);
+        let path = graph.find_path([-3.0, 0.0, 4.0], [1.5, 0.0, 0.0]).unwrap();
+        assert_eq!(path.waypoints[0], [-0.5, 0.0, 0.0]);
+        assert!(close(path.length, 2.0));
+    }
+}
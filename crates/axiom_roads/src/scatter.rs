@@ -0,0 +1,520 @@
+use crate::elevation::level_of;
+use crate::tiles::{cell_at, Cell, TilePlacement};
+use std::collections::HashSet;
+
+/// New props closer than this to each other are treated as overlapping.
+const MIN_GAP: f32 = 0.25;
+/// Jittered positions tried per item before giving up on a blocked spot.
+const ATTEMPTS: usize = 4;
+/// Grid points an area offers per item at most, so a huge area does not build a huge grid.
+const CANDIDATES_PER_ITEM: usize = 4;
+
+/// Small deterministic RNG (SplitMix64), so the same seed always gives the same layout.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, lo: f32, hi: f32) -> f32 {
+        lo + (hi - lo) * self.next_f32()
+    }
+
+    pub fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len.max(1) as u64) as usize
+    }
+}
+
+/// Where to scatter, in world `[x, z]` coordinates.
+#[derive(Clone, Debug)]
+pub enum ScatterArea {
+    /// Items every `spacing` units along a polyline, e.g. a road centre line.
+    Path(Vec<[f32; 2]>),
+    /// Items on a jittered grid inside a polygon.
+    Polygon(Vec<[f32; 2]>),
+    Rect {
+        min: [f32; 2],
+        max: [f32; 2],
+    },
+}
+
+/// Which side of a path the items go on, looking along the path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+    Both,
+    /// Left and right in turn.
+    Alternate,
+    /// On the path itself.
+    Center,
+}
+
+impl Side {
+    pub fn parse(s: &str) -> Option<Side> {
+        match s.trim().to_lowercase().as_str() {
+            "left" => Some(Side::Left),
+            "right" => Some(Side::Right),
+            "both" => Some(Side::Both),
+            "alternate" => Some(Side::Alternate),
+            "center" | "centre" => Some(Side::Center),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ScatterOptions {
+    /// Assets to pick from at random.
+    pub assets: Vec<String>,
+    /// Distance between items along a path, or between grid points inside an area.
+    pub spacing: f32,
+    /// Maximum random displacement of each item on X and Z.
+    pub jitter: f32,
+    pub side: Side,
+    /// Distance from the path to the items (path mode).
+    pub side_offset: f32,
+    pub seed: u64,
+    pub y: f32,
+    /// Random yaw per item. Otherwise path items face the path and area items keep yaw 0.
+    pub random_yaw: bool,
+    /// Added to every yaw, for models whose front is not -Z.
+    pub yaw_offset: f32,
+    pub max_count: usize,
+}
+
+impl Default for ScatterOptions {
+    fn default() -> Self {
+        Self {
+            assets: Vec::new(),
+            spacing: 3.0,
+            jitter: 0.0,
+            side: Side::Both,
+            // Just past the edge of a road tile, so path items land in the neighbouring cell.
+            side_offset: 0.6,
+            seed: 0,
+            y: 0.0,
+            random_yaw: false,
+            yaw_offset: 0.0,
+            max_count: 200,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ScatterResult {
+    pub placements: Vec<TilePlacement>,
+    /// Candidate spots dropped because their cell was occupied or another item was too close.
+    pub blocked: usize,
+    /// `max_count` was reached before the area was filled.
+    pub truncated: bool,
+}
+
+/// Cells props must stay out of: everything standing on the ground (road tiles, pillars and
+/// other props). Elevated roads do not block the ground below them.
+pub fn occupied_cells(world: &[TilePlacement]) -> HashSet<Cell> {
+    world
+        .iter()
+        .filter(|p| level_of(p.translation[1]) == 0)
+        .flat_map(|p| p.cells())
+        .collect()
+}
+
+/// A spot to try, with the point it should face (path mode).
+struct Candidate {
+    position: [f32; 2],
+    facing: Option<[f32; 2]>,
+}
+
+fn path_candidates(points: &[[f32; 2]], options: &ScatterOptions) -> Vec<Candidate> {
+    let segments: Vec<([f32; 2], [f32; 2], f32)> = points
+        .windows(2)
+        .map(|w| {
+            let length = ((w[1][0] - w[0][0]).powi(2) + (w[1][1] - w[0][1]).powi(2)).sqrt();
+            (w[0], w[1], length)
+        })
+        .filter(|(_, _, length)| *length > 1e-4)
+        .collect();
+    let total: f32 = segments.iter().map(|(_, _, l)| l).sum();
+    if segments.is_empty() {
+        return Vec::new();
+    }
+
+    // Centre the stations on the path so both ends get the same margin.
+    let stations = (total / options.spacing).floor() as usize + 1;
+    let start = (total - (stations - 1) as f32 * options.spacing) * 0.5;
+
+    let mut out = Vec::new();
+    let mut segment = 0;
+    let mut walked = 0.0;
+    for station in 0..stations {
+        let s = start + station as f32 * options.spacing;
+        while segment + 1 < segments.len() && walked + segments[segment].2 < s {
+            walked += segments[segment].2;
+            segment += 1;
+        }
+        let (a, b, length) = segments[segment];
+        let t = ((s - walked) / length).clamp(0.0, 1.0);
+        let point = [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t];
+        let tangent = [(b[0] - a[0]) / length, (b[1] - a[1]) / length];
+        // Looking along +X (East), left is -Z (North).
+        let left = [tangent[1], -tangent[0]];
+
+        let sides: &[f32] = match options.side {
+            Side::Left => &[1.0],
+            Side::Right => &[-1.0],
+            Side::Both => &[1.0, -1.0],
+            Side::Alternate if station % 2 == 0 => &[1.0],
+            Side::Alternate => &[-1.0],
+            Side::Center => &[0.0],
+        };
+        for &side in sides {
+            let offset = side * options.side_offset;
+            out.push(Candidate {
+                position: [point[0] + left[0] * offset, point[1] + left[1] * offset],
+                facing: Some(if side == 0.0 {
+                    [point[0] + tangent[0], point[1] + tangent[1]]
+                } else {
+                    point
+                }),
+            });
+        }
+    }
+    out
+}
+
+fn contains(polygon: &[[f32; 2]], p: [f32; 2]) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[j]);
+        if (a[1] > p[1]) != (b[1] > p[1])
+            && p[0] < (b[0] - a[0]) * (p[1] - a[1]) / (b[1] - a[1]) + a[0]
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+impl ScatterArea {
+    /// Bounding box `(min, max)` of an area; `None` for a path.
+    fn bounds(&self) -> Option<([f32; 2], [f32; 2])> {
+        match self {
+            ScatterArea::Path(_) => None,
+            ScatterArea::Polygon(points) => Some(points.iter().fold(
+                ([f32::MAX; 2], [f32::MIN; 2]),
+                |(min, max), p| {
+                    (
+                        [min[0].min(p[0]), min[1].min(p[1])],
+                        [max[0].max(p[0]), max[1].max(p[1])],
+                    )
+                },
+            )),
+            ScatterArea::Rect { min, max } => Some((
+                [min[0].min(max[0]), min[1].min(max[1])],
+                [min[0].max(max[0]), min[1].max(max[1])],
+            )),
+        }
+    }
+
+    /// Whether `p` may hold an item. Rectangles include their edges, so `[0, 0]..[4, 4]`
+    /// covers the cell centres 0 to 4 on both axes.
+    fn allows(&self, p: [f32; 2]) -> bool {
+        const EPS: f32 = 1e-3;
+        match self {
+            ScatterArea::Path(_) => true,
+            ScatterArea::Polygon(points) => contains(points, p),
+            ScatterArea::Rect { .. } => self.bounds().is_some_and(|(min, max)| {
+                (min[0] - EPS..=max[0] + EPS).contains(&p[0])
+                    && (min[1] - EPS..=max[1] + EPS).contains(&p[1])
+            }),
+        }
+    }
+}
+
+/// Grid points centred in the bounding box, in random order so that `max_count` items spread
+/// over the whole area. Big grids are sampled down to [`CANDIDATES_PER_ITEM`] points per item;
+/// the flag tells whether points were left out.
+fn area_candidates(
+    min: [f32; 2],
+    max: [f32; 2],
+    options: &ScatterOptions,
+    rng: &mut Rng,
+) -> (Vec<Candidate>, bool) {
+    let columns = ((max[0] - min[0]) / options.spacing + 1e-3).floor() as usize + 1;
+    let rows = ((max[1] - min[1]) / options.spacing + 1e-3).floor() as usize + 1;
+    // Centre the grid in the bounding box.
+    let x0 = (min[0] + max[0] - (columns - 1) as f32 * options.spacing) * 0.5;
+    let z0 = (min[1] + max[1] - (rows - 1) as f32 * options.spacing) * 0.5;
+
+    let total = columns.saturating_mul(rows);
+    let budget = options.max_count.saturating_mul(CANDIDATES_PER_ITEM).max(1);
+    let mut points: Vec<usize> = if total <= budget {
+        (0..total).collect()
+    } else {
+        // Floyd's algorithm: `budget` distinct grid points without listing the grid.
+        let mut picked = HashSet::new();
+        let mut points = Vec::with_capacity(budget);
+        for j in total - budget..total {
+            let t = rng.index(j + 1);
+            let point = if picked.insert(t) { t } else { j };
+            picked.insert(point);
+            points.push(point);
+        }
+        points
+    };
+    for i in (1..points.len()).rev() {
+        points.swap(i, rng.index(i + 1));
+    }
+
+    let candidates = points
+        .into_iter()
+        .map(|i| Candidate {
+            position: [
+                x0 + (i % columns) as f32 * options.spacing,
+                z0 + (i / columns) as f32 * options.spacing,
+            ],
+            facing: None,
+        })
+        .collect();
+    (candidates, total > budget)
+}
+
+/// Yaw in degrees that turns a model's -Z axis from `from` towards `to`.
+fn yaw_towards(from: [f32; 2], to: [f32; 2]) -> f32 {
+    let (dx, dz) = (to[0] - from[0], to[1] - from[1]);
+    if dx.abs() < 1e-6 && dz.abs() < 1e-6 {
+        return 0.0;
+    }
+    (-dx).atan2(-dz).to_degrees()
+}
+
+/// Places props from `options.assets` over `area`, skipping `occupied` cells. Deterministic for
+/// a given seed.
+pub fn scatter(
+    area: &ScatterArea,
+    options: &ScatterOptions,
+    occupied: &HashSet<Cell>,
+) -> Result<ScatterResult, String> {
+    if options.assets.is_empty() {
+        return Err("No assets to scatter".to_string());
+    }
+    if options.spacing <= 0.05 {
+        return Err(format!("Spacing {} is too small", options.spacing));
+    }
+
+    let mut rng = Rng::new(options.seed);
+    let (candidates, sampled) = match area {
+        ScatterArea::Path(points) if points.len() < 2 => {
+            return Err("A path needs at least two points".to_string())
+        }
+        ScatterArea::Polygon(points) if points.len() < 3 => {
+            return Err("A polygon needs at least three points".to_string())
+        }
+        ScatterArea::Path(points) => (path_candidates(points, options), false),
+        _ => match area.bounds() {
+            Some((min, max)) => area_candidates(min, max, options, &mut rng),
+            None => (Vec::new(), false),
+        },
+    };
+
+    let mut result = ScatterResult::default();
+    let mut placed: Vec<[f32; 2]> = Vec::new();
+    for candidate in candidates {
+        if result.placements.len() >= options.max_count {
+            result.truncated = true;
+            break;
+        }
+        let asset = &options.assets[rng.index(options.assets.len())];
+        let spin = rng.range(0.0, 360.0);
+
+        let attempts = if options.jitter > 0.0 { ATTEMPTS } else { 1 };
+        let spot = (0..attempts).find_map(|_| {
+            let [x, z] = candidate.position;
+            let p = if options.jitter > 0.0 {
+                [
+                    x + rng.range(-options.jitter, options.jitter),
+                    z + rng.range(-options.jitter, options.jitter),
+                ]
+            } else {
+                [x, z]
+            };
+            let free = area.allows(p)
+                && !occupied.contains(&cell_at(p[0], p[1]))
+                && placed
+                    .iter()
+                    .all(|q| (q[0] - p[0]).powi(2) + (q[1] - p[1]).powi(2) >= MIN_GAP * MIN_GAP);
+            free.then_some(p)
+        });
+        let Some(p) = spot else {
+            // Grid points outside the polygon are expected, only count real conflicts.
+            if area.allows(candidate.position) {
+                result.blocked += 1;
+            }
+            continue;
+        };
+
+        let yaw = if options.random_yaw {
+            spin
+        } else {
+            candidate.facing.map_or(0.0, |to| yaw_towards(p, to))
+        };
+        let yaw = ((yaw + options.yaw_offset).rem_euclid(360.0) * 10.0).round() / 10.0;
+        // Centimetre precision keeps tool output and the scene mirror readable.
+        let p = p.map(|v| (v * 100.0).round() / 100.0);
+        placed.push(p);
+        result.placements.push(TilePlacement::new(
+            asset.clone(),
+            [p[0], options.y, p[1]],
+            yaw,
+        ));
+    }
+    if sampled && result.placements.len() >= options.max_count {
+        result.truncated = true;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(side: Side) -> ScatterOptions {
+        ScatterOptions {
+            assets: vec!["tree.glb".to_string(), "rock.glb".to_string()],
+            side,
+            ..Default::default()
+        }
+    }
+
+    fn positions(result: &ScatterResult) -> Vec<[f32; 2]> {
+        result
+            .placements
+            .iter()
+            .map(|p| [p.translation[0], p.translation[2]])
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_layout() {
+        let area = ScatterArea::Rect {
+            min: [0.0, 0.0],
+            max: [12.0, 12.0],
+        };
+        let jittered = |seed| ScatterOptions {
+            jitter: 0.4,
+            random_yaw: true,
+            seed,
+            ..options(Side::Both)
+        };
+        let a = scatter(&area, &jittered(7), &HashSet::new()).unwrap();
+        let b = scatter(&area, &jittered(7), &HashSet::new()).unwrap();
+        let c = scatter(&area, &jittered(8), &HashSet::new()).unwrap();
+        assert_eq!(a.placements, b.placements);
+        assert_ne!(a.placements, c.placements);
+        assert!(!a.placements.is_empty());
+    }
+
+    #[test]
+    fn stations_are_centred_on_the_path() {
+        let path = ScatterArea::Path(vec![[0.0, 0.0], [10.0, 0.0]]);
+        let result = scatter(&path, &options(Side::Center), &HashSet::new()).unwrap();
+        assert_eq!(
+            positions(&result),
+            [[0.5, 0.0], [3.5, 0.0], [6.5, 0.0], [9.5, 0.0]]
+        );
+        // Facing along the path, towards +X.
+        assert!(result.placements.iter().all(|p| p.yaw_degrees == 270.0));
+    }
+
+    #[test]
+    fn alternate_switches_sides() {
+        let path = ScatterArea::Path(vec![[0.0, 0.0], [6.0, 0.0]]);
+        let result = scatter(&path, &options(Side::Alternate), &HashSet::new()).unwrap();
+        // Left of a road heading East is North (-Z).
+        assert_eq!(positions(&result), [[0.0, -0.6], [3.0, 0.6], [6.0, -0.6]]);
+        // Each item faces the road.
+        let yaws: Vec<f32> = result.placements.iter().map(|p| p.yaw_degrees).collect();
+        assert_eq!(yaws, [180.0, 0.0, 180.0]);
+    }
+
+    #[test]
+    fn occupied_cells_are_skipped() {
+        let row = ScatterArea::Rect {
+            min: [0.0, 0.0],
+            max: [4.0, 0.0],
+        };
+        let spaced = ScatterOptions {
+            spacing: 1.0,
+            ..options(Side::Both)
+        };
+        let road = [TilePlacement::new(
+            "road-straight.glb",
+            [2.0, 0.0, 0.0],
+            0.0,
+        )];
+        let result = scatter(&row, &spaced, &occupied_cells(&road)).unwrap();
+        let mut xs: Vec<f32> = positions(&result).iter().map(|p| p[0]).collect();
+        xs.sort_by(f32::total_cmp);
+        assert_eq!(xs, [0.0, 1.0, 3.0, 4.0]);
+        assert_eq!(result.blocked, 1);
+    }
+
+    #[test]
+    fn polygon_holds_its_items() {
+        let triangle = vec![[0.0, 0.0], [8.0, 0.0], [0.0, 8.0]];
+        let spaced = ScatterOptions {
+            spacing: 1.0,
+            ..options(Side::Both)
+        };
+        let result = scatter(
+            &ScatterArea::Polygon(triangle.clone()),
+            &spaced,
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert!(!result.placements.is_empty());
+        assert!(positions(&result)
+            .iter()
+            .all(|&p| contains(&triangle, p) && p[0] + p[1] < 8.0));
+        assert_eq!(result.blocked, 0);
+    }
+
+    #[test]
+    fn max_count_spreads_over_a_large_area() {
+        let area = ScatterArea::Rect {
+            min: [0.0, 0.0],
+            max: [999.0, 999.0],
+        };
+        let capped = ScatterOptions {
+            spacing: 1.0,
+            max_count: 40,
+            ..options(Side::Both)
+        };
+        let result = scatter(&area, &capped, &HashSet::new()).unwrap();
+        assert_eq!(result.placements.len(), 40);
+        assert!(result.truncated);
+        let points = positions(&result);
+        assert!(points.iter().any(|p| p[1] > 500.0), "{:?}", points);
+        let distinct: HashSet<(i32, i32)> =
+            points.iter().map(|p| (p[0] as i32, p[1] as i32)).collect();
+        assert_eq!(distinct.len(), 40);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::tiles::{placement_for_openings, Cell, Dir, TilePlacement};
+
+/// Row-major grid of road / no-road cells, e.g. a thresholded top-down sketch.
+/// Row 0 is the northern edge, so rows grow towards +Z like the world grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellMask {
+    pub width: usize,
+    pub height: usize,
+    cells: Vec<bool>,
+}
+
+impl CellMask {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![false; width * height],
+        }
+    }
+
+    pub fn get(&self, x: i64, z: i64) -> bool {
+        if x < 0 || z < 0 || x >= self.width as i64 || z >= self.height as i64 {
+            return false;
+        }
+        self.cells[z as usize * self.width + x as usize]
+    }
+
+    pub fn set(&mut self, x: usize, z: usize, road: bool) {
+        self.cells[z * self.width + x] = road;
+    }
+
+    pub fn count(&self) -> usize {
+        self.cells.iter().filter(|&&c| c).count()
+    }
+
+    /// One line per row, `#` for road and `.` for empty.
+    pub fn to_ascii(&self) -> String {
+        let mut out = String::new();
+        for z in 0..self.height {
+            for x in 0..self.width {
+                out.push(if self.get(x as i64, z as i64) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Zhang-Suen thinning: reduces thick strokes to one-cell-wide centre lines so that a
+    /// road drawn with a fat brush does not turn into a block of crossroads.
+    pub fn thinned(&self) -> CellMask {
+        let mut mask = self.clone();
+        loop {
+            let mut changed = false;
+            for pass in 0..2 {
+                let mut remove = Vec::new();
+                for z in 0..mask.height as i64 {
+                    for x in 0..mask.width as i64 {
+                        if !mask.get(x, z) {
+                            continue;
+                        }
+                        // P2..P9 clockwise starting north.
+                        let p = [
+                            mask.get(x, z - 1),
+                            mask.get(x + 1, z - 1),
+                            mask.get(x + 1, z),
+                            mask.get(x + 1, z + 1),
+                            mask.get(x, z + 1),
+                            mask.get(x - 1, z + 1),
+                            mask.get(x - 1, z),
+                            mask.get(x - 1, z - 1),
+                        ];
+                        let neighbours = p.iter().filter(|&&b| b).count();
+                        let transitions = (0..8).filter(|&i| !p[i] && p[(i + 1) % 8]).count();
+                        let (n, e, s, w) = (p[0], p[2], p[4], p[6]);
+                        // Zhang-Suen's P2*P4*P6 / P4*P6*P8 (and the second-pass variants) conditions.
+                        let keep = if pass == 0 {
+                            e && s && (n || w)
+                        } else {
+                            n && w && (e || s)
+                        };
+                        if (2..=6).contains(&neighbours) && transitions == 1 && !keep {
+                            remove.push((x as usize, z as usize));
+                        }
+                    }
+                }
+                changed |= !remove.is_empty();
+                for (x, z) in remove {
+                    mask.set(x, z, false);
+                }
+            }
+            if !changed {
+                return mask;
+            }
+        }
+    }
+
+    /// Road tiles only connect through edges, so diagonal steps get a corner cell filled in.
+    pub fn four_connected(&self) -> CellMask {
+        let mut mask = self.clone();
+        for z in 0..self.height as i64 {
+            for x in 0..self.width as i64 {
+                if !self.get(x, z) {
+                    continue;
+                }
+                for dx in [-1, 1] {
+                    let (cx, cz) = (x + dx, z + 1);
+                    if self.get(cx, cz) && !mask.get(cx, z) && !mask.get(x, cz) {
+                        mask.set(cx as usize, z as usize, true);
+                    }
+                }
+            }
+        }
+        mask
+    }
+}
+
+/// Turns a road mask into tiles using the same opening rules as the turtle: every road cell
+/// opens towards its road neighbours. Cell `(0, 0)` of the mask lands on `origin`.
+/// Isolated cells are dropped as noise.
+pub fn layout_from_mask(mask: &CellMask, origin: Cell) -> Vec<TilePlacement> {
+    let mut placements = Vec::new();
+    for z in 0..mask.height as i64 {
+        for x in 0..mask.width as i64 {
+            if !mask.get(x, z) {
+                continue;
+            }
+            let openings = Dir::ALL
+                .into_iter()
+                .filter(|d| {
+                    let (dx, dz) = d.offset();
+                    mask.get(x + dx as i64, z + dz as i64)
+                })
+                .fold(0, |m, d| m | d.bit());
+            if openings == 0 {
+                continue;
+            }
+            let cell = (origin.0 + x as i32, origin.1 + z as i32);
+            placements.extend(placement_for_openings(cell, openings));
+        }
+    }
+    placements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::asset_stem;
+
+    /// `size`x`size` mask with `width`-cell-wide bars, `(horizontal, first row or column)`,
+    /// running from `from` to `to`.
+    fn strokes(
+        size: usize,
+        bars: &[(bool, usize)],
+        width: usize,
+        from: usize,
+        to: usize,
+    ) -> CellMask {
+        let mut mask = CellMask::new(size, size);
+        for &(horizontal, at) in bars {
+            for a in from..to {
+                for b in at..at + width {
+                    if horizontal {
+                        mask.set(a, b, true);
+                    } else {
+                        mask.set(b, a, true);
+                    }
+                }
+            }
+        }
+        mask
+    }
+
+    fn is_skeleton(thin: &CellMask, thick: &CellMask) -> bool {
+        let inside = (0..thin.height as i64)
+            .all(|z| (0..thin.width as i64).all(|x| !thin.get(x, z) || thick.get(x, z)));
+        let no_blocks = (0..thin.height as i64).all(|z| {
+            (0..thin.width as i64).all(|x| {
+                !(thin.get(x, z)
+                    && thin.get(x + 1, z)
+                    && thin.get(x, z + 1)
+                    && thin.get(x + 1, z + 1))
+            })
+        });
+        inside && no_blocks
+    }
+
+    fn kinds(tiles: &[TilePlacement]) -> Vec<String> {
+        let mut kinds: Vec<String> = tiles.iter().map(|t| asset_stem(&t.asset)).collect();
+        kinds.sort();
+        kinds.dedup();
+        kinds
+    }
+
+    fn count(tiles: &[TilePlacement], stem: &str) -> usize {
+        tiles
+            .iter()
+            .filter(|t| asset_stem(&t.asset) == stem)
+            .count()
+    }
+
+    #[test]
+    fn thick_cross_thins_to_a_crossroad() {
+        let thick = strokes(11, &[(true, 4), (false, 4)], 3, 0, 11);
+        let thin = thick.thinned().four_connected();
+        assert!(is_skeleton(&thin, &thick), "{}", thin.to_ascii());
+
+        let tiles = layout_from_mask(&thin, (0, 0));
+        assert_eq!(count(&tiles, "road-crossroad"), 1, "{}", thin.to_ascii());
+        assert_eq!(count(&tiles, "road-end"), 4);
+        assert_eq!(
+            kinds(&tiles),
+            ["road-crossroad", "road-end", "road-straight"]
+        );
+    }
+
+    #[test]
+    fn thick_l_thins_to_one_bend() {
+        let thick = strokes(9, &[(true, 1), (false, 1)], 3, 1, 8);
+        let thin = thick.thinned().four_connected();
+        assert!(is_skeleton(&thin, &thick), "{}", thin.to_ascii());
+
+        let tiles = layout_from_mask(&thin, (10, -5));
+        assert_eq!(count(&tiles, "road-bend"), 1, "{}", thin.to_ascii());
+        assert_eq!(count(&tiles, "road-end"), 2);
+        assert_eq!(kinds(&tiles), ["road-bend", "road-end", "road-straight"]);
+        // The mask origin is shifted onto `origin`.
+        assert!(tiles
+            .iter()
+            .all(|t| t.translation[0] >= 10.0 && t.translation[2] >= -5.0));
+    }
+
+    #[test]
+    fn isolated_cells_are_dropped() {
+        let mut mask = CellMask::new(3, 3);
+        mask.set(1, 1, true);
+        assert!(layout_from_mask(&mask, (0, 0)).is_empty());
+    }
+}
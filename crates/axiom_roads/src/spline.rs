@@ -0,0 +1,403 @@
+use crate::elevation::{at_level, slope_placement};
+use crate::tiles::{
+    cell_at, cell_center, placement_for_openings, rotate_offset, tile_spec, Cell, Dir,
+    TilePlacement, GRID_SIZE,
+};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+/// Samples per grid cell of curve length, dense enough that no cell is skipped.
+const SAMPLES_PER_CELL: f32 = 16.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveKind {
+    /// Passes through every control point.
+    #[default]
+    CatmullRom,
+    /// One Bezier curve using all points as control points; only the ends are hit exactly.
+    Bezier,
+}
+
+#[derive(Clone, Debug)]
+pub struct SplineOptions {
+    pub kind: CurveKind,
+    /// Connect the last point back to the first.
+    pub closed: bool,
+    /// Replace 90° turns spread over a 2x2 block with a single `road-curve` piece.
+    pub wide_curves: bool,
+    /// Finish open ends with `road-end` instead of leaving a straight to connect to.
+    pub end_caps: bool,
+    /// Road level of the curve, see [`crate::elevation::LEVEL_HEIGHT`].
+    pub level: i32,
+    /// Turn the first and last `level` cells into slopes down to the ground.
+    pub ramps: bool,
+}
+
+impl Default for SplineOptions {
+    fn default() -> Self {
+        Self {
+            kind: CurveKind::CatmullRom,
+            closed: false,
+            wide_curves: true,
+            end_caps: false,
+            level: 0,
+            ramps: false,
+        }
+    }
+}
+
+fn lerp(a: [f32; 2], b: [f32; 2], t: f32) -> [f32; 2] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+}
+
+fn dist(a: [f32; 2], b: [f32; 2]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
+
+fn steps_for(length: f32) -> usize {
+    ((length / GRID_SIZE) * SAMPLES_PER_CELL).ceil().max(1.0) as usize
+}
+
+fn catmull_rom(p0: [f32; 2], p1: [f32; 2], p2: [f32; 2], p3: [f32; 2], t: f32) -> [f32; 2] {
+    let (t2, t3) = (t * t, t * t * t);
+    let axis = |i: usize| {
+        0.5 * (2.0 * p1[i]
+            + (-p0[i] + p2[i]) * t
+            + (2.0 * p0[i] - 5.0 * p1[i] + 4.0 * p2[i] - p3[i]) * t2
+            + (-p0[i] + 3.0 * p1[i] - 3.0 * p2[i] + p3[i]) * t3)
+    };
+    [axis(0), axis(1)]
+}
+
+fn bezier(points: &[[f32; 2]], t: f32) -> [f32; 2] {
+    let mut work = points.to_vec();
+    for level in (1..work.len()).rev() {
+        for i in 0..level {
+            work[i] = lerp(work[i], work[i + 1], t);
+        }
+    }
+    work[0]
+}
+
+/// Points along the curve defined by `points` (`[x, z]`).
+pub fn sample_curve(points: &[[f32; 2]], kind: CurveKind, closed: bool) -> Vec<[f32; 2]> {
+    if points.len() < 2 {
+        return points.to_vec();
+    }
+
+    let mut samples = Vec::new();
+    match kind {
+        CurveKind::CatmullRom => {
+            let n = points.len();
+            let at = |i: isize| -> [f32; 2] {
+                if closed {
+                    points[i.rem_euclid(n as isize) as usize]
+                } else {
+                    points[i.clamp(0, n as isize - 1) as usize]
+                }
+            };
+            let segments = if closed { n } else { n - 1 };
+            for s in 0..segments as isize {
+                let (p0, p1, p2, p3) = (at(s - 1), at(s), at(s + 1), at(s + 2));
+                let steps = steps_for(dist(p1, p2) * 1.5);
+                for k in 0..steps {
+                    samples.push(catmull_rom(p0, p1, p2, p3, k as f32 / steps as f32));
+                }
+            }
+            samples.push(if closed { points[0] } else { points[n - 1] });
+        }
+        CurveKind::Bezier => {
+            let mut control = points.to_vec();
+            if closed {
+                control.push(points[0]);
+            }
+            let length: f32 = control.windows(2).map(|w| dist(w[0], w[1])).sum();
+            let steps = steps_for(length);
+            for k in 0..=steps {
+                samples.push(bezier(&control, k as f32 / steps as f32));
+            }
+        }
+    }
+    samples
+}
+
+fn push_cell(cells: &mut Vec<Cell>, cell: Cell) {
+    // Sampling noise can step out of a cell and straight back in; drop the detour.
+    if cells.len() >= 2 && cells[cells.len() - 2] == cell {
+        cells.pop();
+    } else {
+        cells.push(cell);
+    }
+}
+
+/// Walks the samples and returns the 4-connected chain of cells they pass through.
+pub fn rasterize(samples: &[[f32; 2]]) -> Vec<Cell> {
+    let mut cells: Vec<Cell> = Vec::new();
+    for &p in samples {
+        let target = cell_at(p[0], p[1]);
+        if cells.is_empty() {
+            cells.push(target);
+            continue;
+        }
+
+        while let Some(&last) = cells.last() {
+            if last == target {
+                break;
+            }
+            let (dx, dz) = (target.0 - last.0, target.1 - last.1);
+            let step_x = (last.0 + dx.signum(), last.1);
+            let step_z = (last.0, last.1 + dz.signum());
+            // On a diagonal move go through the neighbour the curve passes closer to.
+            let next = if dz == 0 {
+                step_x
+            } else if dx == 0 {
+                step_z
+            } else {
+                let cx = cell_center(step_x);
+                let cz = cell_center(step_z);
+                if dist([cx[0], cx[2]], p) <= dist([cz[0], cz[2]], p) {
+                    step_x
+                } else {
+                    step_z
+                }
+            };
+            push_cell(&mut cells, next);
+        }
+    }
+    cells
+}
+
+fn edge_point(cell: Cell, dir: Dir) -> [f32; 3] {
+    let [x, y, z] = cell_center(cell);
+    let (dx, dz) = dir.offset();
+    let half = GRID_SIZE * 0.5;
+    [x + dx as f32 * half, y, z + dz as f32 * half]
+}
+
+fn close_enough(a: [f32; 3], b: [f32; 3]) -> bool {
+    (a[0] - b[0]).abs() < 1e-3 && (a[1] - b[1]).abs() < 1e-3 && (a[2] - b[2]).abs() < 1e-3
+}
+
+/// Tries to cover the turn `a -> b -> c` (with `a` and `c` diagonal) by one 2x2 `road-curve`
+/// entering `a` through `enter` and leaving `c` through `leave`.
+fn wide_curve(a: Cell, c: Cell, enter: Dir, leave: Dir) -> Option<TilePlacement> {
+    let asset = "road-curve.glb";
+    let spec = tile_spec(asset)?;
+    let ca = cell_center(a);
+    let cc = cell_center(c);
+    let center = [(ca[0] + cc[0]) * 0.5, 0.0, (ca[2] + cc[2]) * 0.5];
+    let want = [edge_point(a, enter), edge_point(c, leave)];
+
+    (0..4u8).find_map(|q| {
+        let ports: Vec<[f32; 3]> = spec
+            .ports
+            .iter()
+            .map(|p| {
+                let [x, y, z] = rotate_offset(p.offset, q);
+                [center[0] + x, center[1] + y, center[2] + z]
+            })
+            .collect();
+        let matches = (close_enough(ports[0], want[0]) && close_enough(ports[1], want[1]))
+            || (close_enough(ports[0], want[1]) && close_enough(ports[1], want[0]));
+        matches.then(|| TilePlacement::new(asset, center, q as f32 * 90.0))
+    })
+}
+
+/// Turns a chain of cells into flat road tiles. Cells visited more than once become junctions.
+/// Cells in `skip` get no tile (they are filled by ramps).
+fn cells_to_tiles(
+    path: &[Cell],
+    options: &SplineOptions,
+    skip: &HashSet<Cell>,
+) -> Vec<TilePlacement> {
+    let n = path.len();
+    if n < 2 {
+        return Vec::new();
+    }
+
+    let mut openings: HashMap<Cell, u8> = HashMap::new();
+    let segments = if options.closed { n } else { n - 1 };
+    for i in 0..segments {
+        let (a, b) = (path[i], path[(i + 1) % n]);
+        if let Some(dir) = Dir::between(a, b) {
+            *openings.entry(a).or_default() |= dir.bit();
+            *openings.entry(b).or_default() |= dir.opposite().bit();
+        }
+    }
+    if !options.closed && !options.end_caps {
+        // Leave the ends open in the direction of travel so other roads can attach.
+        if let Some(dir) = Dir::between(path[1], path[0]) {
+            *openings.entry(path[0]).or_default() |= dir.bit();
+        }
+        if let Some(dir) = Dir::between(path[n - 2], path[n - 1]) {
+            *openings.entry(path[n - 1]).or_default() |= dir.bit();
+        }
+    }
+
+    let mut visits: HashMap<Cell, usize> = HashMap::new();
+    for &cell in path {
+        *visits.entry(cell).or_default() += 1;
+    }
+
+    let mut placements = Vec::new();
+    let mut covered: HashSet<Cell> = skip.clone();
+
+    if options.wide_curves {
+        let mut i = 0;
+        while i + 2 < n {
+            let (a, b, c) = (path[i], path[i + 1], path[i + 2]);
+            let corner = (a.0 + c.0 - b.0, a.1 + c.1 - b.1);
+            let diagonal = (a.0 - c.0).abs() == 1 && (a.1 - c.1).abs() == 1;
+            let simple = [a, b, c]
+                .iter()
+                .all(|cell| visits[cell] == 1 && !skip.contains(cell))
+                && !visits.contains_key(&corner);
+
+            let curve = if diagonal && simple {
+                let enter = openings[&a] & !Dir::between(a, b).map_or(0, Dir::bit);
+                let leave = openings[&c] & !Dir::between(c, b).map_or(0, Dir::bit);
+                match (single_dir(enter), single_dir(leave)) {
+                    (Some(enter), Some(leave)) => wide_curve(a, c, enter, leave),
+                    _ => None,
+                }
+            } else {
+                None
+            };
+
+            match curve {
+                Some(placement) => {
+                    placements.push(placement);
+                    covered.extend([a, b, c]);
+                    i += 3;
+                }
+                None => i += 1,
+            }
+        }
+    }
+
+    for &cell in path {
+        if !covered.insert(cell) {
+            continue;
+        }
+        if let Some(placement) = placement_for_openings(cell, openings[&cell]) {
+            placements.push(placement);
+        }
+    }
+    placements
+}
+
+fn single_dir(mask: u8) -> Option<Dir> {
+    Dir::ALL.into_iter().find(|d| d.bit() == mask)
+}
+
+/// Slopes for the first and last `level` cells of an open path, climbing from the ground
+/// at the start and descending back to it at the end.
+fn ramps(path: &[Cell], level: i32) -> Result<Vec<(Cell, TilePlacement)>, String> {
+    let n = path.len();
+    let len = level as usize;
+    if n <= 2 * len {
+        return Err(format!(
+            "The road is {} cells long, too short for two {}-cell ramps",
+            n, len
+        ));
+    }
+
+    let mut slopes = Vec::new();
+    let up = Dir::between(path[0], path[1]);
+    let down = Dir::between(path[n - 2], path[n - 1]);
+    for k in 0..len {
+        let (start, end) = (path[k], path[n - 1 - k]);
+        if Dir::between(start, path[k + 1]) != up || Dir::between(path[n - 2 - k], end) != down {
+            return Err(format!(
+                "Ramps need {} straight cells at each end of the road",
+                len
+            ));
+        }
+        let (Some(up), Some(down)) = (up, down) else {
+            return Err("Ramps need a continuous road".to_string());
+        };
+        slopes.push((start, slope_placement(start, up, k as i32)));
+        slopes.push((end, slope_placement(end, down.opposite(), k as i32)));
+    }
+    Ok(slopes)
+}
+
+/// Rasterises a curve through `points` (`[x, z]`) into grid road pieces.
+pub fn spline_to_tiles(
+    points: &[[f32; 2]],
+    options: &SplineOptions,
+) -> Result<Vec<TilePlacement>, String> {
+    let samples = sample_curve(points, options.kind, options.closed);
+    let mut cells = rasterize(&samples);
+    if options.closed && cells.len() > 1 && cells.first() == cells.last() {
+        cells.pop();
+    }
+
+    let ramps = if options.ramps && options.level > 0 && !options.closed {
+        ramps(&cells, options.level)?
+    } else {
+        Vec::new()
+    };
+    let skip: HashSet<Cell> = ramps.iter().map(|(cell, _)| *cell).collect();
+
+    let mut placements: Vec<TilePlacement> = ramps.into_iter().map(|(_, p)| p).collect();
+    placements.extend(
+        cells_to_tiles(&cells, options, &skip)
+            .into_iter()
+            .map(|p| at_level(p, options.level)),
+    );
+    Ok(placements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(placements: &[TilePlacement]) -> Vec<Cell> {
+        placements
+            .iter()
+            .map(|p| cell_at(p.translation[0], p.translation[2]))
+            .collect()
+    }
+
+    #[test]
+    fn straight_catmull_rom() {
+        let tiles = spline_to_tiles(&[[0.0, 0.0], [4.0, 0.0]], &SplineOptions::default()).unwrap();
+        assert_eq!(cells(&tiles), [(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]);
+        assert!(tiles
+            .iter()
+            .all(|t| t.asset == "road-straight.glb" && t.yaw_degrees == 0.0));
+    }
+
+    #[test]
+    fn quarter_turn_is_a_contiguous_chain() {
+        let points = [[0.0, 0.0], [3.0, 0.0], [3.0, 3.0]];
+        let path = rasterize(&sample_curve(&points, CurveKind::CatmullRom, false));
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(3, 3)));
+        for pair in path.windows(2) {
+            let step = (pair[1].0 - pair[0].0).abs() + (pair[1].1 - pair[0].1).abs();
+            assert_eq!(step, 1, "gap or diagonal step in {:?}", path);
+        }
+        let unique: HashSet<Cell> = path.iter().copied().collect();
+        assert_eq!(unique.len(), path.len());
+
+        let options = SplineOptions {
+            wide_curves: false,
+            ..Default::default()
+        };
+        let tiles = spline_to_tiles(&points, &options).unwrap();
+        assert_eq!(cells(&tiles), path);
+        assert!(tiles.iter().any(|t| t.asset == "road-bend.glb"));
+    }
+
+    #[test]
+    fn degenerate_two_point_spline() {
+        let tiles = spline_to_tiles(&[[1.0, 1.0], [1.2, 0.9]], &SplineOptions::default()).unwrap();
+        assert!(tiles.is_empty());
+        assert!(spline_to_tiles(&[[1.0, 1.0]], &SplineOptions::default())
+            .unwrap()
+            .is_empty());
+    }
+}
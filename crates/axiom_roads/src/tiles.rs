@@ -0,0 +1,415 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Size of one grid cell in world units (see `road_engineer.md`).
+pub const GRID_SIZE: f32 = 1.0;
+
+/// Grid cell `(x, z)`. Cell centres sit on integer multiples of [`GRID_SIZE`].
+pub type Cell = (i32, i32);
+
+/// World position of the centre of `cell` at ground level.
+pub fn cell_center(cell: Cell) -> [f32; 3] {
+    [cell.0 as f32 * GRID_SIZE, 0.0, cell.1 as f32 * GRID_SIZE]
+}
+
+/// Cell containing the world point `(x, z)`.
+pub fn cell_at(x: f32, z: f32) -> Cell {
+    (
+        (x / GRID_SIZE).round() as i32,
+        (z / GRID_SIZE).round() as i32,
+    )
+}
+
+/// Cardinal direction on the XZ grid. North is -Z and East is +X.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dir {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Dir {
+    /// Clockwise order, starting at North.
+    pub const ALL: [Dir; 4] = [Dir::North, Dir::East, Dir::South, Dir::West];
+
+    fn index(self) -> usize {
+        match self {
+            Dir::North => 0,
+            Dir::East => 1,
+            Dir::South => 2,
+            Dir::West => 3,
+        }
+    }
+
+    /// Grid step `(dx, dz)` for one cell in this direction.
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Dir::North => (0, -1),
+            Dir::East => (1, 0),
+            Dir::South => (0, 1),
+            Dir::West => (-1, 0),
+        }
+    }
+
+    /// Bit of this direction in an opening mask (see [`dir_mask`]).
+    pub fn bit(self) -> u8 {
+        1 << self.index()
+    }
+
+    pub fn opposite(self) -> Dir {
+        Dir::ALL[(self.index() + 2) % 4]
+    }
+
+    /// Clockwise 90° seen from above (East -> South).
+    pub fn turn_right(self) -> Dir {
+        Dir::ALL[(self.index() + 1) % 4]
+    }
+
+    /// Counter-clockwise 90° seen from above (East -> North).
+    pub fn turn_left(self) -> Dir {
+        Dir::ALL[(self.index() + 3) % 4]
+    }
+
+    /// Applies `quarter_turns` steps of positive Bevy yaw (counter-clockwise, East -> North).
+    pub fn rotate(self, quarter_turns: u8) -> Dir {
+        let q = (quarter_turns % 4) as usize;
+        Dir::ALL[(self.index() + 4 - q) % 4]
+    }
+
+    /// Direction from `from` to the 4-neighbour `to`, `None` if they are not adjacent.
+    pub fn between(from: Cell, to: Cell) -> Option<Dir> {
+        let step = (to.0 - from.0, to.1 - from.1);
+        Dir::ALL.into_iter().find(|d| d.offset() == step)
+    }
+
+    /// Parses `north`/`n`/`-z`, `east`/`e`/`+x`, ... (case-insensitive).
+    pub fn parse(s: &str) -> Option<Dir> {
+        match s.trim().to_lowercase().as_str() {
+            "north" | "n" | "-z" => Some(Dir::North),
+            "east" | "e" | "+x" | "x" => Some(Dir::East),
+            "south" | "s" | "+z" | "z" => Some(Dir::South),
+            "west" | "w" | "-x" => Some(Dir::West),
+            _ => None,
+        }
+    }
+}
+
+/// Broad category of a road piece.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TileKind {
+    Straight,
+    Bend,
+    Curve,
+    TJunction,
+    Crossroad,
+    End,
+    Slope,
+    Roundabout,
+}
+
+/// A road opening on the boundary of a tile, in the tile's local (unrotated) frame.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Port {
+    /// Edge midpoint relative to the tile origin, `[x, y, z]`.
+    pub offset: [f32; 3],
+    /// Direction the road leaves the tile through this opening.
+    pub dir: Dir,
+}
+
+impl Port {
+    const fn new(x: f32, y: f32, z: f32, dir: Dir) -> Self {
+        Self {
+            offset: [x, y, z],
+            dir,
+        }
+    }
+}
+
+/// Connectivity of one road asset at rotation 0. Every pair of ports is connected through the tile.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TileSpec {
+    pub kind: TileKind,
+    pub ports: Vec<Port>,
+    /// Footprint in cells along `[x, z]` at rotation 0.
+    pub size: [u32; 2],
+}
+
+/// Strips directories and the extension: `"apps/axiom/resources/models/road-bend.glb"` -> `"road-bend"`.
+pub fn asset_stem(asset: &str) -> String {
+    Path::new(asset)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| asset.to_string())
+}
+
+/// Looks up the connectivity of a Kenney road asset by name or path.
+///
+/// The openings were measured from the meshes in `resources/models`: straights run West-East,
+/// `road-bend` joins West and South, `road-intersection` has its stem to the South, `road-end`
+/// opens to the East and every slope rises towards +X. Returns `None` for props and for pieces
+/// without a clean grid footprint (`road-split`, `road-square`).
+pub fn tile_spec(asset: &str) -> Option<TileSpec> {
+    use Dir::*;
+
+    let stem = asset_stem(asset);
+    let (kind, ports, size) = if stem.starts_with("road-straight-half") {
+        (
+            TileKind::Straight,
+            vec![
+                Port::new(-0.25, 0.0, 0.0, West),
+                Port::new(0.25, 0.0, 0.0, East),
+            ],
+            [1, 1],
+        )
+    } else if stem.starts_with("road-straight")
+        || stem.starts_with("road-crossing")
+        || stem.starts_with("road-side")
+        || stem.starts_with("road-driveway")
+        || stem.starts_with("road-bridge")
+    {
+        (
+            TileKind::Straight,
+            vec![
+                Port::new(-0.5, 0.0, 0.0, West),
+                Port::new(0.5, 0.0, 0.0, East),
+            ],
+            [1, 1],
+        )
+    } else if stem.starts_with("road-bend") {
+        (
+            TileKind::Bend,
+            vec![
+                Port::new(-0.5, 0.0, 0.0, West),
+                Port::new(0.0, 0.0, 0.5, South),
+            ],
+            [1, 1],
+        )
+    } else if stem.starts_with("road-crossroad") {
+        (
+            TileKind::Crossroad,
+            vec![
+                Port::new(0.0, 0.0, -0.5, North),
+                Port::new(0.5, 0.0, 0.0, East),
+                Port::new(0.0, 0.0, 0.5, South),
+                Port::new(-0.5, 0.0, 0.0, West),
+            ],
+            [1, 1],
+        )
+    } else if stem.starts_with("road-intersection") {
+        (
+            TileKind::TJunction,
+            vec![
+                Port::new(-0.5, 0.0, 0.0, West),
+                Port::new(0.5, 0.0, 0.0, East),
+                Port::new(0.0, 0.0, 0.5, South),
+            ],
+            [1, 1],
+        )
+    } else if stem.starts_with("road-end") {
+        (TileKind::End, vec![Port::new(0.5, 0.0, 0.0, East)], [1, 1])
+    } else if stem.starts_with("road-curve-intersection") {
+        // 2x2 piece: a straight along its northern row plus a wide arc down to the south-east cell.
+        (
+            TileKind::Curve,
+            vec![
+                Port::new(-1.0, 0.0, -0.5, West),
+                Port::new(1.0, 0.0, -0.5, East),
+                Port::new(0.5, 0.0, 1.0, South),
+            ],
+            [2, 2],
+        )
+    } else if stem.starts_with("road-curve") {
+        // 2x2 wide arc from the north-west cell to the south-east cell.
+        (
+            TileKind::Curve,
+            vec![
+                Port::new(-1.0, 0.0, -0.5, West),
+                Port::new(0.5, 0.0, 1.0, South),
+            ],
+            [2, 2],
+        )
+    } else if stem.starts_with("road-roundabout") {
+        (
+            TileKind::Roundabout,
+            vec![
+                Port::new(0.0, 0.0, -1.5, North),
+                Port::new(1.5, 0.0, 0.0, East),
+                Port::new(0.0, 0.0, 1.5, South),
+                Port::new(-1.5, 0.0, 0.0, West),
+            ],
+            [3, 3],
+        )
+    } else if stem.starts_with("road-slant-curve") || stem.starts_with("road-slant-flat-curve") {
+        (
+            TileKind::Slope,
+            vec![
+                Port::new(-1.0, 0.0, 0.0, West),
+                Port::new(1.0, 0.5, 0.0, East),
+            ],
+            [2, 1],
+        )
+    } else if stem.starts_with("road-slant-high") || stem.starts_with("road-slant-flat-high") {
+        (
+            TileKind::Slope,
+            vec![
+                Port::new(-0.5, 0.0, 0.0, West),
+                Port::new(0.5, 0.5, 0.0, East),
+            ],
+            [1, 1],
+        )
+    } else if stem.starts_with("road-slant") {
+        (
+            TileKind::Slope,
+            vec![
+                Port::new(-0.5, 0.0, 0.0, West),
+                Port::new(0.5, 0.25, 0.0, East),
+            ],
+            [1, 1],
+        )
+    } else {
+        return None;
+    };
+
+    Some(TileSpec { kind, ports, size })
+}
+
+/// Set of openings as a bitmask, so that opening sets can be compared and merged.
+pub fn dir_mask(dirs: &[Dir]) -> u8 {
+    dirs.iter().fold(0, |mask, d| mask | d.bit())
+}
+
+/// Inverse of [`dir_mask`], in clockwise order starting at North.
+pub fn mask_dirs(mask: u8) -> Vec<Dir> {
+    Dir::ALL
+        .into_iter()
+        .filter(|d| mask & d.bit() != 0)
+        .collect()
+}
+
+/// Quarter turns that make a 1x1 `asset` open exactly towards `openings`.
+pub fn rotation_for(asset: &str, openings: &[Dir]) -> Option<u8> {
+    let spec = tile_spec(asset)?;
+    if spec.size != [1, 1] {
+        return None;
+    }
+    let want = dir_mask(openings);
+    (0..4).find(|&q| spec.ports.iter().fold(0, |m, p| m | p.dir.rotate(q).bit()) == want)
+}
+
+/// Picks the flat road piece and quarter turns whose openings are exactly `openings`:
+/// one opening is a dead end, two a straight or bend, three a T-junction, four a crossroad.
+pub fn tile_for_openings(openings: &[Dir]) -> Option<(&'static str, u8)> {
+    let mask = dir_mask(openings);
+    let asset = match mask.count_ones() {
+        1 => "road-end.glb",
+        2 if mask == dir_mask(&[Dir::East, Dir::West])
+            || mask == dir_mask(&[Dir::North, Dir::South]) =>
+        {
+            "road-straight.glb"
+        }
+        2 => "road-bend.glb",
+        3 => "road-intersection.glb",
+        4 => "road-crossroad.glb",
+        _ => return None,
+    };
+    rotation_for(asset, openings).map(|q| (asset, q))
+}
+
+/// Flat road tile filling `cell` with exactly the openings in `mask`.
+pub fn placement_for_openings(cell: Cell, mask: u8) -> Option<TilePlacement> {
+    let (asset, quarter_turns) = tile_for_openings(&mask_dirs(mask))?;
+    Some(TilePlacement::new(
+        asset,
+        cell_center(cell),
+        quarter_turns as f32 * 90.0,
+    ))
+}
+
+/// Rotates a local `[x, y, z]` offset by `quarter_turns` of positive yaw.
+pub fn rotate_offset(offset: [f32; 3], quarter_turns: u8) -> [f32; 3] {
+    let [x, y, z] = offset;
+    match quarter_turns % 4 {
+        0 => [x, y, z],
+        1 => [z, y, -x],
+        2 => [-x, y, -z],
+        _ => [-z, y, x],
+    }
+}
+
+/// Snaps a yaw in degrees to the nearest quarter turn (0..=3).
+pub fn quarter_turns_from_degrees(yaw_degrees: f32) -> u8 {
+    ((yaw_degrees / 90.0).round() as i32).rem_euclid(4) as u8
+}
+
+/// A road asset placed in the world, as recorded by the editor or read back from the game.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TilePlacement {
+    /// Asset file name or path, e.g. `road-bend.glb`.
+    pub asset: String,
+    pub translation: [f32; 3],
+    /// Rotation around Y in degrees (the `y` of the editor's Euler rotation).
+    #[serde(default)]
+    pub yaw_degrees: f32,
+}
+
+impl TilePlacement {
+    pub fn new(asset: impl Into<String>, translation: [f32; 3], yaw_degrees: f32) -> Self {
+        Self {
+            asset: asset.into(),
+            translation,
+            yaw_degrees,
+        }
+    }
+
+    pub fn quarter_turns(&self) -> u8 {
+        quarter_turns_from_degrees(self.yaw_degrees)
+    }
+
+    pub fn spec(&self) -> Option<TileSpec> {
+        tile_spec(&self.asset)
+    }
+
+    /// Grid cells covered by this placement. Unknown assets are treated as 1x1.
+    pub fn cells(&self) -> Vec<Cell> {
+        let [w, d] = match self.spec() {
+            Some(spec) if self.quarter_turns() % 2 == 1 => [spec.size[1], spec.size[0]],
+            Some(spec) => spec.size,
+            None => [1, 1],
+        };
+        let [tx, _, tz] = self.translation;
+        let x0 = tx - (w as f32 - 1.0) * GRID_SIZE * 0.5;
+        let z0 = tz - (d as f32 - 1.0) * GRID_SIZE * 0.5;
+        let mut cells = Vec::new();
+        for i in 0..w {
+            for j in 0..d {
+                cells.push(cell_at(
+                    x0 + i as f32 * GRID_SIZE,
+                    z0 + j as f32 * GRID_SIZE,
+                ));
+            }
+        }
+        cells
+    }
+
+    /// World-space openings of this placement, or `None` if the asset is not a known road piece.
+    pub fn world_ports(&self) -> Option<Vec<Port>> {
+        let spec = self.spec()?;
+        let q = self.quarter_turns();
+        let [tx, ty, tz] = self.translation;
+        Some(
+            spec.ports
+                .iter()
+                .map(|p| {
+                    let [x, y, z] = rotate_offset(p.offset, q);
+                    Port {
+                        offset: [tx + x, ty + y, tz + z],
+                        dir: p.dir.rotate(q),
+                    }
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,448 @@
+use crate::elevation::{at_level, slope_placement};
+use crate::tiles::{dir_mask, placement_for_openings, Cell, Dir, TilePlacement, GRID_SIZE};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// What to do where the turtle splits the road.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BranchKind {
+    /// Keep going straight, leave a side road to the left.
+    Left,
+    /// Keep going straight, leave a side road to the right.
+    Right,
+    /// The road ends in a T: continue to the right, leave the left arm for later.
+    T,
+    /// Crossroad: keep going straight, leave both side roads for later.
+    Cross,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TurtleCommand {
+    Start {
+        cell: Cell,
+        heading: Option<Dir>,
+        level: Option<i32>,
+    },
+    Forward(u32),
+    /// Lay slopes going up this many levels, one cell per level.
+    Climb(u32),
+    Descend(u32),
+    TurnLeft,
+    TurnRight,
+    Branch(BranchKind),
+    End,
+    Reset,
+}
+
+impl TurtleCommand {
+    /// Parses commands such as `start at [0, 0] heading East level 1`, `forward 3`, `turn_right`,
+    /// `climb`, `descend 2`, `branch T`, `branch left` or `end`.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let cleaned: String = input
+            .to_lowercase()
+            .chars()
+            .map(|c| if "[](),_".contains(c) { ' ' } else { c })
+            .collect();
+        let tokens: Vec<&str> = cleaned.split_whitespace().collect();
+        let Some((&verb, rest)) = tokens.split_first() else {
+            return Err("Empty turtle command".to_string());
+        };
+
+        let command = match verb {
+            "start" | "goto" => {
+                // `level N` is read separately so it is not mistaken for a coordinate.
+                let level_at = rest.iter().position(|t| *t == "level");
+                let level = match level_at {
+                    Some(i) => Some(
+                        rest.get(i + 1)
+                            .and_then(|t| t.parse::<i32>().ok())
+                            .ok_or_else(|| format!("'{}': expected 'level <n>'", input))?,
+                    ),
+                    None => None,
+                };
+                let coords = &rest[..level_at.unwrap_or(rest.len())];
+                let numbers: Vec<i32> = coords
+                    .iter()
+                    .filter_map(|t| t.parse::<f32>().ok())
+                    .map(|n| (n / GRID_SIZE).round() as i32)
+                    .collect();
+                let [x, z] = numbers[..] else {
+                    return Err(format!("'{}': expected a cell like [x, z]", input));
+                };
+                let heading = coords.iter().filter_map(|t| Dir::parse(t)).next_back();
+                TurtleCommand::Start {
+                    cell: (x, z),
+                    heading,
+                    level,
+                }
+            }
+            "forward" | "fwd" | "f" => TurtleCommand::Forward(parse_count(input, rest)?),
+            "climb" | "up" => TurtleCommand::Climb(parse_count(input, rest)?),
+            "descend" | "down" => TurtleCommand::Descend(parse_count(input, rest)?),
+            "turn" => match rest.first() {
+                Some(&"left") => TurtleCommand::TurnLeft,
+                Some(&"right") => TurtleCommand::TurnRight,
+                _ => return Err(format!("'{}': expected turn_left or turn_right", input)),
+            },
+            "left" => TurtleCommand::TurnLeft,
+            "right" => TurtleCommand::TurnRight,
+            "branch" => match rest.first() {
+                Some(&"left") => TurtleCommand::Branch(BranchKind::Left),
+                Some(&"right") => TurtleCommand::Branch(BranchKind::Right),
+                Some(&"t") | None => TurtleCommand::Branch(BranchKind::T),
+                Some(&"cross") | Some(&"crossroad") | Some(&"x") => {
+                    TurtleCommand::Branch(BranchKind::Cross)
+                }
+                _ => {
+                    return Err(format!(
+                        "'{}': expected branch T, left, right or cross",
+                        input
+                    ))
+                }
+            },
+            "end" | "stop" => TurtleCommand::End,
+            "reset" => TurtleCommand::Reset,
+            _ => return Err(format!("Unknown turtle command '{}'", input)),
+        };
+        Ok(command)
+    }
+}
+
+fn parse_count(input: &str, rest: &[&str]) -> Result<u32, String> {
+    match rest.first() {
+        Some(n) => n
+            .parse::<u32>()
+            .map_err(|_| format!("'{}': expected a cell count", input)),
+        None => Ok(1),
+    }
+}
+
+/// One tile the turtle laid down.
+#[derive(Clone, Debug, Serialize)]
+pub struct TileChange {
+    pub cell: Cell,
+    pub level: i32,
+    pub placement: TilePlacement,
+    /// The cell already held a tile from this turtle; it has to be swapped for `placement`.
+    pub replaces_existing: bool,
+}
+
+/// A side road waiting to be drawn once the current one ends.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct PendingBranch {
+    pub cell: Cell,
+    pub heading: Dir,
+    pub level: i32,
+}
+
+/// Road drawing cursor. Tracks its cell, heading and the openings of every tile it placed,
+/// so crossing or rejoining its own road upgrades the tile there (straight -> T -> crossroad).
+#[derive(Clone, Debug)]
+pub struct Turtle {
+    pub cursor: Option<Cell>,
+    pub heading: Dir,
+    /// Road level of the cursor, see [`crate::elevation::LEVEL_HEIGHT`].
+    pub level: i32,
+    /// Opening the road entered the current cell through. `None` right after `start`.
+    entered_from: Option<Dir>,
+    pub branches: Vec<PendingBranch>,
+    cells: HashMap<(Cell, i32), u8>,
+    slopes: HashSet<(Cell, i32)>,
+}
+
+impl Default for Turtle {
+    fn default() -> Self {
+        Self {
+            cursor: None,
+            heading: Dir::East,
+            level: 0,
+            entered_from: None,
+            branches: Vec::new(),
+            cells: HashMap::new(),
+            slopes: HashSet::new(),
+        }
+    }
+}
+
+impl Turtle {
+    /// Runs one command. A command that fails partway, e.g. the third cell of `forward 5`,
+    /// leaves the turtle as it was, so the changes returned are exactly the ones it recorded.
+    pub fn apply(&mut self, command: &TurtleCommand) -> Result<Vec<TileChange>, String> {
+        let mut next = self.clone();
+        let changes = next.step(command)?;
+        *self = next;
+        Ok(changes)
+    }
+
+    fn step(&mut self, command: &TurtleCommand) -> Result<Vec<TileChange>, String> {
+        let mut changes = Vec::new();
+        match command {
+            TurtleCommand::Start {
+                cell,
+                heading,
+                level,
+            } => {
+                self.cursor = Some(*cell);
+                self.heading = heading.unwrap_or(self.heading);
+                self.level = level.unwrap_or(0);
+                self.entered_from = None;
+            }
+            TurtleCommand::Forward(count) => {
+                for _ in 0..*count {
+                    let heading = self.heading;
+                    changes.push(self.lay(&[heading])?);
+                    self.advance(heading);
+                }
+            }
+            TurtleCommand::Climb(count) => {
+                for _ in 0..*count {
+                    changes.push(self.slope(true)?);
+                }
+            }
+            TurtleCommand::Descend(count) => {
+                for _ in 0..*count {
+                    changes.push(self.slope(false)?);
+                }
+            }
+            TurtleCommand::TurnLeft | TurtleCommand::TurnRight => {
+                let heading = if *command == TurtleCommand::TurnLeft {
+                    self.heading.turn_left()
+                } else {
+                    self.heading.turn_right()
+                };
+                changes.push(self.lay(&[heading])?);
+                self.advance(heading);
+            }
+            TurtleCommand::Branch(kind) => {
+                let (left, right, ahead) = (
+                    self.heading.turn_left(),
+                    self.heading.turn_right(),
+                    self.heading,
+                );
+                let (openings, pending, next) = match kind {
+                    BranchKind::Left => (vec![ahead, left], vec![left], ahead),
+                    BranchKind::Right => (vec![ahead, right], vec![right], ahead),
+                    BranchKind::T => (vec![left, right], vec![left], right),
+                    BranchKind::Cross => (vec![ahead, left, right], vec![right, left], ahead),
+                };
+                changes.push(self.lay(&openings)?);
+                let cell = self.current()?;
+                for heading in pending {
+                    let (dx, dz) = heading.offset();
+                    self.branches.push(PendingBranch {
+                        cell: (cell.0 + dx, cell.1 + dz),
+                        heading,
+                        level: self.level,
+                    });
+                }
+                self.advance(next);
+            }
+            TurtleCommand::End => {
+                self.current()?;
+                if self.entered_from.is_none() {
+                    return Err("Nothing to end: no road has been drawn since 'start'".to_string());
+                }
+                changes.push(self.lay(&[])?);
+                match self.branches.pop() {
+                    Some(branch) => {
+                        self.cursor = Some(branch.cell);
+                        self.heading = branch.heading;
+                        self.level = branch.level;
+                        self.entered_from = Some(branch.heading.opposite());
+                    }
+                    None => {
+                        self.cursor = None;
+                        self.entered_from = None;
+                    }
+                }
+            }
+            TurtleCommand::Reset => *self = Turtle::default(),
+        }
+        Ok(changes)
+    }
+
+    fn current(&self) -> Result<Cell, String> {
+        self.cursor.ok_or_else(|| {
+            "The turtle has no position yet, use 'start at [x, z] heading <dir>'".to_string()
+        })
+    }
+
+    fn advance(&mut self, heading: Dir) {
+        if let Some((x, z)) = self.cursor {
+            let (dx, dz) = heading.offset();
+            self.cursor = Some((x + dx, z + dz));
+        }
+        self.heading = heading;
+        self.entered_from = Some(heading.opposite());
+    }
+
+    /// Places the tile for the current cell: the way the road came in plus `exits`,
+    /// merged with whatever the turtle already put there.
+    fn lay(&mut self, exits: &[Dir]) -> Result<TileChange, String> {
+        let cell = self.current()?;
+        let mut mask = dir_mask(exits);
+        if let Some(from) = self.entered_from {
+            mask |= from.bit();
+        }
+
+        let key = (cell, self.level);
+        if self.slopes.contains(&key) {
+            return Err(format!("Cell {:?} already holds a slope", cell));
+        }
+        let existing = self.cells.get(&key).copied().unwrap_or(0);
+        let merged = existing | mask;
+        let placement = placement_for_openings(cell, merged)
+            .ok_or_else(|| format!("No road piece fits the openings at {:?}", cell))?;
+        self.cells.insert(key, merged);
+
+        Ok(TileChange {
+            cell,
+            level: self.level,
+            placement: at_level(placement, self.level),
+            replaces_existing: existing != 0,
+        })
+    }
+
+    /// Lays a slope in the current cell, one level up or down along the heading, and moves on.
+    fn slope(&mut self, up: bool) -> Result<TileChange, String> {
+        let cell = self.current()?;
+        let heading = self.heading;
+        if self
+            .entered_from
+            .is_some_and(|from| from != heading.opposite())
+        {
+            return Err("Slopes are straight pieces, turn before or after climbing".to_string());
+        }
+        let base = if up { self.level } else { self.level - 1 };
+        if base < 0 {
+            return Err("Already at ground level".to_string());
+        }
+        let key = (cell, base);
+        if self.cells.contains_key(&key) || !self.slopes.insert(key) {
+            return Err(format!(
+                "Cell {:?} already has road on level {}",
+                cell, base
+            ));
+        }
+
+        let uphill = if up { heading } else { heading.opposite() };
+        let change = TileChange {
+            cell,
+            level: base,
+            placement: slope_placement(cell, uphill, base),
+            replaces_existing: false,
+        };
+        self.level = if up { self.level + 1 } else { self.level - 1 };
+        self.advance(heading);
+        Ok(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(turtle: &mut Turtle, commands: &[&str]) -> Vec<TileChange> {
+        commands
+            .iter()
+            .flat_map(|c| turtle.apply(&TurtleCommand::parse(c).unwrap()).unwrap())
+            .collect()
+    }
+
+    fn tiles(changes: &[TileChange]) -> Vec<(Cell, &str, f32)> {
+        changes
+            .iter()
+            .map(|c| (c.cell, c.placement.asset.as_str(), c.placement.yaw_degrees))
+            .collect()
+    }
+
+    #[test]
+    fn straight_run() {
+        let mut turtle = Turtle::default();
+        let changes = run(&mut turtle, &["start at [0, 0] heading east", "forward 3"]);
+        assert_eq!(
+            tiles(&changes),
+            [
+                ((0, 0), "road-end.glb", 0.0),
+                ((1, 0), "road-straight.glb", 0.0),
+                ((2, 0), "road-straight.glb", 0.0),
+            ]
+        );
+        assert_eq!(turtle.cursor, Some((3, 0)));
+        assert!(changes.iter().all(|c| !c.replaces_existing));
+    }
+
+    #[test]
+    fn turns_lay_bends() {
+        let mut turtle = Turtle::default();
+        let changes = run(
+            &mut turtle,
+            &["start at [0, 0] heading east", "forward", "turn_left"],
+        );
+        // West and North: the bend's West/South openings turned three times.
+        assert_eq!(changes[1].cell, (1, 0));
+        assert_eq!(changes[1].placement.asset, "road-bend.glb");
+        assert_eq!(changes[1].placement.yaw_degrees, 270.0);
+        assert_eq!(turtle.heading, Dir::North);
+        assert_eq!(turtle.cursor, Some((1, -1)));
+
+        let mut turtle = Turtle::default();
+        let changes = run(
+            &mut turtle,
+            &["start at [0, 0] heading east", "forward", "turn right"],
+        );
+        assert_eq!(changes[1].placement.asset, "road-bend.glb");
+        assert_eq!(changes[1].placement.yaw_degrees, 0.0);
+        assert_eq!(turtle.heading, Dir::South);
+        assert_eq!(turtle.cursor, Some((1, 1)));
+    }
+
+    #[test]
+    fn loop_closes_onto_its_start() {
+        let mut turtle = Turtle::default();
+        let changes = run(
+            &mut turtle,
+            &[
+                "start at [0, 0] heading east",
+                "forward",
+                "turn_right",
+                "turn_right",
+                "turn_right",
+                "turn_right",
+            ],
+        );
+        assert_eq!(changes.len(), 5);
+        let last = changes.last().unwrap();
+        assert_eq!(last.cell, (0, 0));
+        assert!(last.replaces_existing);
+        // The dead end becomes a bend joining South and East.
+        assert_eq!(last.placement.asset, "road-bend.glb");
+        assert_eq!(last.placement.yaw_degrees, 90.0);
+        assert!(changes[1..]
+            .iter()
+            .all(|c| c.placement.asset == "road-bend.glb"));
+        assert_eq!(turtle.cursor, Some((1, 0)));
+    }
+
+    #[test]
+    fn failed_step_leaves_the_turtle_untouched() {
+        let mut turtle = Turtle::default();
+        run(&mut turtle, &["start at [0, 0] heading east level 1"]);
+        // The first slope would reach the ground, the second has nowhere to go.
+        assert!(turtle.apply(&TurtleCommand::Descend(2)).is_err());
+        assert_eq!(turtle.cursor, Some((0, 0)));
+        assert_eq!(turtle.level, 1);
+
+        let changes = run(&mut turtle, &["descend 1"]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].cell, (0, 0));
+        assert_eq!(turtle.level, 0);
+    }
+
+    #[test]
+    fn commands_need_a_start() {
+        let mut turtle = Turtle::default();
+        assert!(turtle.apply(&TurtleCommand::Forward(1)).is_err());
+        assert!(TurtleCommand::parse("jump 3").is_err());
+    }
+}
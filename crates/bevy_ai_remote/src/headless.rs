@@ -0,0 +1,32 @@
+//! App setup without a window or GPU: `MinimalPlugins`, transforms, assets, scenes and the remote
+//! plugin.
+//! Used by the end-to-end tests and usable for CI or server-side builds of a game.
+use crate::{BevyAiRemotePlugin, RemoteCacheDir};
+use bevy::prelude::*;
+use bevy::scene::ScenePlugin;
+use bevy_remote::{http::RemoteHttpPlugin, RemotePlugin};
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
+
+/// Headless app serving BRP on `127.0.0.1:port`, with `asset_dir` as the asset folder (received
+/// files go to its `_remote_cache`). Drive it with `App::update`.
+pub fn headless_app(port: u16, asset_dir: impl Into<PathBuf>) -> App {
+    let asset_dir = asset_dir.into();
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+            file_path: asset_dir.to_string_lossy().into_owned(),
+            ..default()
+        },
+        TransformPlugin,
+        ScenePlugin,
+        RemotePlugin::default(),
+        RemoteHttpPlugin::default()
+            .with_address(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .with_port(port),
+    ));
+    app.insert_resource(RemoteCacheDir(asset_dir.join("_remote_cache")));
+    app.add_plugins(BevyAiRemotePlugin);
+    app
+}
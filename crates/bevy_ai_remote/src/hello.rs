@@ -0,0 +1,67 @@
+//! `axiom/hello`: what this build of the plugin offers, so the editor can adapt to it instead of
+//! assuming a matching version.
+use bevy::prelude::*;
+use bevy_remote::{error_codes, BrpError, BrpResult};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Bumped whenever the components or methods change in a way the editor has to know about.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Answer of `axiom/hello`.
+#[derive(Serialize, Debug, Clone)]
+pub struct Hello {
+    pub plugin: &'static str,
+    pub version: &'static str,
+    pub protocol: u32,
+    /// Type paths of the plugin's reflected components, as they must be named over BRP.
+    pub components: Vec<String>,
+    pub methods: Vec<&'static str>,
+    pub features: Vec<&'static str>,
+}
+
+/// Methods the plugin adds to BRP.
+pub const METHODS: &[&str] = &["axiom/hello", "axiom/nav_path"];
+
+/// Capabilities of this build:
+/// - `upload`: `AxiomRemoteAsset` files are written to the cache and models loaded.
+/// - `upload_dependencies`: models wait for their `dependencies` before loading.
+/// - `nav_path`: the `axiom/nav_path` method.
+/// - `primitives`: `AxiomPrimitive` entities get a mesh (`render` feature).
+/// - `nav_debug`: gizmo overlay of the nav graph (`render` feature).
+pub fn features() -> Vec<&'static str> {
+    let mut features = vec!["upload", "upload_dependencies", "nav_path"];
+    if cfg!(feature = "render") {
+        features.extend(["primitives", "nav_debug"]);
+    }
+    features
+}
+
+pub(crate) fn process_hello_request(
+    In(_params): In<Option<Value>>,
+    registry: Res<AppTypeRegistry>,
+) -> BrpResult {
+    let registry = registry.read();
+    let mut components: Vec<String> = registry
+        .iter()
+        .filter(|registration| registration.data::<ReflectComponent>().is_some())
+        .map(|registration| registration.type_info().type_path())
+        .filter(|path| path.starts_with("bevy_ai_remote::"))
+        .map(str::to_string)
+        .collect();
+    components.sort();
+
+    let hello = Hello {
+        plugin: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        protocol: PROTOCOL_VERSION,
+        components,
+        methods: METHODS.to_vec(),
+        features: features(),
+    };
+    serde_json::to_value(hello).map_err(|e| BrpError {
+        code: error_codes::INTERNAL_ERROR,
+        message: e.to_string(),
+        data: None,
+    })
+}
@@ -1,10 +1,29 @@
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use bevy::prelude::*;
-use bevy_remote::{http::RemoteHttpPlugin, RemotePlugin};
+use bevy_remote::{http::RemoteHttpPlugin, RemoteMethodSystemId, RemoteMethods, RemotePlugin};
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::PathBuf;
+
+pub mod headless;
+pub mod hello;
+pub mod nav;
+
+pub use headless::headless_app;
+pub use hello::PROTOCOL_VERSION;
+pub use nav::{RoadNavDebug, RoadNavGraph};
+
+/// Port the plugin serves BRP on unless the game added its own `RemoteHttpPlugin`.
+pub const DEFAULT_PORT: u16 = 15721;
+
+/// `AXIOM_BRP_PORT` when set (Axiom sets it for games it launches), else [`DEFAULT_PORT`].
+pub fn brp_port() -> u16 {
+    std::env::var("AXIOM_BRP_PORT")
+        .ok()
+        .and_then(|port| port.trim().parse().ok())
+        .unwrap_or(DEFAULT_PORT)
+}
 
 /// Component to tag entities that should be rendered as a primitive shape.
 #[derive(Component, Reflect, Default, Debug, Serialize, Deserialize)]
@@ -23,6 +42,33 @@ pub struct AxiomRemoteAsset {
     pub data_base64: String,
     // Optional sub-path relative to _remote_cache (e.g., "Textures")
     pub subdir: Option<String>,
+    /// Files a `.gltf` / `.glb` references (buffers, images), relative to the model's folder.
+    /// The scene is only loaded once all of them have been received.
+    #[reflect(default)]
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+/// Folder received files are written to. Models load from `_remote_cache/...` in the asset
+/// folder, so this must be the `_remote_cache` folder inside it (default `assets/_remote_cache`).
+#[derive(Resource, Debug, Clone)]
+pub struct RemoteCacheDir(pub PathBuf);
+
+impl Default for RemoteCacheDir {
+    fn default() -> Self {
+        Self(PathBuf::from("assets/_remote_cache"))
+    }
+}
+
+/// How long a model waits for its dependencies before it is loaded anyway.
+const DEPENDENCY_TIMEOUT_SECS: f32 = 10.0;
+
+/// A received model whose external files have not all arrived yet.
+#[derive(Component, Debug)]
+pub struct PendingRemoteScene {
+    pub scene_path: String,
+    pub missing: Vec<std::path::PathBuf>,
+    pub waited: f32,
 }
 
 /// Add this plugin to your Bevy app to enable remote control via Axiom.
@@ -42,21 +88,59 @@ impl Plugin for BevyAiRemotePlugin {
             app.add_plugins(
                 RemoteHttpPlugin::default()
                     .with_address("127.0.0.1".parse::<IpAddr>().unwrap())
-                    .with_port(15721),
+                    .with_port(brp_port()),
             );
         }
 
         // Register our custom components
         app.register_type::<AxiomPrimitive>();
         app.register_type::<AxiomRemoteAsset>();
+        app.register_type::<RoadNavDebug>();
+
+        app.init_resource::<RoadNavGraph>();
+        app.init_resource::<RoadNavDebug>();
+        app.init_resource::<RemoteCacheDir>();
+
+        // Custom BRP methods. Registered directly on `RemoteMethods` so this also
+        // works when the game added its own `RemotePlugin` before ours.
+        let hello = app
+            .world_mut()
+            .register_system(hello::process_hello_request);
+        let nav_path = app
+            .world_mut()
+            .register_system(nav::process_nav_path_request);
+        let mut methods = app.world_mut().resource_mut::<RemoteMethods>();
+        methods.insert("axiom/hello", RemoteMethodSystemId::Instant(hello));
+        methods.insert("axiom/nav_path", RemoteMethodSystemId::Instant(nav_path));
 
         // Add systems
-        app.add_systems(Update, (spawn_primitives, handle_remote_assets));
+        app.add_systems(Update, (handle_remote_assets, load_pending_scenes).chain());
+        app.add_systems(PostUpdate, nav::rebuild_nav_graph);
+
+        // Mesh/material hydration and gizmos need the render plugins; a headless app built
+        // with this feature simply skips them.
+        #[cfg(feature = "render")]
+        {
+            app.add_systems(
+                Update,
+                spawn_primitives.run_if(
+                    resource_exists::<Assets<Mesh>>
+                        .and(resource_exists::<Assets<StandardMaterial>>),
+                ),
+            );
+            app.add_systems(
+                PostUpdate,
+                nav::draw_nav_graph
+                    .after(nav::rebuild_nav_graph)
+                    .run_if(resource_exists::<bevy::gizmos::config::GizmoConfigStore>),
+            );
+        }
 
-        info!("Bevy AI Remote Plugin initialized on port 15721");
+        info!("Bevy AI Remote Plugin initialized");
     }
 }
 
+#[cfg(feature = "render")]
 fn spawn_primitives(
     mut commands: Commands,
     query: Query<(Entity, &AxiomPrimitive), Added<AxiomPrimitive>>,
@@ -89,6 +173,7 @@ fn handle_remote_assets(
     mut commands: Commands,
     query: Query<(Entity, &AxiomRemoteAsset), Added<AxiomRemoteAsset>>,
     asset_server: Res<AssetServer>,
+    cache_root: Res<RemoteCacheDir>,
 ) {
     for (entity, asset) in query.iter() {
         info!("Receiving remote asset: {}", asset.filename);
@@ -103,7 +188,7 @@ fn handle_remote_assets(
         };
 
         // 2. Ensure cache directory exists
-        let mut cache_dir = Path::new("assets/_remote_cache").to_path_buf();
+        let mut cache_dir = cache_root.0.clone();
 
         // Handle subdirectory if provided
         if let Some(sub) = &asset.subdir {
@@ -169,6 +254,26 @@ fn handle_remote_assets(
         // Only load as Scene if it's a model file. If it's a texture, we just write it and stop.
         if asset.filename.ends_with(".glb") || asset.filename.ends_with(".gltf") {
             let scene_path = format!("{}#Scene0", relative_path_str);
+            let missing: Vec<_> = asset
+                .dependencies
+                .iter()
+                .map(|dep| cache_dir.join(dep))
+                .filter(|path| !path.exists())
+                .collect();
+            if !missing.is_empty() {
+                // Buffers and textures are uploaded as separate entities and may still be on their way.
+                info!(
+                    "Waiting for {} dependencies of {} before loading",
+                    missing.len(),
+                    asset.filename
+                );
+                commands.entity(entity).insert(PendingRemoteScene {
+                    scene_path,
+                    missing,
+                    waited: 0.0,
+                });
+                continue;
+            }
             info!("Loading scene from: {}", scene_path);
             let scene_handle: Handle<Scene> = asset_server.load(scene_path);
             // 5. Attach SceneRoot to the entity
@@ -182,3 +287,34 @@ fn handle_remote_assets(
         }
     }
 }
+
+/// Loads models from `PendingRemoteScene` once their dependencies are on disk, or after
+/// `DEPENDENCY_TIMEOUT_SECS` with a warning (the scene then loads with whatever is missing).
+fn load_pending_scenes(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut PendingRemoteScene)>,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+) {
+    for (entity, mut pending) in query.iter_mut() {
+        pending.missing.retain(|path| !path.exists());
+        pending.waited += time.delta_secs();
+
+        if !pending.missing.is_empty() {
+            if pending.waited < DEPENDENCY_TIMEOUT_SECS {
+                continue;
+            }
+            warn!(
+                "Gave up waiting for {:?}, loading {} anyway",
+                pending.missing, pending.scene_path
+            );
+        }
+
+        info!("Loading scene from: {}", pending.scene_path);
+        let scene_handle: Handle<Scene> = asset_server.load(pending.scene_path.clone());
+        commands
+            .entity(entity)
+            .remove::<PendingRemoteScene>()
+            .insert(SceneRoot(scene_handle));
+    }
+}
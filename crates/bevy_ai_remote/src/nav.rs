@@ -0,0 +1,125 @@
+use crate::AxiomRemoteAsset;
+use axiom_roads::{NavGraph, NavPath, TilePlacement};
+use bevy::prelude::*;
+use bevy_remote::{builtin_methods::parse_some, error_codes, BrpError, BrpResult};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Lane graph of the road tiles currently in the world.
+/// Rebuilt automatically whenever a remote asset is spawned, moved or despawned.
+#[derive(Resource, Default, Debug, Deref)]
+pub struct RoadNavGraph(pub NavGraph);
+
+impl RoadNavGraph {
+    /// A* between the lane nodes closest to `start` and `goal`.
+    pub fn find_path(&self, start: Vec3, goal: Vec3) -> Option<NavPath> {
+        self.0.find_path(start.to_array(), goal.to_array())
+    }
+}
+
+/// Toggles the gizmo overlay of the nav graph.
+/// Reflected so the editor can flip it with `world.insert_resources`.
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource)]
+pub struct RoadNavDebug {
+    pub enabled: bool,
+}
+
+type ChangedTiles = (
+    With<AxiomRemoteAsset>,
+    Or<(Added<AxiomRemoteAsset>, Changed<Transform>)>,
+);
+
+pub(crate) fn rebuild_nav_graph(
+    changed: Query<(), ChangedTiles>,
+    mut removed: RemovedComponents<AxiomRemoteAsset>,
+    tiles: Query<(&AxiomRemoteAsset, &Transform)>,
+    mut graph: ResMut<RoadNavGraph>,
+) {
+    let any_removed = removed.read().count() > 0;
+    if changed.is_empty() && !any_removed {
+        return;
+    }
+
+    let placements: Vec<TilePlacement> = tiles
+        .iter()
+        .map(|(asset, transform)| {
+            let (yaw, _, _) = transform.rotation.to_euler(EulerRot::YXZ);
+            TilePlacement::new(
+                asset.filename.clone(),
+                transform.translation.to_array(),
+                yaw.to_degrees(),
+            )
+        })
+        .collect();
+
+    graph.0 = NavGraph::build(&placements);
+    debug!(
+        "Rebuilt road nav graph: {} nodes, {} edges",
+        graph.nodes.len(),
+        graph.edges.len()
+    );
+}
+
+#[cfg(feature = "render")]
+pub(crate) fn draw_nav_graph(
+    debug: Res<RoadNavDebug>,
+    graph: Res<RoadNavGraph>,
+    mut gizmos: Gizmos,
+) {
+    if !debug.enabled {
+        return;
+    }
+
+    // Lift the overlay slightly so it is not hidden inside the road surface.
+    let lift = Vec3::Y * 0.05;
+    for edge in &graph.edges {
+        let from = Vec3::from_array(graph.nodes[edge.from].position) + lift;
+        let to = Vec3::from_array(graph.nodes[edge.to].position) + lift;
+        gizmos.line(from, to, Color::srgb(0.2, 0.8, 1.0));
+    }
+
+    for node in &graph.nodes {
+        let color = if node.tiles == 1 {
+            Color::srgb(1.0, 0.2, 0.2)
+        } else {
+            Color::srgb(0.2, 1.0, 0.4)
+        };
+        gizmos.sphere(Vec3::from_array(node.position) + lift, 0.05, color);
+    }
+}
+
+#[derive(Deserialize)]
+struct NavPathParams {
+    start: [f32; 3],
+    goal: [f32; 3],
+}
+
+/// Handles `axiom/nav_path`: `{ "start": [x, y, z], "goal": [x, y, z] }`.
+pub(crate) fn process_nav_path_request(
+    In(params): In<Option<Value>>,
+    graph: Res<RoadNavGraph>,
+) -> BrpResult {
+    let NavPathParams { start, goal } = parse_some(params)?;
+
+    if graph.is_empty() {
+        return Err(BrpError {
+            code: error_codes::INTERNAL_ERROR,
+            message: "No road tiles placed, nav graph is empty".to_string(),
+            data: None,
+        });
+    }
+
+    match graph.0.find_path(start, goal) {
+        Some(path) => serde_json::to_value(path).map_err(|e| BrpError {
+            code: error_codes::INTERNAL_ERROR,
+            message: e.to_string(),
+            data: None,
+        }),
+        None => Err(BrpError {
+            code: error_codes::INTERNAL_ERROR,
+            message: "No drivable path between the given points".to_string(),
+            data: None,
+        }),
+    }
+}
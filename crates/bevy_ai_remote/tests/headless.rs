@@ -0,0 +1,271 @@
+//! End-to-end tests: a headless app serves BRP on an ephemeral port, requests go over HTTP and
+//! the assertions look at the resulting world.
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use bevy::prelude::*;
+use bevy_ai_remote::{headless_app, PendingRemoteScene, PROTOCOL_VERSION};
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+const TRANSFORM: &str = "bevy_transform::components::transform::Transform";
+const SCENE_ROOT: &str = "bevy_scene::components::SceneRoot";
+const REMOTE_ASSET: &str = "bevy_ai_remote::AxiomRemoteAsset";
+
+struct Game {
+    app: App,
+    port: u16,
+    assets: PathBuf,
+}
+
+impl Game {
+    fn start() -> Game {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let assets = std::env::temp_dir().join(format!(
+            "bevy_ai_remote_test_{}_{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::SeqCst)
+        ));
+        let port = TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .expect("free port")
+            .port();
+        let mut app = headless_app(port, &assets);
+        app.update();
+        Game { app, port, assets }
+    }
+
+    /// Sends one BRP request from another thread while this one keeps updating the app.
+    fn call(&mut self, method: &str, params: Value) -> Value {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let port = self.port;
+        let client = std::thread::spawn(move || post(port, &body.to_string()));
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !client.is_finished() {
+            assert!(Instant::now() < deadline, "{} timed out", method);
+            self.app.update();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        client.join().unwrap()
+    }
+
+    fn result(&mut self, method: &str, params: Value) -> Value {
+        let response = self.call(method, params);
+        assert!(response.get("error").is_none(), "{}: {}", method, response);
+        response["result"].clone()
+    }
+
+    fn spawn_upload(&mut self, filename: &str, subdir: Option<&str>, dependencies: &[&str]) -> u64 {
+        let result = self.result(
+            "world.spawn_entity",
+            json!({
+                "components": {
+                    REMOTE_ASSET: {
+                        "filename": filename,
+                        "data_base64": BASE64.encode(format!("contents of {}", filename)),
+                        "subdir": subdir,
+                        "dependencies": dependencies
+                    },
+                    TRANSFORM: { "translation": [0.0, 0.0, 0.0], "rotation": [0.0, 0.0, 0.0, 1.0], "scale": [1.0, 1.0, 1.0] }
+                }
+            }),
+        );
+        result["entity"].as_u64().unwrap()
+    }
+
+    fn entity(&self, bits: u64) -> Entity {
+        Entity::from_bits(bits)
+    }
+
+    fn run_frames(&mut self, n: usize) {
+        for _ in 0..n {
+            self.app.update();
+        }
+    }
+}
+
+impl Drop for Game {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.assets);
+    }
+}
+
+/// Minimal HTTP/1.1 POST, retried until the server has started listening.
+fn post(port: u16, body: &str) -> Value {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut stream = loop {
+        match TcpStream::connect(("127.0.0.1", port)) {
+            Ok(stream) => break stream,
+            Err(e) if Instant::now() > deadline => panic!("could not connect: {}", e),
+            Err(_) => std::thread::sleep(Duration::from_millis(10)),
+        }
+    };
+    write!(
+        stream,
+        "POST / HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").expect("HTTP response");
+    // The server may answer with a chunked body: the JSON sits between the size lines.
+    let start = body.find('{').expect("JSON body");
+    let end = body.rfind('}').expect("JSON body");
+    serde_json::from_str(&body[start..=end]).unwrap()
+}
+
+#[test]
+fn spawns_queries_moves_and_despawns_entities() {
+    let mut game = Game::start();
+    let entity = game.result(
+        "world.spawn_entity",
+        json!({ "components": { TRANSFORM: { "translation": [1.0, 2.0, 3.0], "rotation": [0.0, 0.0, 0.0, 1.0], "scale": [1.0, 1.0, 1.0] } } }),
+    )["entity"]
+        .as_u64()
+        .unwrap();
+
+    let rows = game.result(
+        "world.query",
+        json!({ "data": { "components": [TRANSFORM] } }),
+    );
+    let row = rows
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["entity"] == entity)
+        .expect("spawned entity in query");
+    assert_eq!(
+        row["components"][TRANSFORM]["translation"],
+        json!([1.0, 2.0, 3.0])
+    );
+
+    game.result(
+        "world.insert_components",
+        json!({ "entity": entity, "components": { TRANSFORM: { "translation": [5.0, 0.0, 0.0], "rotation": [0.0, 0.0, 0.0, 1.0], "scale": [2.0, 2.0, 2.0] } } }),
+    );
+    let transform = game
+        .app
+        .world()
+        .get::<Transform>(game.entity(entity))
+        .unwrap();
+    assert_eq!(transform.translation, Vec3::new(5.0, 0.0, 0.0));
+    assert_eq!(transform.scale, Vec3::splat(2.0));
+
+    game.result("world.despawn_entity", json!({ "entity": entity }));
+    assert!(game.app.world().get_entity(game.entity(entity)).is_err());
+    let response = game.call("world.despawn_entity", json!({ "entity": entity }));
+    assert_eq!(response["error"]["code"], json!(-23401));
+}
+
+#[test]
+fn uploaded_model_is_cached_and_gets_a_scene_root() {
+    let mut game = Game::start();
+    let entity = game.spawn_upload("crate.glb", None, &[]);
+    game.run_frames(2);
+
+    let cached = game.assets.join("_remote_cache/crate.glb");
+    assert_eq!(
+        std::fs::read_to_string(cached).unwrap(),
+        "contents of crate.glb"
+    );
+    let rows = game.result(
+        "world.query",
+        json!({ "data": { "components": [] }, "filter": { "with": [SCENE_ROOT] } }),
+    );
+    assert_eq!(rows.as_array().unwrap().len(), 1);
+    assert_eq!(rows[0]["entity"], json!(entity));
+}
+
+#[test]
+fn textures_are_cached_without_becoming_scenes() {
+    let mut game = Game::start();
+    let entity = game.spawn_upload("colormap.png", Some("Textures"), &[]);
+    game.run_frames(2);
+
+    assert!(game
+        .assets
+        .join("_remote_cache/Textures/colormap.png")
+        .exists());
+    let world = game.app.world();
+    let id = game.entity(entity);
+    assert!(world.get::<SceneRoot>(id).is_none());
+    assert!(world.get::<bevy_ai_remote::AxiomRemoteAsset>(id).is_none());
+}
+
+#[test]
+fn model_waits_for_its_dependencies() {
+    let mut game = Game::start();
+    let model = game.spawn_upload("road.gltf", None, &["Textures/colormap.png"]);
+    game.run_frames(2);
+    let id = game.entity(model);
+    assert!(game.app.world().get::<PendingRemoteScene>(id).is_some());
+    assert!(game.app.world().get::<SceneRoot>(id).is_none());
+
+    game.spawn_upload("colormap.png", Some("Textures"), &[]);
+    game.run_frames(3);
+    assert!(game.app.world().get::<PendingRemoteScene>(id).is_none());
+    assert!(game.app.world().get::<SceneRoot>(id).is_some());
+}
+
+#[test]
+fn hello_reports_protocol_components_and_features() {
+    let mut game = Game::start();
+    let hello = game.result("axiom/hello", Value::Null);
+    assert_eq!(hello["plugin"], json!("bevy_ai_remote"));
+    assert_eq!(hello["protocol"], json!(PROTOCOL_VERSION));
+    let components = hello["components"].as_array().unwrap();
+    assert!(components.contains(&json!(REMOTE_ASSET)), "{}", hello);
+    assert!(components.contains(&json!("bevy_ai_remote::AxiomPrimitive")));
+    let features = hello["features"].as_array().unwrap();
+    assert!(features.contains(&json!("upload")));
+    assert!(features.contains(&json!("nav_path")));
+    assert_eq!(
+        features.contains(&json!("primitives")),
+        cfg!(feature = "render"),
+        "{}",
+        hello
+    );
+    assert!(hello["methods"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("axiom/nav_path")));
+}
+
+#[test]
+fn nav_path_follows_uploaded_road_tiles() {
+    let mut game = Game::start();
+    let response = game.call(
+        "axiom/nav_path",
+        json!({ "start": [0, 0, 0], "goal": [2, 0, 0] }),
+    );
+    assert!(response["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("empty"));
+
+    for x in 0..3 {
+        let entity = game.spawn_upload("road-straight.glb", None, &[]);
+        game.result(
+            "world.insert_components",
+            // Unrotated straights run west-east, so three in a row make one road along x.
+            json!({ "entity": entity, "components": { TRANSFORM: { "translation": [x as f32, 0.0, 0.0], "rotation": [0.0, 0.0, 0.0, 1.0], "scale": [1.0, 1.0, 1.0] } } }),
+        );
+    }
+    game.run_frames(2);
+
+    let path = game.result(
+        "axiom/nav_path",
+        json!({ "start": [0, 0, 0], "goal": [2, 0, 0] }),
+    );
+    // Nodes sit on tile edges, so the path runs from the west edge of the first tile to the
+    // east edge of the middle one, the node nearest the goal.
+    assert_eq!(
+        path["waypoints"],
+        json!([[-0.5, 0.0, 0.0], [0.5, 0.0, 0.0], [1.5, 0.0, 0.0]])
+    );
+    assert_eq!(path["length"], json!(2.0));
+}